@@ -1,6 +1,6 @@
 #![allow(dead_code, unused)]
 
-use unity_mirror_rs::mirror::NetworkManager;
+use unity_mirror_rs::mirror::{NetworkManager, NetworkServer};
 use unity_mirror_rs::unity_engine::PlayerLooper;
 
 mod backend_metadata;
@@ -45,5 +45,15 @@ fn init_logger() {
 
 fn main() {
     NetworkManager::init("Assets/Prefabs/NetworkRoomManager.prefab");
+
+    // 子弹频繁生成和销毁，使用对象池复用
+    if let Some(asset_id) = NetworkServer.object_pool.register_prefab("Assets/Prefabs/Projectile.prefab", 64) {
+        NetworkServer.object_pool.prewarm(asset_id, 16);
+    }
+
+    // SIGINT / SIGTERM 时优雅关闭
+    let stop_handle = PlayerLooper::stop_handle();
+    ctrlc::set_handler(move || stop_handle.stop()).expect("Error setting signal handler");
+
     PlayerLooper::run();
 }
@@ -1,8 +1,8 @@
 use unity_mirror_rs::macro_namespace::*;
 use unity_mirror_rs::macro_network_behaviour::*;
 use unity_mirror_rs::metadata_settings::MetadataNetworkBehaviourWrapper;
-use unity_mirror_rs::mirror::{NetworkServer, NetworkTime, TNetworkBehaviour};
-use unity_mirror_rs::unity_engine::{GameObject, MonoBehaviour};
+use unity_mirror_rs::mirror::{NetworkServer, TNetworkBehaviour};
+use unity_mirror_rs::unity_engine::{GameObject, MonoBehaviour, Time};
 
 #[namespace]
 #[network_behaviour(
@@ -23,13 +23,13 @@ impl TNetworkBehaviour for Projectile {
         projectile
     }
     fn on_start_server(&mut self) {
-        self.z = NetworkTime.local_time();
+        self.z = Time::time();
     }
 }
 
 impl MonoBehaviour for Projectile {
     fn update(&mut self) {
-        if NetworkTime.local_time() - self.z >= 2f64 {
+        if Time::time() - self.z >= 2f64 {
             NetworkServer::destroy(self.game_object.clone())
         }
     }
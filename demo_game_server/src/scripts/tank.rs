@@ -13,9 +13,9 @@ use unity_mirror_rs::unity_engine::{GameObject, MonoBehaviour, Transform};
     metadata(MetadataTank)
 )]
 pub struct Tank {
-    turret: Transform,
+    turret: RevelWeak<Transform>,
     projectile_prefab: String,
-    projectile_mount: Transform,
+    projectile_mount: RevelWeak<Transform>,
     #[sync_var]
     health: i32,
     #[sync_obj]
@@ -27,13 +27,23 @@ impl TankOnChangeCallback for Tank {}
 impl MonoBehaviour for Tank {}
 
 impl TNetworkBehaviour for Tank {
-    fn new(_weak_game_object: RevelWeak<GameObject>, metadata: &MetadataNetworkBehaviourWrapper) -> Self
+    fn new(weak_game_object: RevelWeak<GameObject>, metadata: &MetadataNetworkBehaviourWrapper) -> Self
     where
         Self: Sized,
     {
         let mut tank = Self::default();
         {
             let config = metadata.get::<MetadataTank>();
+            if let Some(game_object) = weak_game_object.get() {
+                if let Some(turret) = game_object.find_transform(&config.turret.instance_id) {
+                    tank.turret = turret;
+                }
+                if let Some(projectile_mount) =
+                    game_object.find_transform(&config.projectile_mount.instance_id)
+                {
+                    tank.projectile_mount = projectile_mount;
+                }
+            }
             tank.set_health(config.health);
             tank.projectile_prefab = config.projectile_prefab.asset_path.clone();
         }
@@ -45,10 +55,15 @@ impl Tank {
     #[command(Tank)]
     fn cmd_fire(&mut self, _pos: Vec<f32>, _rot: Vec<f32>) {
         if let Some(prefab) = Metadata::get_prefab("Assets/Prefabs/Projectile.prefab") {
-            let mut obj = GameObject::instantiate(&prefab);
-            obj.transform.local_position = Vector3::new(_pos[0], _pos[1], _pos[2]);
-            obj.transform.local_rotation = Quaternion::new(_rot[3], _rot[0], _rot[1], _rot[2]);
-            NetworkServer::spawn(obj.downgrade());
+            let scene_handle = self.game_object.get().map_or(0, |game_object| game_object.scene_handle());
+            if let Some(obj) = NetworkServer.object_pool.get(
+                prefab.asset_id,
+                Vector3::new(_pos[0], _pos[1], _pos[2]),
+                Quaternion::new(_rot[3], _rot[0], _rot[1], _rot[2]),
+                scene_handle,
+            ) {
+                NetworkServer::spawn(obj.downgrade());
+            }
         }
 
         self.u32_list.add(1);
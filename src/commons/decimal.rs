@@ -0,0 +1,73 @@
+use crate::macro_namespace::*;
+use crate::mirror::{DataTypeDeserializer, DataTypeSerializer, NetworkReader, NetworkWriter, Pod};
+
+/// System.Decimal 的内存布局 (.NET Core)，96 位整数部分加符号和小数位数
+#[namespace(prefix = "System")]
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Decimal {
+    // 16..24 位为小数位数，31 位为符号
+    flags: u32,
+    hi: u32,
+    lo: u64,
+}
+
+unsafe impl Pod for Decimal {}
+
+impl Decimal {
+    pub const MAX_SCALE: u8 = 28;
+    const MAX_MANTISSA: u128 = (1 << 96) - 1;
+
+    /// mantissa / 10^scale，超出 96 位或 scale 大于 28 时返回 None
+    pub fn new(mantissa: i128, scale: u8) -> Option<Self> {
+        let magnitude = mantissa.unsigned_abs();
+        if magnitude > Self::MAX_MANTISSA || scale > Self::MAX_SCALE {
+            return None;
+        }
+        let sign = if mantissa < 0 { 1 << 31 } else { 0 };
+        Some(Self {
+            flags: sign | ((scale as u32) << 16),
+            hi: (magnitude >> 64) as u32,
+            lo: magnitude as u64,
+        })
+    }
+
+    pub fn mantissa(&self) -> i128 {
+        let magnitude = ((self.hi as i128) << 64) | self.lo as i128;
+        if self.is_negative() {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+
+    pub fn scale(&self) -> u8 {
+        ((self.flags >> 16) & 0xff) as u8
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.flags & (1 << 31) != 0
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.mantissa() as f64 / 10f64.powi(self.scale() as i32)
+    }
+}
+
+impl DataTypeSerializer for Decimal {
+    fn serialize(&self, writer: &mut NetworkWriter) {
+        writer.write_blittable(*self);
+    }
+}
+
+impl DataTypeDeserializer for Decimal {
+    fn deserialize(reader: &mut NetworkReader) -> Self {
+        let value = reader.read_blittable::<Decimal>();
+        // flags 中除小数位数和符号外必须为 0
+        if value.scale() > Self::MAX_SCALE || value.flags & 0x7f00_ffff != 0 {
+            reader.fail(crate::mirror::ReadError::InvalidValue { type_name: "Decimal" });
+            return Self::default();
+        }
+        value
+    }
+}
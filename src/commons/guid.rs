@@ -0,0 +1,73 @@
+use crate::macro_namespace::*;
+use crate::mirror::{DataTypeDeserializer, DataTypeSerializer, NetworkReader, NetworkWriter};
+use std::fmt::{Display, Formatter};
+
+/// System.Guid，字节顺序与 Guid.ToByteArray 相同 (前三段为小端)
+#[namespace(prefix = "System")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Guid(pub [u8; 16]);
+
+impl Guid {
+    pub const EMPTY: Guid = Guid([0; 16]);
+
+    pub fn new_v4() -> Self {
+        let mut bytes: [u8; 16] = rand::random();
+        // 版本 4，变体 RFC 4122
+        bytes[7] = (bytes[7] & 0x0f) | 0x40;
+        bytes[8] = (bytes[8] & 0x3f) | 0x80;
+        Self(bytes)
+    }
+
+    /// 解析 "xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx"
+    pub fn parse(value: &str) -> Option<Self> {
+        let hex = value.trim_matches(|c| c == '{' || c == '}').replace('-', "");
+        if hex.len() != 32 || !hex.is_ascii() {
+            return None;
+        }
+        let mut text = [0u8; 16];
+        for (index, byte) in text.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[index * 2..index * 2 + 2], 16).ok()?;
+        }
+        Some(Self::from_text_order(text))
+    }
+
+    // 文本顺序与 ToByteArray 顺序互转，前三段字节序相反
+    fn from_text_order(mut bytes: [u8; 16]) -> Self {
+        bytes[0..4].reverse();
+        bytes[4..6].reverse();
+        bytes[6..8].reverse();
+        Self(bytes)
+    }
+
+    fn to_text_order(self) -> [u8; 16] {
+        Self::from_text_order(self.0).0
+    }
+}
+
+impl Display for Guid {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let b = self.to_text_order();
+        write!(
+            f,
+            "{:02x}{:02x}{:02x}{:02x}-{:02x}{:02x}-{:02x}{:02x}-{:02x}{:02x}-{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}",
+            b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7], b[8], b[9], b[10], b[11], b[12], b[13], b[14], b[15]
+        )
+    }
+}
+
+// 直接写 16 字节，没有长度前缀
+impl DataTypeSerializer for Guid {
+    fn serialize(&self, writer: &mut NetworkWriter) {
+        writer.write_slice(&self.0, 0, 16);
+    }
+}
+
+impl DataTypeDeserializer for Guid {
+    fn deserialize(reader: &mut NetworkReader) -> Self {
+        let bytes = reader.read_slice(16);
+        match <[u8; 16]>::try_from(bytes) {
+            Ok(bytes) => Self(bytes),
+            Err(_) => Self::EMPTY,
+        }
+    }
+}
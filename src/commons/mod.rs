@@ -8,6 +8,16 @@ pub use revel_arc::*;
 mod revel_weak;
 pub use revel_weak::*;
 
+mod guid;
+pub use guid::*;
+
+mod decimal;
+pub use decimal::*;
+
+mod thread_static;
+pub use thread_static::*;
+pub(crate) use thread_static::thread_static;
+
 pub mod string_case;
 pub mod to_hex_string;
 pub mod trace;
@@ -1,3 +1,7 @@
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` has no C# full name",
+    note = "add #[namespace(prefix = \"...\")] to `{Self}`, types deriving NetworkSerialize need it when used as Rpc / Command arguments"
+)]
 pub trait Object {
     fn get_full_name() -> &'static str
     where
@@ -0,0 +1,66 @@
+use std::cell::UnsafeCell;
+use std::ops::{Deref, DerefMut};
+use std::thread::LocalKey;
+
+/// 线程内的静态数据，每个线程独立一份，首次访问时初始化
+/// 服务器状态 (NetworkServer、WorldManager 等) 都以此声明，
+/// 同一进程中的多个服务器实例运行在各自的线程上，互不干扰
+pub struct ThreadStatic<T: 'static>(&'static LocalKey<UnsafeCell<T>>);
+
+impl<T> ThreadStatic<T> {
+    pub const fn new(key: &'static LocalKey<UnsafeCell<T>>) -> Self {
+        Self(key)
+    }
+}
+
+impl<T> Deref for ThreadStatic<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.0.with(|cell| cell.get()) }
+    }
+}
+
+impl<T> DerefMut for ThreadStatic<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { &mut *self.0.with(|cell| cell.get()) }
+    }
+}
+
+/// 声明线程内静态数据，用法与 `static mut X: Lazy<T>` 相同
+macro_rules! thread_static {
+    ($($(#[$attr:meta])* $vis:vis static mut $name:ident: $ty:ty = $init:expr;)+) => {
+        $(
+            $(#[$attr])*
+            $vis static mut $name: $crate::commons::ThreadStatic<$ty> = {
+                thread_local! {
+                    static LOCAL: std::cell::UnsafeCell<$ty> = std::cell::UnsafeCell::new($init);
+                }
+                $crate::commons::ThreadStatic::new(&LOCAL)
+            };
+        )+
+    };
+}
+pub(crate) use thread_static;
+
+#[cfg(test)]
+mod tests {
+    thread_static! {
+        static mut COUNTER: u32 = 0;
+    }
+
+    #[test]
+    fn test_isolated_per_thread() {
+        #[allow(static_mut_refs)]
+        unsafe {
+            *COUNTER += 1;
+            std::thread::spawn(|| {
+                assert_eq!(*COUNTER, 0);
+                *COUNTER = 10;
+            })
+            .join()
+            .unwrap();
+            assert_eq!(*COUNTER, 1);
+        }
+    }
+}
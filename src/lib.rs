@@ -32,11 +32,31 @@ pub mod macro_namespace {
 // use unity_mirror_rs::macro_network_message::*;
 // use crate::macro_network_message::*;
 pub mod macro_network_message {
-    pub use super::mirror::message::NetworkMessage;
+    pub use super::commons::Object;
+    pub use super::mirror::message::{MessageDeserializer, MessageSerializer, NetworkMessage};
+    pub use super::mirror::DataTypeDeserializer;
+    pub use super::mirror::DataTypeSerializer;
+    pub use super::mirror::NetworkReader;
+    pub use super::mirror::NetworkWriter;
+    pub use super::mirror::ReadError;
+    pub use super::mirror::StableHash;
     pub use unity_mirror_macro_rs::NetworkMessage;
 }
 
 
+// use unity_mirror_rs::macro_network_serialize::*;
+// use crate::macro_network_serialize::*;
+pub mod macro_network_serialize {
+    pub use super::mirror::DataTypeDeserializer;
+    pub use super::mirror::DataTypeSerializer;
+    pub use super::mirror::NetworkReader;
+    pub use super::mirror::NetworkWriter;
+    pub use super::mirror::ReadError;
+    pub use unity_mirror_macro_rs::NetworkDeserialize;
+    pub use unity_mirror_macro_rs::NetworkSerialize;
+}
+
+
 // use unity_mirror_rs::macro_callback_processor::*;
 // use crate::macro_callback_processor::*;
 pub mod macro_callback_processor {
@@ -57,8 +77,10 @@ pub mod macro_network_manager {
 // use unity_mirror_rs::macro_network_behaviour::*;
 // use crate::macro_network_behaviour::*;
 pub mod macro_network_behaviour {
+    pub use super::commons::Object;
     pub use super::commons::RevelArc;
     pub use super::commons::RevelWeak;
+    pub use super::mirror::BitWriter;
     pub use super::mirror::DataTypeDeserializer;
     pub use super::mirror::DataTypeSerializer;
     pub use super::mirror::NetworkBehaviour;
@@ -71,13 +93,20 @@ pub mod macro_network_behaviour {
     pub use super::mirror::NetworkConnectionToClient;
     pub use super::mirror::NetworkIdentity;
     pub use super::mirror::NetworkReader;
+    pub use super::mirror::NetworkReferenceId;
     pub use super::mirror::NetworkWriter;
     pub use super::mirror::NetworkWriterPool;
+    pub use super::mirror::Quantization;
+    pub use super::mirror::Quantize;
     pub use super::mirror::RemoteProcedureCalls;
     pub use super::mirror::StableHash;
     pub use super::mirror::SyncDirection;
     pub use super::mirror::SyncMode;
     pub use super::mirror::SyncObject;
+    pub use super::mirror::SyncVarDeltaField;
+    pub use super::mirror::SyncVarDeltaReader;
+    pub use super::mirror::SyncVarReference;
+    pub use super::mirror::SyncVarSnapshot;
     pub use super::mirror::TBaseNetworkBehaviour;
     pub use super::mirror::TransportChannel;
     pub use unity_mirror_macro_rs::ancestor_on_deserialize;
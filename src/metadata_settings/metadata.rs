@@ -1,5 +1,8 @@
 use crate::metadata_settings::MetadataNetworkManagerWrapper;
+use crate::metadata_settings::unity::metadata_nav_mesh::MetadataNavMesh;
 use crate::metadata_settings::unity::metadata_prefab::MetadataPrefab;
+use crate::commons::thread_static;
+use crate::unity_engine::NavMesh;
 use once_cell::sync::Lazy;
 use serde::Deserialize;
 use std::collections::HashMap;
@@ -10,7 +13,19 @@ pub struct MetadataLoader;
 
 const FILE_PATH: &str = "metadata_settings.json";
 
-static mut METADATA: Lazy<Arc<Metadata>> = Lazy::new(|| MetadataLoader::load(FILE_PATH));
+const NAV_MESH_FILE_PATH: &str = "navmesh_settings.json";
+
+// 默认元数据，所有未单独加载的线程共享
+static DEFAULT_METADATA: Lazy<Arc<Metadata>> = Lazy::new(|| MetadataLoader::load(FILE_PATH));
+static DEFAULT_NAV_MESHES: Lazy<Arc<HashMap<String, MetadataNavMesh>>> =
+    Lazy::new(|| Arc::new(MetadataLoader::load_nav_meshes(NAV_MESH_FILE_PATH)));
+
+thread_static! {
+    // 为空时使用默认元数据，首次读取时才加载默认文件
+    static mut METADATA: Option<Arc<Metadata>> = None;
+    // 场景路径 -> NavMesh 三角化数据
+    static mut NAV_MESHES: Arc<HashMap<String, MetadataNavMesh>> = DEFAULT_NAV_MESHES.clone();
+}
 
 impl MetadataLoader {
     pub fn load(file_path: &str) -> Arc<Metadata> {
@@ -22,7 +37,25 @@ impl MetadataLoader {
                 }
             },
             Err(err) => {
-                panic!("Failed to open configuration file '{FILE_PATH}'\n{}", err)
+                panic!("Failed to open configuration file '{file_path}'\n{}", err)
+            }
+        }
+    }
+
+    // NavMesh 文件是可选的，缺失时所有场景都没有 NavMesh
+    pub fn load_nav_meshes(file_path: &str) -> HashMap<String, MetadataNavMesh> {
+        match fs::read_to_string(file_path) {
+            Ok(string_value) => {
+                match serde_json::from_str::<HashMap<String, MetadataNavMesh>>(&string_value) {
+                    Ok(value) => value,
+                    Err(err) => {
+                        panic!("Failed to parse the navmesh file:\n{}", err);
+                    }
+                }
+            }
+            Err(err) => {
+                log::warn!("Failed to open navmesh file '{file_path}': {}", err);
+                HashMap::new()
             }
         }
     }
@@ -37,32 +70,100 @@ pub struct Metadata {
 }
 
 impl Metadata {
-    pub(crate) fn get_scene(path: &str) -> Option<&HashMap<String, MetadataPrefab>> {
+    /// 当前线程 (服务器实例) 使用的元数据
+    /// 返回 Arc，重新加载元数据后已取得的引用依然有效
+    pub fn current() -> Arc<Metadata> {
         #[allow(static_mut_refs)]
         unsafe {
-            METADATA.scenes.get(path)
+            METADATA.get_or_insert_with(|| DEFAULT_METADATA.clone()).clone()
         }
     }
+
+    pub(crate) fn get_scene(&self, path: &str) -> Option<&HashMap<String, MetadataPrefab>> {
+        self.scenes.get(path)
+    }
 }
 
 impl Metadata {
-    pub fn get_prefab(prefab_path: &str) -> Option<&MetadataPrefab> {
+    /// 为当前线程 (服务器实例) 加载元数据，需在 NetworkManager::init 之前调用
+    pub fn load(file_path: &str) {
+        #[allow(static_mut_refs)]
+        unsafe {
+            *METADATA = Some(MetadataLoader::load(file_path));
+        }
+    }
+
+    /// 为当前线程使用代码中构建的元数据，例如测试或不依赖导出文件的服务器
+    pub fn load_from(metadata: Metadata) {
         #[allow(static_mut_refs)]
         unsafe {
-            METADATA.prefabs.get(prefab_path)
+            *METADATA = Some(Arc::new(metadata));
         }
     }
 
-    pub fn get_network_manager(prefab_path: &str) -> Option<&MetadataNetworkManagerWrapper> {
+    /// 为当前线程 (服务器实例) 加载 NavMesh 元数据
+    pub fn load_nav_meshes(file_path: &str) {
         #[allow(static_mut_refs)]
         unsafe {
-            METADATA.network_managers.get(prefab_path)
+            *NAV_MESHES = Arc::new(MetadataLoader::load_nav_meshes(file_path));
         }
+        NavMesh::clear_nav_mesh_data();
+    }
+
+    pub fn get_prefab(&self, prefab_path: &str) -> Option<&MetadataPrefab> {
+        self.prefabs.get(prefab_path)
+    }
+
+    // 返回副本，重新加载 NavMesh 元数据不影响已取出的数据
+    pub fn get_nav_mesh(scene_path: &str) -> Option<MetadataNavMesh> {
+        #[allow(static_mut_refs)]
+        unsafe {
+            NAV_MESHES.get(scene_path).cloned()
+        }
+    }
+
+    pub fn get_network_manager(&self, prefab_path: &str) -> Option<&MetadataNetworkManagerWrapper> {
+        self.network_managers.get(prefab_path)
+    }
+}
+
+#[cfg(test)]
+impl Metadata {
+    // 测试用：当前线程只使用给定的场景，场景中的根对象按顺序编号
+    pub(crate) fn load_scenes(scenes: Vec<(&str, Vec<MetadataPrefab>)>) {
+        Self::load_from(Metadata {
+            prefabs: HashMap::new(),
+            scenes: scenes
+                .into_iter()
+                .map(|(scene_path, root_game_objects)| {
+                    let root_game_objects = root_game_objects
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, prefab)| (i.to_string(), prefab))
+                        .collect();
+                    (scene_path.to_string(), root_game_objects)
+                })
+                .collect(),
+            network_managers: HashMap::new(),
+        });
     }
 }
 
 #[cfg(test)]
 mod metadata_test {
+    use super::*;
+
+    #[test]
+    fn test_reload_keeps_current() {
+        Metadata::load_scenes(vec![("Assets/Scenes/A.unity", vec![MetadataPrefab::builder("A").build().unwrap()])]);
+        let metadata = Metadata::current();
+        let scene = metadata.get_scene("Assets/Scenes/A.unity").unwrap();
+
+        // 重新加载后，之前取得的元数据仍然可用
+        Metadata::load_scenes(vec![("Assets/Scenes/B.unity", vec![])]);
+        assert_eq!(scene["0"].name, "A");
+        assert!(Metadata::current().get_scene("Assets/Scenes/A.unity").is_none());
+    }
 
     // #[test]
     // fn test() {
@@ -27,6 +27,73 @@ pub struct MetadataLayer {
     pub normalized_time: f32,
     #[serde(rename = "weight")]
     pub weight: f32,
+    // 以下为 AnimatorController 状态机数据，旧版导出中不存在
+    #[serde(rename = "defaultState", default)]
+    pub default_state: i32,
+    #[serde(rename = "states", default)]
+    pub states: Vec<MetadataAnimatorState>,
+    #[serde(rename = "anyStateTransitions", default)]
+    pub any_state_transitions: Vec<MetadataAnimatorTransition>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct MetadataAnimatorState {
+    #[serde(rename = "name")]
+    pub name: String,
+    #[serde(rename = "fullPathHash")]
+    pub full_path_hash: i32,
+    // 动画片段时长（秒）
+    #[serde(rename = "length")]
+    pub length: f32,
+    #[serde(rename = "speed", default = "default_speed")]
+    pub speed: f32,
+    #[serde(rename = "loop", default)]
+    pub is_loop: bool,
+    #[serde(rename = "transitions", default)]
+    pub transitions: Vec<MetadataAnimatorTransition>,
+}
+
+fn default_speed() -> f32 {
+    1.0
+}
+
+#[derive(Deserialize, Clone)]
+pub struct MetadataAnimatorTransition {
+    #[serde(rename = "destinationState")]
+    pub destination_state: i32,
+    #[serde(rename = "hasExitTime", default)]
+    pub has_exit_time: bool,
+    #[serde(rename = "exitTime", default)]
+    pub exit_time: f32,
+    #[serde(rename = "hasFixedDuration", default)]
+    pub has_fixed_duration: bool,
+    #[serde(rename = "duration", default)]
+    pub duration: f32,
+    #[serde(rename = "offset", default)]
+    pub offset: f32,
+    #[serde(rename = "conditions", default)]
+    pub conditions: Vec<MetadataAnimatorCondition>,
+}
+
+#[derive(Deserialize_repr, Debug, Clone, Copy, Eq, PartialEq)]
+#[repr(u8)]
+pub enum MetadataAnimatorConditionMode {
+    If = 1,
+    IfNot = 2,
+    Greater = 3,
+    Less = 4,
+    Equals = 6,
+    NotEqual = 7,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct MetadataAnimatorCondition {
+    #[serde(rename = "mode")]
+    pub mode: MetadataAnimatorConditionMode,
+    #[serde(rename = "parameter")]
+    pub parameter: String,
+    #[serde(rename = "threshold", default)]
+    pub threshold: f32,
 }
 
 #[derive(Deserialize_repr, Debug, Clone, Eq, PartialEq)]
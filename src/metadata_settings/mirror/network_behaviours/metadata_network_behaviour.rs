@@ -28,4 +28,11 @@ pub struct MetadataNetworkBehaviour {
     pub sync_direction: MetadataSyncDirection,
     #[serde(rename = "syncInterval")]
     pub sync_interval: f32,
+    // 带宽不足时的发送优先级，越大越优先
+    #[serde(default = "default_priority")]
+    pub priority: f32,
+}
+
+fn default_priority() -> f32 {
+    1.0
 }
@@ -0,0 +1,44 @@
+#![allow(dead_code)]
+use crate::commons::Object;
+use crate::macro_namespace::*;
+use crate::metadata_settings::unity::metadata_component::MetadataComponentWrapper;
+use crate::metadata_settings::Settings;
+use crate::{settings_wrapper_register, MetadataSettingsWrapper};
+use serde::Deserialize;
+
+/// 导出的 NavMesh 三角化数据（对应 Unity 的 NavMeshTriangulation）
+#[derive(Deserialize, Clone, Default)]
+pub struct MetadataNavMesh {
+    pub vertices: Vec<[f32; 3]>,
+    pub indices: Vec<u32>,
+    /// 每个三角形所属的 area 索引
+    pub areas: Vec<i32>,
+}
+
+#[namespace(prefix = "UnityEngine.AI", rename = "NavMeshAgent")]
+#[derive(Deserialize, MetadataSettingsWrapper, Clone)]
+pub struct MetadataNavMeshAgent {
+    #[serde(rename = "instanceId")]
+    pub instance_id: i32,
+    pub speed: f32,
+    #[serde(rename = "angularSpeed")]
+    pub angular_speed: f32,
+    pub acceleration: f32,
+    #[serde(rename = "stoppingDistance")]
+    pub stopping_distance: f32,
+    #[serde(rename = "autoBraking")]
+    pub auto_braking: bool,
+    #[serde(rename = "areaMask")]
+    pub area_mask: i32,
+}
+
+impl Object for MetadataNavMeshAgentWrapper {
+    fn get_full_name() -> &'static str
+    where
+        Self: Sized,
+    {
+        "UnityEngine.AI.NavMeshAgent"
+    }
+}
+
+settings_wrapper_register!(MetadataNavMeshAgentWrapper as MetadataComponentWrapper);
@@ -1,5 +1,6 @@
 use crate::metadata_settings::unity::metadata_component::MetadataComponentWrapper;
 use crate::metadata_settings::unity::metadata_transform::MetadataTransform;
+use nalgebra::{Isometry3, Quaternion, Translation3, UnitQuaternion, Vector3};
 use serde::Deserialize;
 
 #[derive(Deserialize)]
@@ -21,3 +22,199 @@ pub struct MetadataPrefab {
     #[serde(rename = "assetPath")]
     pub asset_path: String,
 }
+
+impl MetadataPrefab {
+    /// 在代码中构建预制体，用于没有导出 Unity 预制体的运行时对象
+    pub fn builder(name: &str) -> MetadataPrefabBuilder {
+        MetadataPrefabBuilder::new(name)
+    }
+}
+
+/// 运行时预制体构建器
+/// 组件以导出格式的 json 描述，实例化时与导出的预制体一样经由组件工厂创建
+pub struct MetadataPrefabBuilder {
+    name: String,
+    tag: String,
+    layer: i32,
+    is_active: bool,
+    asset_id: u32,
+    local_position: [f32; 3],
+    local_rotation: [f32; 4],
+    local_scale: [f32; 3],
+    components: serde_json::Map<String, serde_json::Value>,
+    network_identity: bool,
+    scene_id: u64,
+    network_behaviours: Vec<serde_json::Value>,
+    children: Vec<MetadataPrefabBuilder>,
+}
+
+impl MetadataPrefabBuilder {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            tag: "Untagged".to_string(),
+            layer: 0,
+            is_active: true,
+            asset_id: 0,
+            local_position: [0.0; 3],
+            local_rotation: [0.0, 0.0, 0.0, 1.0],
+            local_scale: [1.0; 3],
+            components: Default::default(),
+            network_identity: false,
+            scene_id: 0,
+            network_behaviours: vec![],
+            children: vec![],
+        }
+    }
+
+    pub fn tag(mut self, tag: &str) -> Self {
+        self.tag = tag.to_string();
+        self
+    }
+
+    pub fn layer(mut self, layer: i32) -> Self {
+        self.layer = layer;
+        self
+    }
+
+    pub fn active(mut self, is_active: bool) -> Self {
+        self.is_active = is_active;
+        self
+    }
+
+    /// 通常不需要设置，NetworkServer.register_prefab 实例化时会写入注册的 assetId
+    pub fn asset_id(mut self, asset_id: u32) -> Self {
+        self.asset_id = asset_id;
+        self
+    }
+
+    pub fn local_position(mut self, local_position: [f32; 3]) -> Self {
+        self.local_position = local_position;
+        self
+    }
+
+    /// [x, y, z, w]
+    pub fn local_rotation(mut self, local_rotation: [f32; 4]) -> Self {
+        self.local_rotation = local_rotation;
+        self
+    }
+
+    pub fn local_scale(mut self, local_scale: [f32; 3]) -> Self {
+        self.local_scale = local_scale;
+        self
+    }
+
+    /// 添加组件，chain 为从基类到子类的 (全名, 配置)，例如
+    /// [("UnityEngine.Collider", ...), ("UnityEngine.CapsuleCollider", ...)]
+    pub fn component(mut self, chain: Vec<(&str, serde_json::Value)>) -> Self {
+        let Some((full_name, _)) = chain.first() else {
+            return self;
+        };
+        let full_name = full_name.to_string();
+        let value = Self::chain_value(chain);
+        match self.components.get_mut(&full_name) {
+            Some(serde_json::Value::Array(values)) => values.push(value),
+            _ => {
+                self.components.insert(full_name, serde_json::Value::Array(vec![value]));
+            }
+        }
+        self
+    }
+
+    pub fn network_identity(mut self) -> Self {
+        self.network_identity = true;
+        self
+    }
+
+    /// 场景对象的 sceneId，只用于构建场景元数据，自动添加 NetworkIdentity
+    pub fn scene_id(mut self, scene_id: u64) -> Self {
+        self.network_identity = true;
+        self.scene_id = scene_id;
+        self
+    }
+
+    /// 添加 NetworkBehaviour，自动添加 NetworkIdentity，chain 需从 Mirror.NetworkBehaviour 开始
+    pub fn network_behaviour(mut self, chain: Vec<(&str, serde_json::Value)>) -> Self {
+        self.network_identity = true;
+        if !chain.is_empty() {
+            self.network_behaviours.push(Self::chain_value(chain));
+        }
+        self
+    }
+
+    pub fn child(mut self, child: MetadataPrefabBuilder) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    pub fn build(self) -> Result<MetadataPrefab, serde_json::Error> {
+        serde_json::from_value(self.into_json(&Isometry3::identity(), &Vector3::new(1.0, 1.0, 1.0)))
+    }
+
+    fn chain_value(chain: Vec<(&str, serde_json::Value)>) -> serde_json::Value {
+        serde_json::Value::Array(
+            chain
+                .into_iter()
+                .map(|(full_name, value)| serde_json::json!({ full_name: value }))
+                .collect(),
+        )
+    }
+
+    // 导出格式同时包含世界坐标，由父节点的世界变换 (parent, parent_scale) 推算
+    fn into_json(self, parent: &Isometry3<f32>, parent_scale: &Vector3<f32>) -> serde_json::Value {
+        let [x, y, z, w] = self.local_rotation;
+        let local_rotation = UnitQuaternion::from_quaternion(Quaternion::new(w, x, y, z));
+        let local_position = Vector3::from(self.local_position).component_mul(parent_scale);
+        let world = parent * Isometry3::from_parts(Translation3::from(local_position), local_rotation);
+        let world_scale = Vector3::from(self.local_scale).component_mul(parent_scale);
+        let position = world.translation.vector;
+        let rotation = world.rotation.quaternion().coords;
+
+        let transform = serde_json::json!({
+            "instanceId": 0,
+            "position": [position.x, position.y, position.z],
+            "localPosition": self.local_position,
+            "rotation": [rotation.x, rotation.y, rotation.z, rotation.w],
+            "localRotation": self.local_rotation,
+            "localScale": self.local_scale,
+            "type": "UnityEngine.Transform",
+            "assetId": self.asset_id,
+            "assetPath": ""
+        });
+
+        let mut components = self.components;
+        if self.network_identity {
+            let identity = serde_json::json!({
+                "Mirror.NetworkIdentity": {
+                    "assetId": self.asset_id,
+                    "sceneId": self.scene_id.to_string(),
+                    "serverOnly": false,
+                    "visibility": 0,
+                    "networkBehaviours": self.network_behaviours
+                }
+            });
+            components.insert(
+                "Mirror.NetworkIdentity".to_string(),
+                serde_json::json!([[identity]]),
+            );
+        }
+
+        serde_json::json!({
+            "id": 0,
+            "name": self.name,
+            "tag": self.tag,
+            "layer": self.layer,
+            "isStatic": false,
+            "isActive": self.is_active,
+            "transform": transform,
+            "components": components,
+            "children": self
+                .children
+                .into_iter()
+                .map(|child| child.into_json(&world, &world_scale))
+                .collect::<Vec<_>>(),
+            "assetId": self.asset_id,
+            "assetPath": ""
+        })
+    }
+}
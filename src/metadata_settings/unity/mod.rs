@@ -2,6 +2,7 @@ pub mod capsule_collider;
 pub mod collider;
 pub mod metadata_asset;
 pub mod metadata_component;
+pub mod metadata_nav_mesh;
 pub mod metadata_prefab;
 pub mod metadata_scene;
 pub mod metadata_transform;
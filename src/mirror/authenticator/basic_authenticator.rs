@@ -3,12 +3,8 @@ use crate::commons::RevelArc;
 use crate::macro_authenticator_factory::*;
 use crate::macro_namespace::*;
 use crate::macro_network_message::*;
-use crate::mirror::messages::message::{MessageDeserializer, MessageSerializer};
-use crate::mirror::stable_hash::StableHash;
 use crate::mirror::transport::TransportChannel;
 use crate::mirror::AuthenticatorBase;
-use crate::mirror::NetworkReader;
-use crate::mirror::NetworkWriter;
 use crate::mirror::{Authenticator, NetworkConnectionToClient, NetworkServer};
 use crate::unity_engine::MonoBehaviour;
 
@@ -63,29 +59,6 @@ pub struct BasicAuthenticatorRequestMessage {
     auth_password: String,
 }
 
-impl MessageSerializer for BasicAuthenticatorRequestMessage {
-    fn serialize(&mut self, writer: &mut NetworkWriter)
-    where
-        Self: Sized,
-    {
-        writer.write_blittable(Self::get_full_name().hash16());
-        writer.write_str(&self.auth_username);
-        writer.write_str(&self.auth_password);
-    }
-}
-
-impl MessageDeserializer for BasicAuthenticatorRequestMessage {
-    fn deserialize(reader: &mut NetworkReader) -> Self
-    where
-        Self: Sized,
-    {
-        Self {
-            auth_username: reader.read_string(),
-            auth_password: reader.read_string(),
-        }
-    }
-}
-
 // BasicAuthenticator AuthResponseMessage
 #[namespace(
     prefix = "Mirror.Authenticators.BasicAuthenticator+",
@@ -96,26 +69,3 @@ pub struct BasicAuthenticatorResponseMessage {
     code: u8,
     message: String,
 }
-
-impl MessageSerializer for BasicAuthenticatorResponseMessage {
-    fn serialize(&mut self, writer: &mut NetworkWriter)
-    where
-        Self: Sized,
-    {
-        writer.write_blittable(Self::get_full_name().hash16());
-        writer.write_blittable(self.code);
-        writer.write_str(self.message.as_str());
-    }
-}
-
-impl MessageDeserializer for BasicAuthenticatorResponseMessage {
-    fn deserialize(reader: &mut NetworkReader) -> Self
-    where
-        Self: Sized,
-    {
-        Self {
-            code: reader.read_blittable(),
-            message: reader.read_string(),
-        }
-    }
-}
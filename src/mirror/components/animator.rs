@@ -0,0 +1,761 @@
+use crate::metadata_settings::{
+    MetadataAnimator, MetadataAnimatorConditionMode, MetadataAnimatorTransition, MetadataLayer,
+    MetadataParameterType,
+};
+use std::mem;
+
+impl From<MetadataParameterType> for AnimatorParameterType {
+    fn from(value: MetadataParameterType) -> Self {
+        match value {
+            MetadataParameterType::Float => AnimatorParameterType::Float,
+            MetadataParameterType::Int => AnimatorParameterType::Int,
+            MetadataParameterType::Bool => AnimatorParameterType::Bool,
+            MetadataParameterType::Trigger => AnimatorParameterType::Trigger,
+        }
+    }
+}
+
+impl From<MetadataAnimator> for Animator {
+    fn from(value: MetadataAnimator) -> Self {
+        let mut animator = Animator {
+            layers: Default::default(),
+            parameters: Default::default(),
+            events: Default::default(),
+        };
+
+        value
+            .parameters
+            .iter()
+            .enumerate()
+            .for_each(|(index, parameter)| {
+                let r#type: AnimatorParameterType = parameter.r#type.clone().into();
+                let mut value = parameter.value.clone();
+                // Trigger 在元数据中没有值，服务端用一个 bool 记录是否被触发
+                if r#type == AnimatorParameterType::Trigger && value.is_empty() {
+                    value = vec![0];
+                }
+                animator.parameters.push(AnimatorParameter {
+                    index,
+                    name: parameter.name.clone(),
+                    r#type,
+                    value,
+                })
+            });
+
+        value.layers.iter().for_each(|layer| {
+            let layer = AnimatorLayer::new(layer, &animator.parameters);
+            animator.layers.push(layer)
+        });
+
+        animator
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[repr(u8)]
+pub enum AnimatorParameterType {
+    Float = 1,
+    Int = 3,
+    Bool = 4,
+    Trigger = 9,
+}
+
+#[derive(Debug, Clone)]
+pub struct AnimatorParameter {
+    #[allow(unused)]
+    pub index: usize,
+    pub name: String,
+    pub r#type: AnimatorParameterType,
+    pub value: Vec<u8>,
+}
+
+impl AnimatorParameter {
+    fn read_bytes<const N: usize>(&self) -> [u8; N] {
+        let mut bytes = [0u8; N];
+        let len = N.min(self.value.len());
+        bytes[..len].copy_from_slice(&self.value[..len]);
+        bytes
+    }
+
+    pub fn get_float(&self) -> f32 {
+        f32::from_le_bytes(self.read_bytes())
+    }
+
+    pub fn get_integer(&self) -> i32 {
+        i32::from_le_bytes(self.read_bytes())
+    }
+
+    pub fn get_bool(&self) -> bool {
+        self.read_bytes::<1>()[0] != 0
+    }
+
+    pub fn set_float(&mut self, value: f32) {
+        self.value = value.to_le_bytes().to_vec();
+    }
+
+    pub fn set_integer(&mut self, value: i32) {
+        self.value = value.to_le_bytes().to_vec();
+    }
+
+    pub fn set_bool(&mut self, value: bool) {
+        self.value = vec![value as u8];
+    }
+
+    // 条件比较时 Int 与 Float 统一按 f32 处理
+    fn get_number(&self) -> f32 {
+        match self.r#type {
+            AnimatorParameterType::Int => self.get_integer() as f32,
+            _ => self.get_float(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AnimatorCondition {
+    pub mode: MetadataAnimatorConditionMode,
+    // 参数索引，参数不存在时条件永远不成立
+    pub parameter: Option<usize>,
+    pub threshold: f32,
+}
+
+impl AnimatorCondition {
+    fn check(&self, parameters: &[AnimatorParameter]) -> bool {
+        let Some(parameter) = self.parameter.and_then(|index| parameters.get(index)) else {
+            return false;
+        };
+        match self.mode {
+            MetadataAnimatorConditionMode::If => parameter.get_bool(),
+            MetadataAnimatorConditionMode::IfNot => !parameter.get_bool(),
+            MetadataAnimatorConditionMode::Greater => parameter.get_number() > self.threshold,
+            MetadataAnimatorConditionMode::Less => parameter.get_number() < self.threshold,
+            MetadataAnimatorConditionMode::Equals => {
+                parameter.get_integer() == self.threshold as i32
+            }
+            MetadataAnimatorConditionMode::NotEqual => {
+                parameter.get_integer() != self.threshold as i32
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AnimatorTransition {
+    pub destination: usize,
+    pub has_exit_time: bool,
+    pub exit_time: f32,
+    pub has_fixed_duration: bool,
+    pub duration: f32,
+    pub offset: f32,
+    pub conditions: Vec<AnimatorCondition>,
+}
+
+impl AnimatorTransition {
+    fn new(
+        metadata: &MetadataAnimatorTransition,
+        states: &[AnimatorState],
+        parameters: &[AnimatorParameter],
+    ) -> Option<Self> {
+        let Some(destination) = states
+            .iter()
+            .position(|state| state.full_path_hash == metadata.destination_state)
+        else {
+            log::warn!(
+                "Animator transition destination state {} not found",
+                metadata.destination_state
+            );
+            return None;
+        };
+
+        let conditions = metadata
+            .conditions
+            .iter()
+            .map(|condition| {
+                let parameter = parameters
+                    .iter()
+                    .position(|parameter| parameter.name == condition.parameter);
+                if parameter.is_none() {
+                    log::warn!("Animator parameter '{}' does not exist.", condition.parameter);
+                }
+                AnimatorCondition {
+                    mode: condition.mode,
+                    parameter,
+                    threshold: condition.threshold,
+                }
+            })
+            .collect();
+
+        Some(Self {
+            destination,
+            has_exit_time: metadata.has_exit_time,
+            exit_time: metadata.exit_time,
+            has_fixed_duration: metadata.has_fixed_duration,
+            duration: metadata.duration,
+            offset: metadata.offset,
+            conditions,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AnimatorState {
+    pub name: String,
+    pub name_hash: i32,
+    pub full_path_hash: i32,
+    pub length: f32,
+    pub speed: f32,
+    pub is_loop: bool,
+    pub transitions: Vec<AnimatorTransition>,
+}
+
+impl AnimatorState {
+    // 每秒推进的 normalized time
+    fn normalized_speed(&self) -> f32 {
+        if self.length > 0.0 {
+            self.speed / self.length
+        } else {
+            self.speed
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct AnimatorStateInfo {
+    pub full_path_hash: i32,
+    pub short_name_hash: i32,
+    pub normalized_time: f32,
+    pub length: f32,
+    pub speed: f32,
+    pub is_loop: bool,
+}
+
+impl AnimatorStateInfo {
+    pub fn is_name(&self, name: &str) -> bool {
+        let hash = Animator::string_to_hash(name);
+        hash == self.full_path_hash || hash == self.short_name_hash
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnimatorEvent {
+    // 过渡开始时进入目标状态
+    StateEnter { layer: usize, full_path_hash: i32 },
+    // 过渡结束时离开源状态
+    StateExit { layer: usize, full_path_hash: i32 },
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ActiveTransition {
+    destination: usize,
+    normalized_time: f32,
+    elapsed: f32,
+    duration: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct AnimatorLayer {
+    // 当前（过渡中则为目标）状态，用于序列化
+    pub full_path_hash: i32,
+    pub normalized_time: f32,
+    pub layer_weight: f32,
+
+    states: Vec<AnimatorState>,
+    any_state_transitions: Vec<AnimatorTransition>,
+    current_state: Option<usize>,
+    current_normalized_time: f32,
+    transition: Option<ActiveTransition>,
+}
+
+impl AnimatorLayer {
+    fn new(metadata: &MetadataLayer, parameters: &[AnimatorParameter]) -> Self {
+        let mut states = metadata
+            .states
+            .iter()
+            .map(|state| AnimatorState {
+                name: state.name.clone(),
+                name_hash: Animator::string_to_hash(&state.name),
+                full_path_hash: state.full_path_hash,
+                length: state.length,
+                speed: state.speed,
+                is_loop: state.is_loop,
+                transitions: vec![],
+            })
+            .collect::<Vec<_>>();
+
+        for (index, state) in metadata.states.iter().enumerate() {
+            let transitions = state
+                .transitions
+                .iter()
+                .filter_map(|transition| AnimatorTransition::new(transition, &states, parameters))
+                .collect();
+            states[index].transitions = transitions;
+        }
+
+        let any_state_transitions = metadata
+            .any_state_transitions
+            .iter()
+            .filter_map(|transition| AnimatorTransition::new(transition, &states, parameters))
+            .collect();
+
+        // 默认状态优先，其次是导出时所在的状态
+        let current_state = states
+            .iter()
+            .position(|state| state.full_path_hash == metadata.default_state)
+            .or_else(|| {
+                states
+                    .iter()
+                    .position(|state| state.full_path_hash == metadata.full_path_hash)
+            })
+            .or(if states.is_empty() { None } else { Some(0) });
+
+        let mut layer = Self {
+            full_path_hash: metadata.full_path_hash,
+            normalized_time: metadata.normalized_time,
+            layer_weight: metadata.weight,
+            states,
+            any_state_transitions,
+            current_state,
+            current_normalized_time: metadata.normalized_time,
+            transition: None,
+        };
+        layer.sync_serialized_state();
+        layer
+    }
+
+    pub fn states(&self) -> &[AnimatorState] {
+        &self.states
+    }
+
+    fn state_info(&self, state: usize, normalized_time: f32) -> AnimatorStateInfo {
+        let state = &self.states[state];
+        AnimatorStateInfo {
+            full_path_hash: state.full_path_hash,
+            short_name_hash: state.name_hash,
+            normalized_time,
+            length: state.length,
+            speed: state.speed,
+            is_loop: state.is_loop,
+        }
+    }
+
+    pub fn current_state_info(&self) -> Option<AnimatorStateInfo> {
+        let state = self.current_state?;
+        Some(self.state_info(state, self.current_normalized_time))
+    }
+
+    pub fn next_state_info(&self) -> Option<AnimatorStateInfo> {
+        let transition = self.transition?;
+        Some(self.state_info(transition.destination, transition.normalized_time))
+    }
+
+    pub fn is_in_transition(&self) -> bool {
+        self.transition.is_some()
+    }
+
+    fn find_state(&self, hash: i32) -> Option<usize> {
+        self.states
+            .iter()
+            .position(|state| state.full_path_hash == hash || state.name_hash == hash)
+    }
+
+    fn sync_serialized_state(&mut self) {
+        if let Some(info) = self.next_state_info().or(self.current_state_info()) {
+            self.full_path_hash = info.full_path_hash;
+            self.normalized_time = info.normalized_time;
+        }
+    }
+
+    fn exit_time_reached(previous: f32, current: f32, exit_time: f32) -> bool {
+        if exit_time >= 1.0 {
+            return previous < exit_time && current >= exit_time;
+        }
+        // exit_time < 1 时每个循环都会检查一次
+        (current - exit_time).floor() > (previous - exit_time).floor()
+    }
+
+    fn find_transition(
+        &self,
+        previous_time: f32,
+        parameters: &[AnimatorParameter],
+    ) -> Option<AnimatorTransition> {
+        let current = self.current_state?;
+        let any_state_transitions = self
+            .any_state_transitions
+            .iter()
+            .filter(|transition| transition.destination != current);
+        let state_transitions = self.states[current].transitions.iter();
+
+        any_state_transitions
+            .chain(state_transitions)
+            .find(|transition| {
+                if !transition.has_exit_time && transition.conditions.is_empty() {
+                    return false;
+                }
+                if transition.has_exit_time
+                    && !Self::exit_time_reached(
+                        previous_time,
+                        self.current_normalized_time,
+                        transition.exit_time,
+                    )
+                {
+                    return false;
+                }
+                transition
+                    .conditions
+                    .iter()
+                    .all(|condition| condition.check(parameters))
+            })
+            .cloned()
+    }
+
+    fn switch_state(
+        &mut self,
+        layer: usize,
+        destination: usize,
+        normalized_time: f32,
+        events: &mut Vec<AnimatorEvent>,
+    ) {
+        if let Some(current) = self.current_state {
+            events.push(AnimatorEvent::StateExit {
+                layer,
+                full_path_hash: self.states[current].full_path_hash,
+            });
+        }
+        self.current_state = Some(destination);
+        self.current_normalized_time = normalized_time;
+        self.transition = None;
+    }
+
+    fn start_transition(
+        &mut self,
+        layer: usize,
+        destination: usize,
+        duration: f32,
+        offset: f32,
+        events: &mut Vec<AnimatorEvent>,
+    ) {
+        events.push(AnimatorEvent::StateEnter {
+            layer,
+            full_path_hash: self.states[destination].full_path_hash,
+        });
+        if duration <= 0.0 {
+            self.switch_state(layer, destination, offset, events);
+        } else {
+            self.transition = Some(ActiveTransition {
+                destination,
+                normalized_time: offset,
+                elapsed: 0.0,
+                duration,
+            });
+        }
+    }
+
+    fn update(
+        &mut self,
+        layer: usize,
+        delta_time: f32,
+        parameters: &mut [AnimatorParameter],
+        events: &mut Vec<AnimatorEvent>,
+    ) {
+        let Some(current) = self.current_state else {
+            return;
+        };
+
+        let previous_time = self.current_normalized_time;
+        self.current_normalized_time += delta_time * self.states[current].normalized_speed();
+
+        if let Some(mut transition) = self.transition {
+            let destination = &self.states[transition.destination];
+            transition.normalized_time += delta_time * destination.normalized_speed();
+            transition.elapsed += delta_time;
+            if transition.elapsed >= transition.duration {
+                self.switch_state(
+                    layer,
+                    transition.destination,
+                    transition.normalized_time,
+                    events,
+                );
+            } else {
+                self.transition = Some(transition);
+            }
+        } else if let Some(transition) = self.find_transition(previous_time, parameters) {
+            // 被条件使用的 Trigger 会被消耗
+            for condition in transition.conditions.iter() {
+                if let Some(parameter) = condition.parameter.and_then(|i| parameters.get_mut(i)) {
+                    if parameter.r#type == AnimatorParameterType::Trigger {
+                        parameter.set_bool(false);
+                    }
+                }
+            }
+            let duration = if transition.has_fixed_duration {
+                transition.duration
+            } else {
+                transition.duration * self.states[current].length
+            };
+            self.start_transition(
+                layer,
+                transition.destination,
+                duration,
+                transition.offset,
+                events,
+            );
+        }
+
+        self.sync_serialized_state();
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Animator {
+    pub layers: Vec<AnimatorLayer>,
+    pub parameters: Vec<AnimatorParameter>,
+    events: Vec<AnimatorEvent>,
+}
+
+impl Animator {
+    /// Animator.StringToHash (CRC32)
+    pub fn string_to_hash(name: &str) -> i32 {
+        let mut crc = 0xFFFF_FFFFu32;
+        for byte in name.bytes() {
+            crc ^= byte as u32;
+            for _ in 0..8 {
+                let mask = (crc & 1).wrapping_neg();
+                crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+            }
+        }
+        !crc as i32
+    }
+
+    /// 推进状态机，返回本帧产生的状态事件
+    pub fn update(&mut self, delta_time: f32) -> Vec<AnimatorEvent> {
+        let mut events = mem::take(&mut self.events);
+        for (index, layer) in self.layers.iter_mut().enumerate() {
+            layer.update(index, delta_time, &mut self.parameters, &mut events);
+        }
+        events
+    }
+
+    pub fn parameter(&self, name: &str) -> Option<&AnimatorParameter> {
+        self.parameters
+            .iter()
+            .find(|parameter| parameter.name == name)
+    }
+
+    fn parameter_mut(&mut self, name: &str) -> Option<&mut AnimatorParameter> {
+        let parameter = self
+            .parameters
+            .iter_mut()
+            .find(|parameter| parameter.name == name);
+        if parameter.is_none() {
+            log::warn!("Animator parameter '{}' does not exist.", name);
+        }
+        parameter
+    }
+
+    pub fn get_float(&self, name: &str) -> f32 {
+        self.parameter(name).map(|p| p.get_float()).unwrap_or_default()
+    }
+
+    pub fn get_integer(&self, name: &str) -> i32 {
+        self.parameter(name).map(|p| p.get_integer()).unwrap_or_default()
+    }
+
+    pub fn get_bool(&self, name: &str) -> bool {
+        self.parameter(name).map(|p| p.get_bool()).unwrap_or_default()
+    }
+
+    pub fn set_float(&mut self, name: &str, value: f32) {
+        if let Some(parameter) = self.parameter_mut(name) {
+            parameter.set_float(value);
+        }
+    }
+
+    pub fn set_integer(&mut self, name: &str, value: i32) {
+        if let Some(parameter) = self.parameter_mut(name) {
+            parameter.set_integer(value);
+        }
+    }
+
+    pub fn set_bool(&mut self, name: &str, value: bool) {
+        if let Some(parameter) = self.parameter_mut(name) {
+            parameter.set_bool(value);
+        }
+    }
+
+    pub fn set_trigger(&mut self, name: &str) {
+        self.set_bool(name, true);
+    }
+
+    pub fn reset_trigger(&mut self, name: &str) {
+        self.set_bool(name, false);
+    }
+
+    pub(super) fn set_trigger_hash(&mut self, hash: i32, value: bool) {
+        if let Some(parameter) = self
+            .parameters
+            .iter_mut()
+            .find(|parameter| Self::string_to_hash(&parameter.name) == hash)
+        {
+            parameter.set_bool(value);
+        }
+    }
+
+    /// 立即切换到指定状态（状态名、完整路径或其 hash）
+    pub fn play(&mut self, state: &str, layer: usize, normalized_time: f32) {
+        self.play_hash(Self::string_to_hash(state), layer, normalized_time)
+    }
+
+    pub fn play_hash(&mut self, state_hash: i32, layer: usize, normalized_time: f32) {
+        if let Some(animator_layer) = self.layers.get_mut(layer) {
+            match animator_layer.find_state(state_hash) {
+                None => log::warn!("Animator state {} not found in layer {}", state_hash, layer),
+                Some(destination) => {
+                    animator_layer.start_transition(
+                        layer,
+                        destination,
+                        0.0,
+                        normalized_time,
+                        &mut self.events,
+                    );
+                    animator_layer.sync_serialized_state();
+                }
+            }
+        }
+    }
+
+    /// 以 normalized 时长过渡到指定状态
+    pub fn cross_fade(&mut self, state: &str, normalized_transition_duration: f32, layer: usize) {
+        if let Some(animator_layer) = self.layers.get_mut(layer) {
+            let Some(destination) = animator_layer.find_state(Self::string_to_hash(state)) else {
+                log::warn!("Animator state {} not found in layer {}", state, layer);
+                return;
+            };
+            let length = animator_layer
+                .current_state_info()
+                .map(|info| info.length)
+                .unwrap_or_default();
+            animator_layer.start_transition(
+                layer,
+                destination,
+                normalized_transition_duration * length,
+                0.0,
+                &mut self.events,
+            );
+            animator_layer.sync_serialized_state();
+        }
+    }
+
+    pub fn get_current_animator_state_info(&self, layer: usize) -> Option<AnimatorStateInfo> {
+        self.layers.get(layer)?.current_state_info()
+    }
+
+    pub fn get_next_animator_state_info(&self, layer: usize) -> Option<AnimatorStateInfo> {
+        self.layers.get(layer)?.next_state_info()
+    }
+
+    pub fn is_in_transition(&self, layer: usize) -> bool {
+        self.layers
+            .get(layer)
+            .is_some_and(|layer| layer.is_in_transition())
+    }
+
+    /// 当前或正在过渡到的状态是否为 state
+    pub fn is_playing(&self, state: &str, layer: usize) -> bool {
+        self.get_current_animator_state_info(layer)
+            .is_some_and(|info| info.is_name(state))
+            || self
+                .get_next_animator_state_info(layer)
+                .is_some_and(|info| info.is_name(state))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata_settings::{MetadataAnimatorState, MetadataParameter};
+
+    fn reload_animator() -> Animator {
+        let idle = Animator::string_to_hash("Base Layer.Idle");
+        let reload = Animator::string_to_hash("Base Layer.Reload");
+        let state = |name: &str, hash: i32, transition: MetadataAnimatorTransition| {
+            MetadataAnimatorState {
+                name: name.to_string(),
+                full_path_hash: hash,
+                length: 1.0,
+                speed: 1.0,
+                is_loop: name == "Idle",
+                transitions: vec![transition],
+            }
+        };
+        MetadataAnimator {
+            layers: vec![MetadataLayer {
+                name: "Base Layer".to_string(),
+                full_path_hash: idle,
+                normalized_time: 0.0,
+                weight: 1.0,
+                default_state: idle,
+                states: vec![
+                    state(
+                        "Idle",
+                        idle,
+                        MetadataAnimatorTransition {
+                            destination_state: reload,
+                            has_exit_time: false,
+                            exit_time: 0.0,
+                            has_fixed_duration: true,
+                            duration: 0.0,
+                            offset: 0.0,
+                            conditions: vec![crate::metadata_settings::MetadataAnimatorCondition {
+                                mode: MetadataAnimatorConditionMode::If,
+                                parameter: "Reload".to_string(),
+                                threshold: 0.0,
+                            }],
+                        },
+                    ),
+                    state(
+                        "Reload",
+                        reload,
+                        MetadataAnimatorTransition {
+                            destination_state: idle,
+                            has_exit_time: true,
+                            exit_time: 1.0,
+                            has_fixed_duration: true,
+                            duration: 0.25,
+                            offset: 0.0,
+                            conditions: vec![],
+                        },
+                    ),
+                ],
+                any_state_transitions: vec![],
+            }],
+            parameters: vec![MetadataParameter {
+                name: "Reload".to_string(),
+                r#type: MetadataParameterType::Trigger,
+                value: vec![],
+            }],
+        }
+        .into()
+    }
+
+    #[test]
+    fn test_animator_state_machine() {
+        let mut animator = reload_animator();
+        assert!(animator.is_playing("Idle", 0));
+
+        animator.set_trigger("Reload");
+        let events = animator.update(0.1);
+        assert!(animator.is_playing("Reload", 0));
+        assert!(!animator.get_bool("Reload"));
+        assert_eq!(events.len(), 2);
+
+        // 退出时间到达后开始过渡，过渡结束才离开 Reload
+        animator.update(1.0);
+        assert!(animator.is_in_transition(0));
+        let events = animator.update(0.3);
+        assert!(!animator.is_in_transition(0));
+        assert!(animator.is_playing("Idle", 0));
+        assert!(events.contains(&AnimatorEvent::StateExit {
+            layer: 0,
+            full_path_hash: Animator::string_to_hash("Base Layer.Reload"),
+        }));
+    }
+}
@@ -1,6 +1,9 @@
 mod network_room_player;
 pub use network_room_player::*;
 
+mod animator;
+pub use animator::*;
+
 mod network_animator;
 pub use network_animator::*;
 
@@ -1,83 +1,14 @@
+use crate::commons::action::SelfMutAction;
 use crate::commons::Object;
 use crate::commons::RevelArc;
 use crate::commons::RevelWeak;
 use crate::macro_namespace::*;
 use crate::macro_network_behaviour::*;
+use crate::metadata_settings::MetadataNetworkAnimator;
 use crate::metadata_settings::MetadataNetworkBehaviourWrapper;
-use crate::metadata_settings::{MetadataAnimator, MetadataNetworkAnimator, MetadataParameterType};
 use crate::mirror::*;
-use crate::unity_engine::{GameObject, MonoBehaviour};
-
-impl Into<AnimatorParameterType> for MetadataParameterType {
-    fn into(self) -> AnimatorParameterType {
-        match self {
-            MetadataParameterType::Float => AnimatorParameterType::Float,
-            MetadataParameterType::Int => AnimatorParameterType::Int,
-            MetadataParameterType::Bool => AnimatorParameterType::Bool,
-            MetadataParameterType::Trigger => AnimatorParameterType::Trigger,
-        }
-    }
-}
-
-impl Into<Animator> for MetadataAnimator {
-    fn into(self) -> Animator {
-        let mut animator = Animator {
-            layers: Default::default(),
-            parameters: Default::default(),
-        };
-
-        self.layers.iter().for_each(|layer| {
-            animator.layers.push(AnimatorLayer {
-                full_path_hash: layer.full_path_hash,
-                normalized_time: layer.normalized_time,
-                layer_weight: layer.weight,
-            })
-        });
-
-        self.parameters
-            .iter()
-            .enumerate()
-            .for_each(|(index, parameter)| {
-                animator.parameters.push(AnimatorParameter {
-                    index,
-                    r#type: parameter.r#type.clone().into(),
-                    value: parameter.value.clone(),
-                })
-            });
-
-        animator
-    }
-}
-
-#[derive(Debug, Clone, Eq, PartialEq)]
-#[repr(u8)]
-pub enum AnimatorParameterType {
-    Float = 1,
-    Int = 3,
-    Bool = 4,
-    Trigger = 9,
-}
-
-#[derive(Debug, Copy, Clone)]
-pub struct AnimatorLayer {
-    pub full_path_hash: i32,
-    pub normalized_time: f32,
-    pub layer_weight: f32,
-}
-
-#[derive(Debug, Clone)]
-pub struct AnimatorParameter {
-    #[allow(unused)]
-    pub index: usize,
-    pub r#type: AnimatorParameterType,
-    pub value: Vec<u8>,
-}
-
-#[derive(Debug, Clone, Default)]
-pub struct Animator {
-    pub layers: Vec<AnimatorLayer>,
-    pub parameters: Vec<AnimatorParameter>,
-}
+use crate::mirror::components::{Animator, AnimatorEvent, AnimatorParameterType};
+use crate::unity_engine::{GameObject, MonoBehaviour, Time};
 
 #[namespace(prefix = "Mirror")]
 #[network_behaviour(
@@ -95,7 +26,10 @@ pub struct NetworkAnimator {
     last_int_parameters: Vec<i32>,
     last_float_parameters: Vec<f32>,
     last_bool_parameters: Vec<bool>,
-    parameters: Vec<AnimatorParameter>,
+
+    // (layer, full_path_hash)
+    pub on_state_enter_event: SelfMutAction<(usize, i32), ()>,
+    pub on_state_exit_event: SelfMutAction<(usize, i32), ()>,
 }
 
 // sync hook
@@ -106,7 +40,7 @@ impl NetworkAnimator {
     // CmdOnAnimationServerMessage(int stateHash, float normalizedTime, int layerId, float weight, byte[] parameters)
     #[command(NetworkAnimator)]
     fn cmd_on_animation_server_message(
-        &self,
+        &mut self,
         state_hash: i32,
         normalized_time: f32,
         layer_id: i32,
@@ -117,6 +51,18 @@ impl NetworkAnimator {
             return;
         }
 
+        // 客户端权威时以客户端的状态驱动服务端状态机
+        if state_hash != 0 {
+            self.animator
+                .play_hash(state_hash, layer_id as usize, normalized_time);
+        }
+        if let Some(layer) = self.animator.layers.get_mut(layer_id as usize) {
+            layer.layer_weight = weight;
+        }
+        NetworkReaderPool::get_with_slice_return(parameters, |reader| {
+            self.read_parameters(reader);
+        });
+
         self.rpc_on_animation_client_message(
             state_hash,
             normalized_time,
@@ -145,11 +91,15 @@ impl NetworkAnimator {
 
     // CmdOnAnimationParametersServerMessage(byte[] parameters)
     #[command(NetworkAnimator)]
-    fn cmd_on_animation_parameters_server_message(&self, parameters: &[u8]) {
+    fn cmd_on_animation_parameters_server_message(&mut self, parameters: &[u8]) {
         if !self.client_authority {
             return;
         }
 
+        NetworkReaderPool::get_with_slice_return(parameters, |reader| {
+            self.read_parameters(reader);
+        });
+
         self.rpc_on_animation_parameters_client_message(parameters);
     }
 
@@ -161,11 +111,13 @@ impl NetworkAnimator {
 
     // CmdOnAnimationTriggerServerMessage(int hash)
     #[command(NetworkAnimator)]
-    fn cmd_on_animation_trigger_server_message(&self, hash: i32) {
+    fn cmd_on_animation_trigger_server_message(&mut self, hash: i32) {
         if !self.client_authority {
             return;
         }
 
+        self.animator.set_trigger_hash(hash, true);
+
         self.rpc_on_animation_trigger_client_message(hash);
     }
 
@@ -177,11 +129,13 @@ impl NetworkAnimator {
 
     // CmdOnAnimationResetTriggerServerMessage(int hash)
     #[command(NetworkAnimator)]
-    fn cmd_on_animation_reset_trigger_server_message(&self, hash: i32) {
+    fn cmd_on_animation_reset_trigger_server_message(&mut self, hash: i32) {
         if !self.client_authority {
             return;
         }
 
+        self.animator.set_trigger_hash(hash, false);
+
         self.rpc_on_animation_reset_trigger_client_message(hash);
     }
 
@@ -202,7 +156,37 @@ impl NetworkAnimator {
 impl MonoBehaviour for NetworkAnimator {
     fn awake(&mut self) {}
 
-    fn update(&mut self) {}
+    fn update(&mut self) {
+        let delta_time = Time::delta_time() * *self.get_animator_speed();
+
+        for event in self.animator.update(delta_time) {
+            match event {
+                AnimatorEvent::StateEnter {
+                    layer,
+                    full_path_hash,
+                } => {
+                    if !self.client_authority {
+                        self.send_animation_message(layer, full_path_hash);
+                    }
+                    if self.on_state_enter_event.is_registered() {
+                        self.on_state_enter_event.call((layer, full_path_hash));
+                    }
+                }
+                AnimatorEvent::StateExit {
+                    layer,
+                    full_path_hash,
+                } => {
+                    if self.on_state_exit_event.is_registered() {
+                        self.on_state_exit_event.call((layer, full_path_hash));
+                    }
+                }
+            }
+        }
+
+        if !self.client_authority {
+            self.send_parameters_message();
+        }
+    }
 }
 // TNetworkBehaviour
 impl TNetworkBehaviour for NetworkAnimator {
@@ -228,11 +212,47 @@ impl TNetworkBehaviour for NetworkAnimator {
 impl NetworkAnimator {
     fn initialize(&mut self, metadata: &MetadataNetworkAnimator) {
         self.animator = metadata.animator.clone().into();
-        self.parameters = self.animator.parameters.clone();
+        for parameter in self.animator.parameters.iter() {
+            self.last_int_parameters.push(parameter.get_integer());
+            self.last_float_parameters.push(parameter.get_float());
+            self.last_bool_parameters.push(parameter.get_bool());
+        }
+    }
+
+    // 服务端权威时，状态切换同步给客户端
+    fn send_animation_message(&mut self, layer: usize, state_hash: i32) {
+        let (normalized_time, weight) = match self.animator.layers.get(layer) {
+            None => return,
+            Some(animator_layer) => (animator_layer.normalized_time, animator_layer.layer_weight),
+        };
+        let mut parameters = vec![];
+        NetworkWriterPool::get_by_closure(|writer| {
+            self.write_parameters(writer, false);
+            parameters = writer.to_vec();
+        });
+        self.rpc_on_animation_client_message(
+            state_hash,
+            normalized_time,
+            layer as i32,
+            weight,
+            &parameters,
+        );
+    }
+
+    fn send_parameters_message(&mut self) {
+        let mut parameters = vec![];
+        let mut changed = false;
+        NetworkWriterPool::get_by_closure(|writer| {
+            changed = self.write_parameters(writer, false);
+            parameters = writer.to_vec();
+        });
+        if changed {
+            self.rpc_on_animation_parameters_client_message(&parameters);
+        }
     }
     fn next_dirty_bits(&mut self) -> u64 {
         let mut dirty_bits = 0u64;
-        for (i, par) in self.parameters.iter().enumerate() {
+        for (i, par) in self.animator.parameters.iter().enumerate() {
             let mut changed = false;
             if par.r#type == AnimatorParameterType::Int {
                 NetworkReaderPool::get_with_slice_return(&par.value, |reader| {
@@ -268,7 +288,7 @@ impl NetworkAnimator {
     }
 
     fn write_parameters(&mut self, writer: &mut NetworkWriter, force_all: bool) -> bool {
-        let parameter_count = self.parameters.len() as u8;
+        let parameter_count = self.animator.parameters.len() as u8;
         writer.write_blittable::<u8>(parameter_count);
 
         let dirty_bits: u64;
@@ -278,7 +298,7 @@ impl NetworkAnimator {
             dirty_bits = self.next_dirty_bits();
         }
         writer.write_blittable::<u64>(dirty_bits);
-        for (i, par) in self.parameters.iter().enumerate() {
+        for (i, par) in self.animator.parameters.iter().enumerate() {
             if dirty_bits & (1 << i) == 0 {
                 continue;
             }
@@ -306,8 +326,8 @@ impl NetworkAnimator {
     fn read_parameters(&mut self, reader: &mut NetworkReader) {
         let parameter_count = reader.read_blittable::<u8>() as usize;
 
-        if parameter_count != self.parameters.len() {
-            log::error!("NetworkAnimator: serialized parameter count={} does not match expected parameter count={}. Are you changing animators at runtime?", parameter_count, self.parameters.len());
+        if parameter_count != self.animator.parameters.len() {
+            log::error!("NetworkAnimator: serialized parameter count={} does not match expected parameter count={}. Are you changing animators at runtime?", parameter_count, self.animator.parameters.len());
             return;
         }
 
@@ -317,13 +337,13 @@ impl NetworkAnimator {
             if dirty_bits & (1 << i) == 0 {
                 continue;
             }
-            let par = &self.animator.parameters[i];
+            let par = &mut self.animator.parameters[i];
             if par.r#type == AnimatorParameterType::Int {
-                let _int_value = reader.read_blittable::<i32>();
+                par.set_integer(reader.read_blittable::<i32>());
             } else if par.r#type == AnimatorParameterType::Float {
-                let _float_value = reader.read_blittable::<f32>();
+                par.set_float(reader.read_blittable::<f32>());
             } else if par.r#type == AnimatorParameterType::Bool {
-                let _bool_value = reader.read_blittable::<bool>();
+                par.set_bool(reader.read_blittable::<bool>());
             }
         }
     }
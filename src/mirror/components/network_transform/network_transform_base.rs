@@ -119,8 +119,8 @@ impl NetworkTransformBase {
     pub fn get_position(&self) -> Vector3<f32> {
         if let Some(target) = self.target.get() {
             return match self.coordinate_space {
-                CoordinateSpace::Local => target.local_position,
-                CoordinateSpace::World => target.position,
+                CoordinateSpace::Local => target.local_position(),
+                CoordinateSpace::World => target.position(),
             };
         }
         Vector3::new(0.0, 0.0, 0.0)
@@ -129,10 +129,10 @@ impl NetworkTransformBase {
         if let Some(target) = self.target.get() {
             match self.coordinate_space {
                 CoordinateSpace::Local => {
-                    target.local_position = value;
+                    target.set_local_position(value);
                 }
                 CoordinateSpace::World => {
-                    target.position = value;
+                    target.set_position(value);
                 }
             }
         }
@@ -141,8 +141,8 @@ impl NetworkTransformBase {
     pub fn get_rotation(&self) -> Quaternion<f32> {
         if let Some(target) = self.target.get() {
             return match self.coordinate_space {
-                CoordinateSpace::Local => target.local_rotation,
-                CoordinateSpace::World => target.rotation,
+                CoordinateSpace::Local => target.local_rotation(),
+                CoordinateSpace::World => target.rotation(),
             };
         }
         Quaternion::identity()
@@ -151,10 +151,10 @@ impl NetworkTransformBase {
         if let Some(target) = self.target.get() {
             match self.coordinate_space {
                 CoordinateSpace::Local => {
-                    target.local_rotation = value;
+                    target.set_local_rotation(value);
                 }
                 CoordinateSpace::World => {
-                    target.rotation = value;
+                    target.set_rotation(value);
                 }
             }
         }
@@ -162,13 +162,13 @@ impl NetworkTransformBase {
 
     pub fn get_scale(&self) -> Vector3<f32> {
         if let Some(target) = self.target.get() {
-            return target.local_scale;
+            return target.local_scale();
         }
         Vector3::new(1.0, 1.0, 1.0)
     }
     pub fn set_scale(&self, value: Vector3<f32>) {
         if let Some(target) = self.target.get() {
-            target.local_scale = value;
+            target.set_local_scale(value);
         }
     }
 }
@@ -0,0 +1,325 @@
+use crate::mirror::{NetworkReader, NetworkWriter, ReadError};
+use nalgebra::{Quaternion, Vector2, Vector3, Vector4};
+
+// 同步变量的增量压缩
+// 每个观察者保存上次发送的同步变量快照作为基线 (状态走 Reliable 通道，发送即视为已确认)，
+// 只写与基线不同的字段，格式为：
+//   varuint 位段字节数
+//   位段：每个字段 1 位变化标记，变化的量化字段紧跟其后
+//   字节段：变化的非量化字段，按字段顺序使用 DataTypeSerializer
+
+/// 按位写入，低位在前
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BitWriter {
+    buffer: Vec<u8>,
+    bit_length: usize,
+}
+
+impl BitWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn bit_length(&self) -> usize {
+        self.bit_length
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    pub fn write_bits(&mut self, value: u64, bits: u8) {
+        for i in 0..bits {
+            if self.bit_length.is_multiple_of(8) {
+                self.buffer.push(0);
+            }
+            if (value >> i) & 1 != 0 {
+                self.buffer[self.bit_length / 8] |= 1 << (self.bit_length % 8);
+            }
+            self.bit_length += 1;
+        }
+    }
+
+    pub fn write_bool(&mut self, value: bool) {
+        self.write_bits(value as u64, 1);
+    }
+
+    pub fn append(&mut self, other: &BitWriter) {
+        for (i, byte) in other.buffer.iter().enumerate() {
+            let bits = (other.bit_length - i * 8).min(8) as u8;
+            self.write_bits(*byte as u64, bits);
+        }
+    }
+}
+
+/// 按位读取，超出数据长度时返回 EndOfStream
+#[derive(Debug, Clone, Default)]
+pub struct BitReader {
+    buffer: Vec<u8>,
+    bit_position: usize,
+}
+
+impl BitReader {
+    pub fn new(buffer: Vec<u8>) -> Self {
+        Self {
+            buffer,
+            bit_position: 0,
+        }
+    }
+
+    pub fn remaining_bits(&self) -> usize {
+        self.buffer.len() * 8 - self.bit_position
+    }
+
+    pub fn read_bits(&mut self, bits: u8) -> Result<u64, ReadError> {
+        if self.remaining_bits() < bits as usize {
+            return Err(ReadError::EndOfStream {
+                type_name: "bits",
+                needed: bits as usize,
+                remaining: self.remaining_bits(),
+            });
+        }
+        let mut value = 0u64;
+        for i in 0..bits {
+            let byte = self.buffer[self.bit_position / 8];
+            if (byte >> (self.bit_position % 8)) & 1 != 0 {
+                value |= 1 << i;
+            }
+            self.bit_position += 1;
+        }
+        Ok(value)
+    }
+
+    pub fn read_bool(&mut self) -> Result<bool, ReadError> {
+        Ok(self.read_bits(1)? != 0)
+    }
+}
+
+/// 浮点量化参数，对应 #[sync_var(quantize(min, max, precision))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quantization {
+    pub min: f32,
+    pub max: f32,
+    pub precision: f32,
+}
+
+impl Quantization {
+    pub const fn new(min: f32, max: f32, precision: f32) -> Self {
+        Self { min, max, precision }
+    }
+
+    fn steps(&self) -> u64 {
+        ((self.max - self.min) / self.precision).ceil().max(1.0) as u64
+    }
+
+    /// 每个分量占用的位数
+    pub fn bits(&self) -> u8 {
+        (u64::BITS - self.steps().leading_zeros()) as u8
+    }
+
+    /// 超出范围的值被截断，NaN 视为 min
+    pub fn quantize(&self, value: f32) -> u64 {
+        let value = if value.is_nan() { self.min } else { value.clamp(self.min, self.max) };
+        (((value - self.min) / self.precision).round() as u64).min(self.steps())
+    }
+
+    pub fn dequantize(&self, value: u64) -> f32 {
+        (self.min + value.min(self.steps()) as f32 * self.precision).min(self.max)
+    }
+
+    pub fn write(&self, value: f32, writer: &mut BitWriter) {
+        writer.write_bits(self.quantize(value), self.bits());
+    }
+
+    pub fn read(&self, reader: &mut BitReader) -> Result<f32, ReadError> {
+        Ok(self.dequantize(reader.read_bits(self.bits())?))
+    }
+}
+
+/// 可量化的同步变量类型，向量按分量量化
+pub trait Quantize: Default + Sized {
+    fn write_quantized(&self, quantization: &Quantization, writer: &mut BitWriter);
+
+    fn read_quantized(quantization: &Quantization, reader: &mut BitReader) -> Result<Self, ReadError>;
+}
+
+impl Quantize for f32 {
+    fn write_quantized(&self, quantization: &Quantization, writer: &mut BitWriter) {
+        quantization.write(*self, writer);
+    }
+
+    fn read_quantized(quantization: &Quantization, reader: &mut BitReader) -> Result<Self, ReadError> {
+        quantization.read(reader)
+    }
+}
+
+impl Quantize for f64 {
+    fn write_quantized(&self, quantization: &Quantization, writer: &mut BitWriter) {
+        quantization.write(*self as f32, writer);
+    }
+
+    fn read_quantized(quantization: &Quantization, reader: &mut BitReader) -> Result<Self, ReadError> {
+        Ok(quantization.read(reader)? as f64)
+    }
+}
+
+macro_rules! vector_quantize {
+    ($($ty:ident => $n:literal),*) => {
+        $(
+            impl Quantize for $ty<f32> {
+                fn write_quantized(&self, quantization: &Quantization, writer: &mut BitWriter) {
+                    for value in self.iter() {
+                        quantization.write(*value, writer);
+                    }
+                }
+
+                fn read_quantized(quantization: &Quantization, reader: &mut BitReader) -> Result<Self, ReadError> {
+                    let mut values = [0f32; $n];
+                    for value in values.iter_mut() {
+                        *value = quantization.read(reader)?;
+                    }
+                    Ok(Self::from(values))
+                }
+            }
+        )*
+    };
+}
+
+vector_quantize!(Vector2 => 2, Vector3 => 3, Vector4 => 4);
+
+impl Quantize for Quaternion<f32> {
+    fn write_quantized(&self, quantization: &Quantization, writer: &mut BitWriter) {
+        self.coords.write_quantized(quantization, writer);
+    }
+
+    fn read_quantized(quantization: &Quantization, reader: &mut BitReader) -> Result<Self, ReadError> {
+        Ok(Quaternion::from(Vector4::read_quantized(quantization, reader)?))
+    }
+}
+
+/// 一个同步变量编码后的值，量化字段写入位段，其余写入字节段
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyncVarDeltaField {
+    Bits(BitWriter),
+    Bytes(Vec<u8>),
+}
+
+/// 组件全部同步变量编码后的值，按字段顺序
+pub type SyncVarSnapshot = Vec<SyncVarDeltaField>;
+
+/// 写入 snapshot 相对 baseline 的变化，没有变化时不写入并返回 false
+pub fn write_sync_var_delta(
+    snapshot: &SyncVarSnapshot,
+    baseline: Option<&SyncVarSnapshot>,
+    writer: &mut NetworkWriter,
+) -> bool {
+    let mut bits = BitWriter::new();
+    let mut bytes = NetworkWriter::new();
+    let mut changed = false;
+
+    for (i, field) in snapshot.iter().enumerate() {
+        let field_changed = baseline.and_then(|baseline| baseline.get(i)) != Some(field);
+        bits.write_bool(field_changed);
+        if !field_changed {
+            continue;
+        }
+        changed = true;
+        match field {
+            SyncVarDeltaField::Bits(value) => bits.append(value),
+            SyncVarDeltaField::Bytes(value) => bytes.write_slice(value, 0, value.len()),
+        }
+    }
+
+    if changed {
+        writer.write_slice_and_size(bits.as_bytes());
+        writer.write_slice(bytes.to_slice(), 0, bytes.position);
+    }
+    changed
+}
+
+/// 读取增量数据，失败时记录到 NetworkReader 并视为没有变化
+#[derive(Debug, Default)]
+pub struct SyncVarDeltaReader {
+    bits: BitReader,
+}
+
+impl SyncVarDeltaReader {
+    pub fn new(reader: &mut NetworkReader) -> Self {
+        Self {
+            bits: BitReader::new(reader.read_slice_and_size().to_vec()),
+        }
+    }
+
+    /// 下一个字段是否有变化
+    pub fn changed(&mut self, reader: &mut NetworkReader) -> bool {
+        if reader.has_error() {
+            return false;
+        }
+        self.bits.read_bool().unwrap_or_else(|err| {
+            reader.fail(err);
+            false
+        })
+    }
+
+    pub fn read_quantized<T: Quantize>(&mut self, quantization: &Quantization, reader: &mut NetworkReader) -> T {
+        T::read_quantized(quantization, &mut self.bits).unwrap_or_else(|err| {
+            reader.fail(err);
+            T::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sync_var_delta() {
+        let quantization = Quantization::new(-100.0, 100.0, 0.01);
+        assert_eq!(quantization.bits(), 15);
+
+        let snapshot = |position: Vector3<f32>, name: &str| {
+            let mut bits = BitWriter::new();
+            position.write_quantized(&quantization, &mut bits);
+            let mut bytes = NetworkWriter::new();
+            bytes.write_str(name);
+            vec![SyncVarDeltaField::Bits(bits), SyncVarDeltaField::Bytes(bytes.to_vec())]
+        };
+
+        let baseline = snapshot(Vector3::new(1.0, 2.0, 3.0), "flag");
+        // 小于精度的变化量化后相同，不发送
+        let mut writer = NetworkWriter::new();
+        assert!(!write_sync_var_delta(&snapshot(Vector3::new(1.001, 2.0, 3.0), "flag"), Some(&baseline), &mut writer));
+        assert_eq!(writer.position, 0);
+
+        // 只有位置变化：变化标记 2 位 + 3 * 15 位
+        let current = snapshot(Vector3::new(-4.5, 2.0, 300.0), "flag");
+        assert!(write_sync_var_delta(&current, Some(&baseline), &mut writer));
+        assert_eq!(writer.position, 1 + 6);
+
+        let mut reader = NetworkReader::new(writer.to_vec());
+        let mut delta = SyncVarDeltaReader::new(&mut reader);
+        assert!(delta.changed(&mut reader));
+        let position: Vector3<f32> = delta.read_quantized(&quantization, &mut reader);
+        assert!((position - Vector3::new(-4.5, 2.0, 100.0)).norm() < 0.01);
+        assert!(!delta.changed(&mut reader));
+        assert!(!reader.has_error());
+
+        // 没有基线时全部发送
+        let mut writer = NetworkWriter::new();
+        assert!(write_sync_var_delta(&current, None, &mut writer));
+        let mut reader = NetworkReader::new(writer.to_vec());
+        let mut delta = SyncVarDeltaReader::new(&mut reader);
+        assert!(delta.changed(&mut reader));
+        let _: Vector3<f32> = delta.read_quantized(&quantization, &mut reader);
+        assert!(delta.changed(&mut reader));
+        assert_eq!(reader.read_string(), "flag");
+
+        // 位段不足
+        let mut reader = NetworkReader::new(vec![2, 0b1]);
+        let mut delta = SyncVarDeltaReader::new(&mut reader);
+        assert!(delta.changed(&mut reader));
+        let _: f32 = delta.read_quantized(&quantization, &mut reader);
+        assert!(matches!(reader.error(), Some(ReadError::EndOfStream { .. })));
+    }
+}
@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+
+// EntityStateMessage 除数据外的大致开销：消息 id、netId 和数据长度
+const ENTITY_STATE_OVERHEAD: usize = 8;
+
+/// 本帧要发送给连接的实体状态
+#[derive(Debug, PartialEq)]
+pub enum EntityStateUpdate {
+    /// 本帧的增量数据
+    Delta(Vec<u8>),
+    /// 增量数据推迟期间又产生了新的变化，重新发送完整状态
+    Resync,
+}
+
+struct PendingEntityState {
+    // 每个实体只保留一个未发送的增量，再次变化时改为发送完整状态，队列不会随帧数增长
+    update: EntityStateUpdate,
+    size: usize,
+    // 优先级累加值，未发送的每帧加上当帧优先级，发送后清零
+    priority: f32,
+}
+
+/// 连接的实体状态发送调度
+/// 带宽超出预算时，优先级低的实体状态推迟到之后的帧发送，等待越久优先级越高
+#[derive(Default)]
+pub struct EntityStateScheduler {
+    /// 每秒可发送的实体状态字节数，None 时使用 NetworkServer.max_bandwidth_per_connection，0 表示不限制
+    pub max_bandwidth: Option<usize>,
+    pending: HashMap<u32, PendingEntityState>,
+    // 可发送的字节数，最多累积 1 秒
+    allowance: f64,
+    last_time: Option<f64>,
+}
+
+impl EntityStateScheduler {
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn pending_bytes(&self) -> usize {
+        self.pending.values().map(|state| state.size).sum()
+    }
+
+    pub(crate) fn push(&mut self, net_id: u32, payload: Vec<u8>) {
+        let size = payload.len() + ENTITY_STATE_OVERHEAD;
+        match self.pending.get_mut(&net_id) {
+            None => {
+                self.pending.insert(
+                    net_id,
+                    PendingEntityState {
+                        update: EntityStateUpdate::Delta(payload),
+                        size,
+                        priority: 0.0,
+                    },
+                );
+            }
+            // 完整状态的大小在发送时才知道，按已知的最大增量估算
+            Some(state) => {
+                state.update = EntityStateUpdate::Resync;
+                state.size = state.size.max(size);
+            }
+        }
+    }
+
+    /// 不再观察或已销毁的对象不再发送
+    pub(crate) fn remove(&mut self, net_id: u32) {
+        self.pending.remove(&net_id);
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.pending.clear();
+    }
+
+    pub(crate) fn pending_net_ids(&self) -> Vec<u32> {
+        self.pending.keys().copied().collect()
+    }
+
+    pub(crate) fn accumulate(&mut self, net_id: u32, priority: f32) {
+        if let Some(state) = self.pending.get_mut(&net_id) {
+            state.priority += priority.max(0.0);
+        }
+    }
+
+    /// 取出本帧可发送的实体状态，按优先级从高到低
+    pub(crate) fn take(&mut self, now: f64, max_bandwidth: usize) -> Vec<(u32, EntityStateUpdate)> {
+        if max_bandwidth == 0 {
+            self.last_time = None;
+            return self
+                .pending
+                .drain()
+                .map(|(net_id, state)| (net_id, state.update))
+                .collect();
+        }
+
+        let max_bandwidth = max_bandwidth as f64;
+        self.allowance = match self.last_time {
+            None => max_bandwidth,
+            Some(last_time) => (self.allowance + (now - last_time).max(0.0) * max_bandwidth).min(max_bandwidth),
+        };
+        self.last_time = Some(now);
+
+        let mut order = self
+            .pending
+            .iter()
+            .map(|(net_id, state)| (*net_id, state.priority))
+            .collect::<Vec<_>>();
+        order.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        let mut sent = Vec::new();
+        for (net_id, _) in order {
+            let size = self.pending[&net_id].size as f64;
+            // 单个超过预算的状态在预算攒满时发送，避免永远无法发出
+            let oversized = sent.is_empty() && self.allowance >= max_bandwidth;
+            if size > self.allowance && !oversized {
+                continue;
+            }
+            self.allowance -= size;
+            if let Some(state) = self.pending.remove(&net_id) {
+                sent.push((net_id, state.update));
+            }
+        }
+        sent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bandwidth_budget() {
+        let mut scheduler = EntityStateScheduler::default();
+        // 每秒 100 字节，每个状态 50 + 8 字节
+        for net_id in 1..=3 {
+            scheduler.push(net_id, vec![0; 50]);
+            scheduler.accumulate(net_id, net_id as f32);
+        }
+        let sent = scheduler.take(0.0, 100);
+        assert_eq!(sent.iter().map(|(net_id, _)| *net_id).collect::<Vec<_>>(), vec![3]);
+        assert_eq!(scheduler.pending_count(), 2);
+
+        // 推迟的状态继续累加优先级，再次变化时改为发送完整状态
+        scheduler.push(1, vec![1; 10]);
+        scheduler.push(3, vec![0; 50]);
+        for net_id in 1..=3 {
+            scheduler.accumulate(net_id, net_id as f32);
+        }
+        let sent = scheduler.take(0.5, 100);
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].0, 2);
+
+        // 等待越久优先级越高
+        scheduler.accumulate(1, 5.0);
+        let sent = scheduler.take(1.5, 100);
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].0, 1);
+        assert_eq!(sent[0].1, EntityStateUpdate::Resync);
+
+        // 不限制时全部发送
+        let sent = scheduler.take(1.5, 0);
+        assert_eq!(sent, vec![(3, EntityStateUpdate::Delta(vec![0; 50]))]);
+        assert_eq!(scheduler.pending_count(), 0);
+    }
+
+    #[test]
+    fn test_bandwidth_budget_over_many_ticks() {
+        let mut scheduler = EntityStateScheduler::default();
+        // 每秒 600 字节、每帧 0.1 秒，10 个实体每帧各产生 58 字节，远超预算
+        let mut sent_bytes = 0.0;
+        for tick in 0..1000 {
+            for net_id in 1..=10 {
+                scheduler.push(net_id, vec![0; 50]);
+                scheduler.accumulate(net_id, 1.0);
+            }
+            let sent = scheduler.take(tick as f64 * 0.1, 600);
+            sent_bytes += sent.len() as f64 * 58.0;
+            assert!(scheduler.pending_count() <= 10);
+            assert!(scheduler.pending_bytes() <= 10 * 58);
+            // 最多多出第一帧攒满的 1 秒预算
+            assert!(sent_bytes <= 600.0 * (tick as f64 * 0.1 + 1.0));
+        }
+        // 每个实体最终都能发出
+        assert!(sent_bytes >= 600.0 * 90.0);
+    }
+}
@@ -1,7 +1,3 @@
-use crate::mirror::messages::message::{MessageDeserializer, MessageSerializer};
-use crate::mirror::stable_hash::StableHash;
-use crate::mirror::NetworkReader;
-use crate::mirror::NetworkWriter;
 use crate::macro_namespace::*;
 use crate::macro_network_message::*;
 
@@ -15,22 +11,3 @@ impl AddPlayerMessage {
         Self
     }
 }
-
-impl MessageSerializer for AddPlayerMessage {
-    fn serialize(&mut self, writer: &mut NetworkWriter)
-    where
-        Self: Sized,
-    {
-        writer.write_blittable(Self::get_full_name().hash16());
-    }
-}
-
-impl MessageDeserializer for AddPlayerMessage {
-    fn deserialize(reader: &mut NetworkReader) -> Self
-    where
-        Self: Sized,
-    {
-        let _ = reader;
-        Self
-    }
-}
@@ -1,8 +1,4 @@
 use crate::macro_namespace::*;
-use crate::mirror::messages::message::{MessageDeserializer, MessageSerializer};
-use crate::mirror::stable_hash::StableHash;
-use crate::mirror::NetworkReader;
-use crate::mirror::NetworkWriter;
 use crate::macro_network_message::*;
 
 #[namespace(prefix = "Mirror")]
@@ -23,28 +19,3 @@ impl EntityStateMessage {
         self.payload[4..].to_vec()
     }
 }
-
-impl MessageSerializer for EntityStateMessage {
-    fn serialize(&mut self, writer: &mut NetworkWriter)
-    where
-        Self: Sized,
-    {
-        writer.write_blittable(Self::get_full_name().hash16());
-        writer.write_blittable_compress(self.net_id);
-        writer.write_slice_and_size(self.payload.as_slice());
-    }
-}
-
-impl MessageDeserializer for EntityStateMessage {
-    fn deserialize(reader: &mut NetworkReader) -> Self
-    where
-        Self: Sized,
-    {
-        let net_id = reader.read_blittable_compress();
-        let payload = reader.read_slice_and_size();
-        Self {
-            net_id,
-            payload: payload.to_vec(),
-        }
-    }
-}
@@ -39,6 +39,10 @@ impl MessageHandler {
         // 将泛型函数包装为动态分发函数
         let wrapped_func: MessageHandlerWrappedFuncType = Box::new(move |conn, reader, channel| {
             let msg = M::deserialize(reader);
+            // 读取失败时不处理，由 NetworkServer 断开发送方
+            if reader.has_error() {
+                return;
+            }
             func.call((conn, msg, channel))
         });
         Self {
@@ -81,3 +85,90 @@ pub fn max_message_size(channel: TransportChannel) -> usize {
 pub fn max_content_size(_channel: TransportChannel) -> usize {
     1500
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::macro_namespace::*;
+    use crate::macro_network_message::*;
+    use crate::mirror::messages::command_message::CommandMessage;
+    use crate::mirror::messages::scene_message::{SceneMessage, SceneOperation};
+    use crate::mirror::round_trip_message;
+
+    #[namespace(prefix = "Test")]
+    #[derive(Debug, PartialEq, Default, NetworkMessage)]
+    struct SkipMessage {
+        id: u32,
+        #[message(skip)]
+        cache: String,
+        #[message(serialize_with = write_percent, deserialize_with = read_percent)]
+        ratio: f32,
+    }
+
+    fn write_percent(value: &f32, writer: &mut NetworkWriter) {
+        writer.write_blittable((*value * 100.0) as u8);
+    }
+
+    fn read_percent(reader: &mut NetworkReader) -> f32 {
+        reader.read_blittable::<u8>() as f32 / 100.0
+    }
+
+    #[namespace(prefix = "Test")]
+    #[derive(Debug, PartialEq, Default, NetworkMessage)]
+    #[repr(u16)]
+    enum EventMessage {
+        #[default]
+        None,
+        Hit { target: u32, damage: f32 } = 5,
+        Chat(String),
+    }
+
+    #[namespace(prefix = "Test")]
+    #[derive(Debug, PartialEq, NetworkMessage)]
+    struct EnvelopeMessage<T> {
+        value: T,
+    }
+
+    #[test]
+    fn test_derive_message() {
+        // 与手写格式一致
+        let (bytes, message) = round_trip_message(CommandMessage::new(300, 1, 0xABCD, vec![7, 8]));
+        let mut writer = NetworkWriter::new();
+        writer.write_blittable(CommandMessage::get_full_name().hash16());
+        writer.write_blittable_compress(300u32);
+        writer.write_blittable(1u8);
+        writer.write_blittable(0xABCDu16);
+        writer.write_slice_and_size(&[7, 8]);
+        assert_eq!(bytes, writer.to_vec());
+        assert_eq!(message.payload, vec![7, 8]);
+
+        let (_, message) = round_trip_message(SceneMessage::new("Room".to_string(), SceneOperation::LoadAdditive, true));
+        assert_eq!(message.operation, SceneOperation::LoadAdditive);
+        // 未知的 SceneOperation
+        let mut reader = NetworkReader::new(vec![3]);
+        assert!(matches!(reader.try_read::<SceneOperation>(), Err(ReadError::InvalidValue { .. })));
+
+        let (bytes, message) = round_trip_message(SkipMessage {
+            id: 1,
+            cache: "local".to_string(),
+            ratio: 0.5,
+        });
+        assert_eq!(bytes.len(), 2 + 1 + 1);
+        assert_eq!(message.cache, "");
+        assert_eq!(message.ratio, 0.5);
+
+        let (bytes, message) = round_trip_message(EventMessage::Hit { target: 2, damage: 1.5 });
+        assert_eq!(&bytes[2..4], &5u16.to_le_bytes());
+        assert_eq!(message, EventMessage::Hit { target: 2, damage: 1.5 });
+        let (bytes, message) = round_trip_message(EventMessage::Chat("hi".to_string()));
+        assert_eq!(&bytes[2..4], &6u16.to_le_bytes());
+        assert_eq!(message, EventMessage::Chat("hi".to_string()));
+
+        let (_, message) = round_trip_message(EnvelopeMessage { value: vec![1u32, 2] });
+        assert_eq!(message.value, vec![1, 2]);
+
+        // 未知标签
+        let mut reader = NetworkReader::new(vec![9, 0]);
+        assert_eq!(EventMessage::deserialize(&mut reader), EventMessage::None);
+        assert!(matches!(reader.error(), Some(ReadError::InvalidValue { .. })));
+    }
+}
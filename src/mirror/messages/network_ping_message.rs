@@ -1,8 +1,4 @@
 use crate::macro_namespace::*;
-use crate::mirror::messages::message::{MessageDeserializer, MessageSerializer};
-use crate::mirror::stable_hash::StableHash;
-use crate::mirror::NetworkReader;
-use crate::mirror::NetworkWriter;
 use crate::macro_network_message::*;
 
 #[namespace(prefix = "Mirror")]
@@ -21,28 +17,3 @@ impl NetworkPingMessage {
         }
     }
 }
-
-impl MessageSerializer for NetworkPingMessage {
-    fn serialize(&mut self, writer: &mut NetworkWriter)
-    where
-        Self: Sized,
-    {
-        writer.write_blittable(Self::get_full_name().hash16());
-        writer.write_blittable(self.local_time);
-        writer.write_blittable(self.predicted_time_adjusted);
-    }
-}
-
-impl MessageDeserializer for NetworkPingMessage {
-    fn deserialize(reader: &mut NetworkReader) -> Self
-    where
-        Self: Sized,
-    {
-        let local_time = reader.read_blittable();
-        let predicted_time_adjusted = reader.read_blittable();
-        Self {
-            local_time,
-            predicted_time_adjusted,
-        }
-    }
-}
@@ -1,8 +1,4 @@
 use crate::macro_namespace::*;
-use crate::mirror::messages::message::{MessageDeserializer, MessageSerializer};
-use crate::mirror::stable_hash::StableHash;
-use crate::mirror::NetworkReader;
-use crate::mirror::NetworkWriter;
 use crate::macro_network_message::*;
 
 #[namespace(prefix = "Mirror")]
@@ -27,31 +23,3 @@ impl NetworkPongMessage {
         }
     }
 }
-
-impl MessageSerializer for NetworkPongMessage {
-    fn serialize(&mut self, writer: &mut NetworkWriter)
-    where
-        Self: Sized,
-    {
-        writer.write_blittable(Self::get_full_name().hash16());
-        writer.write_blittable(self.local_time);
-        writer.write_blittable(self.prediction_error_unadjusted);
-        writer.write_blittable(self.prediction_error_adjusted);
-    }
-}
-
-impl MessageDeserializer for NetworkPongMessage {
-    fn deserialize(reader: &mut NetworkReader) -> Self
-    where
-        Self: Sized,
-    {
-        let local_time = reader.read_blittable();
-        let prediction_error_unadjusted = reader.read_blittable();
-        let prediction_error_adjusted = reader.read_blittable();
-        Self {
-            local_time,
-            prediction_error_unadjusted,
-            prediction_error_adjusted,
-        }
-    }
-}
@@ -1,8 +1,4 @@
 use crate::macro_namespace::*;
-use crate::mirror::messages::message::{MessageDeserializer, MessageSerializer};
-use crate::mirror::stable_hash::StableHash;
-use crate::mirror::NetworkReader;
-use crate::mirror::NetworkWriter;
 use crate::macro_network_message::*;
 
 #[namespace(prefix = "Mirror")]
@@ -17,23 +13,3 @@ impl ObjectHideMessage {
         Self { net_id }
     }
 }
-
-impl MessageSerializer for ObjectHideMessage {
-    fn serialize(&mut self, writer: &mut NetworkWriter)
-    where
-        Self: Sized,
-    {
-        writer.write_blittable(Self::get_full_name().hash16());
-        writer.write_blittable_compress(self.net_id);
-    }
-}
-
-impl MessageDeserializer for ObjectHideMessage {
-    fn deserialize(reader: &mut NetworkReader) -> Self
-    where
-        Self: Sized,
-    {
-        let net_id = reader.read_blittable_compress();
-        Self { net_id }
-    }
-}
@@ -1,8 +1,4 @@
 use crate::macro_namespace::*;
-use crate::mirror::messages::message::{MessageDeserializer, MessageSerializer};
-use crate::mirror::stable_hash::StableHash;
-use crate::mirror::NetworkReader;
-use crate::mirror::NetworkWriter;
 use crate::macro_network_message::*;
 
 #[namespace(prefix = "Mirror")]
@@ -15,21 +11,3 @@ impl ObjectSpawnStartedMessage {
         Self {}
     }
 }
-
-impl MessageSerializer for ObjectSpawnStartedMessage {
-    fn serialize(&mut self, writer: &mut NetworkWriter)
-    where
-        Self: Sized,
-    {
-        writer.write_blittable(Self::get_full_name().hash16());
-    }
-}
-
-impl MessageDeserializer for ObjectSpawnStartedMessage {
-    fn deserialize(_: &mut NetworkReader) -> Self
-    where
-        Self: Sized,
-    {
-        Self
-    }
-}
@@ -1,11 +1,9 @@
 use crate::macro_namespace::*;
-use crate::mirror::messages::message::{MessageDeserializer, MessageSerializer};
-use crate::mirror::stable_hash::StableHash;
-use crate::mirror::NetworkReader;
-use crate::mirror::NetworkWriter;
 use crate::macro_network_message::*;
+use crate::macro_network_serialize::*;
 
-#[derive(Debug, PartialEq, Clone, Copy, Default)]
+// 按 u8 标签读写，未知标签读取失败
+#[derive(Debug, PartialEq, Clone, Copy, Default, NetworkSerialize, NetworkDeserialize)]
 #[repr(u8)]
 pub enum SceneOperation {
     #[default]
@@ -13,19 +11,6 @@ pub enum SceneOperation {
     LoadAdditive = 1,
     UnloadAdditive = 2,
 }
-impl SceneOperation {
-    pub fn from(value: u8) -> SceneOperation {
-        match value {
-            0 => SceneOperation::Normal,
-            1 => SceneOperation::LoadAdditive,
-            2 => SceneOperation::UnloadAdditive,
-            _ => SceneOperation::Normal,
-        }
-    }
-    pub fn to_u8(&self) -> u8 {
-        *self as u8
-    }
-}
 
 #[namespace(prefix = "Mirror")]
 #[derive(Debug, PartialEq, Clone, Default, NetworkMessage)]
@@ -49,31 +34,3 @@ impl SceneMessage {
         }
     }
 }
-
-impl MessageSerializer for SceneMessage {
-    fn serialize(&mut self, writer: &mut NetworkWriter)
-    where
-        Self: Sized,
-    {
-        writer.write_blittable(Self::get_full_name().hash16());
-        writer.write_str(self.scene_name.as_str());
-        writer.write_blittable(self.operation.to_u8());
-        writer.write_blittable(self.custom_handling);
-    }
-}
-
-impl MessageDeserializer for SceneMessage {
-    fn deserialize(reader: &mut NetworkReader) -> Self
-    where
-        Self: Sized,
-    {
-        let scene_name = reader.read_string();
-        let operation = SceneOperation::from(reader.read_blittable());
-        let custom_handling = reader.read_blittable();
-        Self {
-            scene_name,
-            operation,
-            custom_handling,
-        }
-    }
-}
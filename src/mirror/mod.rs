@@ -50,6 +50,12 @@ pub mod batching;
 pub mod compress;
 pub use compress::*;
 
+mod delta_compression;
+pub use delta_compression::*;
+
+mod entity_state_scheduler;
+pub use entity_state_scheduler::*;
+
 mod network_connection;
 pub use network_connection::*;
 
@@ -60,6 +66,27 @@ mod network_connection_trait;
 pub use network_connection_trait::*;
 
 
+mod network_object_pool;
+pub use network_object_pool::*;
+
+#[cfg(test)]
+mod test_transport;
+#[cfg(test)]
+pub(crate) use test_transport::*;
+
+#[cfg(test)]
+mod test_utils;
+#[cfg(test)]
+pub(crate) use test_utils::*;
+
+#[cfg(test)]
+mod test_network_behaviours;
+#[cfg(test)]
+pub(crate) use test_network_behaviours::*;
+
+mod network_references;
+pub use network_references::*;
+
 mod remote_calls;
 pub use remote_calls::*;
 
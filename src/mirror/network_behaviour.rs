@@ -6,6 +6,7 @@ use crate::mirror::messages::rpc_message::RpcMessage;
 use crate::mirror::transport::TransportChannel;
 use crate::mirror::NetworkReader;
 use crate::mirror::NetworkWriter;
+use crate::mirror::SyncVarSnapshot;
 use crate::mirror::{NetworkConnectionToClient, NetworkIdentity};
 use crate::macro_namespace::*;
 use crate::unity_engine::{GameObject, MonoBehaviour};
@@ -50,6 +51,8 @@ pub struct NetworkBehaviour {
     pub sync_direction: SyncDirection,
     pub sync_mode: SyncMode,
     pub sync_interval: f32,
+    // 带宽不足时的发送优先级
+    pub priority: f32,
     last_sync_time: f64,
 
     pub component_index: u8,
@@ -126,6 +129,7 @@ impl NetworkBehaviour {
             network_behaviour.sync_direction = config.sync_direction.clone().into();
             network_behaviour.sync_mode = config.sync_mode.clone().into();
             network_behaviour.sync_interval = config.sync_interval;
+            network_behaviour.priority = config.priority;
             network_behaviour.game_object = weak_game_object.clone();
             if let Some(game_object) = weak_game_object.get() {
                 network_behaviour.transform = game_object.transform.downgrade();
@@ -215,6 +219,14 @@ impl NetworkBehaviourBase for NetworkBehaviour {
         self.network_identity = weak_identity;
     }
 
+    fn get_component_index(&self) -> u8 {
+        self.component_index
+    }
+
+    fn get_network_identity(&self) -> &RevelWeak<Box<NetworkIdentity>> {
+        &self.network_identity
+    }
+
     fn is_dirty(&self) -> bool {
         (self.sync_var_dirty_bits | self.sync_object_dirty_bits) != 0u64
             && Time::unscaled_time_f64() - self.last_sync_time > self.sync_interval as f64
@@ -228,6 +240,10 @@ impl NetworkBehaviourBase for NetworkBehaviour {
         &self.sync_mode
     }
 
+    fn get_priority(&self) -> f32 {
+        self.priority
+    }
+
     fn clear_all_dirty_bits(&mut self) {
         self.sync_var_dirty_bits = 0;
         self.sync_object_dirty_bits = 0;
@@ -297,9 +313,12 @@ MonoBehaviour + NetworkBehaviourBase + NetworkBehaviourSerializer + NetworkBehav
 }
 pub trait NetworkBehaviourBase {
     fn initialize(&mut self, index: u8, weak_identity: RevelWeak<Box<NetworkIdentity>>);
+    fn get_component_index(&self) -> u8;
+    fn get_network_identity(&self) -> &RevelWeak<Box<NetworkIdentity>>;
     fn is_dirty(&self) -> bool;
     fn get_sync_direction(&self) -> &SyncDirection;
     fn get_sync_mode(&self) -> &SyncMode;
+    fn get_priority(&self) -> f32;
     fn clear_all_dirty_bits(&mut self);
 }
 
@@ -313,6 +332,15 @@ pub trait NetworkBehaviourSerializer: NetworkBehaviourOnSerializer {
     fn serialize_objects_all(&mut self, writer: &mut NetworkWriter) {}
     fn serialize_sync_object_delta(&mut self, writer: &mut NetworkWriter) {}
     fn serialize_sync_vars(&mut self, writer: &mut NetworkWriter, initial_state: bool) {}
+    // 增量压缩，#[network_behaviour(.., delta_compression)] 生成
+    // 服务器写入数据后即把快照记为该连接的基线，依赖 EntityStateMessage 走可靠通道按序到达，
+    // 不能用于不可靠通道
+    fn is_delta_compressed(&self) -> bool {
+        false
+    }
+    fn sync_var_snapshot(&self) -> SyncVarSnapshot {
+        SyncVarSnapshot::new()
+    }
 }
 
 #[allow(unused)]
@@ -326,3 +354,77 @@ pub trait NetworkBehaviourDeserializer: NetworkBehaviourOnDeserializer {
     fn deserialize_sync_object_delta(&mut self, reader: &mut NetworkReader) {}
     fn deserialize_sync_vars(&mut self, reader: &mut NetworkReader, initial_state: bool) {}
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mirror::{network_prefab, HookBehaviour};
+
+    // 返回的 GameObject 持有组件链，需与组件一同保留
+    fn instantiate() -> (RevelArc<GameObject>, RevelArc<Box<HookBehaviour>>) {
+        let prefab = network_prefab("Hook", &["Tests.HookBehaviour"]).build().unwrap();
+        let game_object = GameObject::instantiate(&prefab);
+        let behaviour = game_object.try_get_component2::<HookBehaviour>().unwrap();
+        (game_object, behaviour)
+    }
+
+    fn round_trip(source: &mut HookBehaviour, target: &mut HookBehaviour, initial_state: bool) {
+        let mut writer = NetworkWriter::new();
+        source.on_serialize(&mut writer, initial_state);
+        let mut reader = NetworkReader::new(writer.to_vec());
+        target.on_deserialize(&mut reader, initial_state);
+        assert_eq!(reader.remaining(), 0);
+    }
+
+    #[test]
+    fn test_sync_var_hook() {
+        let (_game_object, mut behaviour) = instantiate();
+
+        // hook 只调用一次，相同的值不调用
+        behaviour.set_health(5);
+        behaviour.set_health(5);
+        assert_eq!(behaviour.health_changes, vec![(0, 5)]);
+        assert_eq!(*behaviour.get_health(), 5);
+        assert_eq!(behaviour.sync_var_dirty_bits, 0b01);
+
+        // set_xxx_without_dirty 调用 hook 但不修改脏标记
+        behaviour.clear_all_dirty_bits();
+        behaviour.set_health_without_dirty(7);
+        assert_eq!(behaviour.health_changes, vec![(0, 5), (5, 7)]);
+        assert_eq!(behaviour.sync_var_dirty_bits, 0);
+    }
+
+    #[test]
+    fn test_sync_var_deserialize() {
+        let (_source_object, mut source) = instantiate();
+        let (_target_object, mut target) = instantiate();
+        source.set_health(5);
+        source.set_name("tank".to_string());
+
+        // 初始状态只调用 hook_on_initial 的 hook
+        round_trip(&mut source, &mut target, true);
+        assert_eq!((*target.get_health(), target.get_name().as_str()), (5, "tank"));
+        assert!(target.health_changes.is_empty());
+        assert_eq!(target.name_changes, vec![(String::new(), "tank".to_string())]);
+        assert_eq!(target.sync_var_dirty_bits, 0);
+
+        // 非初始状态只包含脏的变量，并调用 hook
+        source.clear_all_dirty_bits();
+        source.set_health(9);
+        round_trip(&mut source, &mut target, false);
+        assert_eq!(*target.get_health(), 9);
+        assert_eq!(target.health_changes, vec![(5, 9)]);
+        assert_eq!(target.name_changes.len(), 1);
+    }
+
+    #[test]
+    fn test_derived_command_signature() {
+        use crate::mirror::{RemoteProcedureCalls, StableHash};
+
+        // 派生类型取 #[namespace] 的全名，Vec 和切片与 Weaver 的参数类型名一致
+        let signature = "System.Void Tests.HookBehaviour::CmdPickUp(\
+            Tests.Loot,System.Collections.Generic.List`1<Tests.Loot>,Tests.Loot[])";
+        let hash = signature.fn_hash();
+        assert_eq!(RemoteProcedureCalls.get_function_method_name(hash).as_deref(), Some(signature));
+    }
+}
@@ -7,6 +7,7 @@ use crate::mirror::snapshot_interpolation::snapshot_interpolation::SnapshotInter
 use crate::mirror::snapshot_interpolation::snapshot_interpolation_settings::SnapshotInterpolationSettings;
 use crate::mirror::snapshot_interpolation::time_snapshot::TimeSnapshot;
 use crate::mirror::transport::{TransportChannel, TransportManager};
+use crate::mirror::EntityStateScheduler;
 use crate::mirror::NetworkTime;
 use crate::mirror::NetworkWriter;
 use crate::mirror::{NetworkConnection, NetworkIdentity, NetworkServer, RemovePlayerOptions};
@@ -29,6 +30,9 @@ pub struct NetworkConnectionToClient {
     pub address: String,
     /// 此连接可以观察到的网络对象。
     pub observing: HashSet<RevelWeak<Box<NetworkIdentity>>>,
+    /// 连接所在的场景实例句柄，0 表示未分配，只能观察非隔离场景中的对象。
+    /// 通过 NetworkServer::set_connection_scene 修改
+    pub scene_handle: u32,
     /// 用于处理网络消息的拆包操作。
     pub un_batcher: UnBatcher,
     /// 平均时间偏移，用于时间插值。
@@ -49,6 +53,10 @@ pub struct NetworkConnectionToClient {
     last_ping_time: f64,
     /// 往返时间（以秒为单位），表示消息从服务器到客户端再返回服务器所需的时间。
     pub rtt: ExponentialMovingAverage,
+    /// 服务器主动断开时的原因，断开回调中可读取
+    pub disconnect_reason: Option<String>,
+    /// 实体状态的带宽预算和待发送队列
+    pub entity_states: EntityStateScheduler,
 }
 
 impl NetworkConnectionToClient {
@@ -91,6 +99,13 @@ impl NetworkConnectionToClient {
         )
     }
 
+    /// 带原因断开连接
+    pub fn disconnect_with_reason(&mut self, reason: &str) {
+        log::info!("Disconnecting connectionId={}: {}", self.connection_id, reason);
+        self.disconnect_reason = Some(reason.to_string());
+        self.disconnect.call(());
+    }
+
     pub fn disconnect(&mut self) {
         self.is_ready = false;
         self.reliable_rpc.reset();
@@ -168,6 +183,7 @@ impl NetworkConnectionToClient {
     ) {
         self.observing
             .retain(|observing| !observing.ptr_eq(&identity.downgrade()));
+        self.entity_states.remove(identity.net_id());
 
         if !is_destroyed {
             if let Some(self_arc) = self.self_weak.upgrade() {
@@ -182,6 +198,7 @@ impl NetworkConnectionToClient {
             }
         }
         self.observing.clear();
+        self.entity_states.clear();
     }
 
     pub fn add_owned_object(&mut self, identity: RevelArc<Box<NetworkIdentity>>) {
@@ -8,17 +8,18 @@ use crate::mirror::NetworkReader;
 use crate::mirror::NetworkWriter;
 use crate::mirror::NetworkWriterPool;
 use crate::mirror::{
-    NetworkConnectionToClient, NetworkServer, RemoteCallType, RemoteProcedureCalls, SyncDirection,
-    SyncMode, TNetworkBehaviour,
+    write_sync_var_delta, NetworkConnectionToClient, NetworkServer, RemoteCallType, RemoteProcedureCalls,
+    SyncDirection, SyncMode, SyncVarSnapshot, TNetworkBehaviour,
 };
 use crate::unity_engine::MonoBehaviour;
 use crate::unity_engine::MonoBehaviourFactory;
 use crate::unity_engine::{GameObject, WorldManager};
 use lazy_static::lazy_static;
-use once_cell::sync::Lazy;
+use crate::commons::thread_static;
 use std::any::{Any, TypeId};
 use std::collections::HashMap;
 use std::hash::Hash;
+use std::ops::Range;
 use std::sync::atomic::AtomicU32;
 use std::sync::atomic::Ordering::SeqCst;
 use crate::macro_namespace::*;
@@ -50,8 +51,11 @@ fn static_init() {
 lazy_static! {
     static ref NEXT_NETWORK_ID: AtomicU32 = AtomicU32::new(1);
 }
-#[allow(unused)]
-static mut SCENE_IDS: Lazy<HashMap<u64, RevelWeak<NetworkIdentity>>> = Lazy::new(|| HashMap::new());
+// 场景实例句柄 -> (sceneId -> 场景对象)，同一场景可加载多个实例，所以按实例区分
+type SceneObjects = HashMap<u64, RevelWeak<Box<NetworkIdentity>>>;
+thread_static! {
+    static mut SCENE_IDS: HashMap<u32, SceneObjects> = HashMap::new();
+}
 
 #[allow(unused)]
 pub(crate) trait IntoNum {
@@ -91,20 +95,38 @@ for crate::metadata_settings::MetadataVisibility
     }
 }
 
+// (组件索引, 数据范围)
+pub type ComponentRanges = Vec<(u8, Range<usize>)>;
+
 #[derive(Default)]
 pub struct NetworkIdentitySerialization {
     pub tick: u64,
     pub owner_writer: RevelArc<NetworkWriter>,
     pub observers_writer: RevelArc<NetworkWriter>,
+    // 各组件数据在 writer 中的范围，存在增量压缩组件时按连接重新拼装
+    pub owner_ranges: ComponentRanges,
+    pub observers_ranges: ComponentRanges,
+    pub delta_snapshots: Vec<ComponentDeltaSnapshot>,
 }
 
 impl NetworkIdentitySerialization {
     pub fn reset_writers(&mut self) {
         self.owner_writer.reset();
         self.observers_writer.reset();
+        self.owner_ranges.clear();
+        self.observers_ranges.clear();
+        self.delta_snapshots.clear();
     }
 }
 
+/// 开启增量压缩的组件在本帧的同步变量快照
+pub struct ComponentDeltaSnapshot {
+    pub component_index: u8,
+    pub snapshot: SyncVarSnapshot,
+    pub to_owner: bool,
+    pub to_observers: bool,
+}
+
 #[namespace(prefix = "Mirror")]
 #[derive(Default)]
 pub struct NetworkIdentity {
@@ -123,11 +145,17 @@ pub struct NetworkIdentity {
 
     pub scene_id: u64,
     _asset_id: u32,
+    // 与同一场景中其他对象的 sceneId 冲突，不会被生成
+    scene_id_conflict: bool,
     pub destroy_called: bool,
     pub visibility: Visibility,
 
     pub(crate) observers: HashMap<u64, RevelWeak<Box<NetworkConnectionToClient>>>,
     last_serialization: RevelArc<NetworkIdentitySerialization>,
+    // 增量压缩组件在每个观察者上的基线 connection_id -> component_index -> snapshot
+    delta_baselines: HashMap<u64, HashMap<u8, SyncVarSnapshot>>,
+    // 已清除脏标记但还有观察者未收到的增量压缩组件
+    delta_pending: u64,
 
     spawned_from_instantiate: bool,
     has_spawned: bool,
@@ -186,6 +214,7 @@ impl NetworkIdentity {
 
     pub fn remove_observer(&mut self, conn: RevelArc<Box<NetworkConnectionToClient>>) {
         self.observers.remove(&conn.connection_id);
+        self.delta_baselines.remove(&conn.connection_id);
     }
 
     pub fn set_client_owner(&mut self, arc: RevelArc<Box<NetworkConnectionToClient>>) {
@@ -271,11 +300,14 @@ impl NetworkIdentity {
         if self.last_serialization.tick != tick {
             self.last_serialization.reset_writers();
 
-            self.serialize_server(
+            let (owner_ranges, observers_ranges) = self.serialize_server(
                 false,
                 self.last_serialization.owner_writer.clone(),
                 self.last_serialization.observers_writer.clone(),
             );
+            self.last_serialization.owner_ranges = owner_ranges;
+            self.last_serialization.observers_ranges = observers_ranges;
+            self.last_serialization.delta_snapshots = self.delta_snapshots();
             self.last_serialization.tick = tick;
         }
 
@@ -294,6 +326,11 @@ impl NetworkIdentity {
 
         for (i, network_behaviour_chain) in self.network_behaviours.iter().enumerate() {
             if let Some(network_behaviour) = network_behaviour_chain.last().and_then(|x| x.get()) {
+                // 增量压缩组件按连接单独序列化
+                if !initial_state && network_behaviour.is_delta_compressed() {
+                    continue;
+                }
+
                 let nth_bit = 1u64 << (i as u8);
                 let dirty = network_behaviour.is_dirty();
 
@@ -313,12 +350,15 @@ impl NetworkIdentity {
         (mask & (1u64 << index)) != 0
     }
 
+    // 返回各组件在 owner_writer / observers_writer 中的范围
     pub(crate) fn serialize_server(
         &self,
         initial_state: bool,
         mut owner_writer: RevelArc<NetworkWriter>,
         mut observers_writer: RevelArc<NetworkWriter>,
-    ) {
+    ) -> (ComponentRanges, ComponentRanges) {
+        let mut owner_ranges = Vec::new();
+        let mut observers_ranges = Vec::new();
         let (owner_mask, observer_mask) = self.server_dirty_masks(initial_state);
 
         if owner_mask != 0 {
@@ -338,25 +378,18 @@ impl NetworkIdentity {
                         // serialize
                         if let Some(last) = network_behaviour_chain.last() {
                             if let Some(comp) = last.get() {
-                                let header_position = writer.position;
-                                writer.write_byte(0);
-                                let content_position = writer.position;
-
-                                comp.on_serialize(writer, initial_state);
-
-                                let end_position = writer.position;
-                                writer.position = header_position;
-                                let size = (end_position - content_position) as i32;
-                                let safety = (size & 0xFF) as u8;
-                                writer.write_byte(safety);
-                                writer.position = end_position;
+                                Self::write_component(writer, |writer| comp.on_serialize(writer, initial_state));
                             }
                         }
                         if owner_dirty {
+                            let start = owner_writer.position;
                             owner_writer.write_slice(writer.to_slice(), 0, writer.position);
+                            owner_ranges.push((network_behaviour_i as u8, start..owner_writer.position));
                         }
                         if observers_dirty {
+                            let start = observers_writer.position;
                             observers_writer.write_slice(writer.to_slice(), 0, writer.position);
+                            observers_ranges.push((network_behaviour_i as u8, start..observers_writer.position));
                         }
                     });
 
@@ -370,6 +403,139 @@ impl NetworkIdentity {
                 }
             }
         }
+        (owner_ranges, observers_ranges)
+    }
+
+    // 组件数据前写入数据长度的低 8 位用于校验
+    fn write_component<R>(writer: &mut NetworkWriter, write: impl FnOnce(&mut NetworkWriter) -> R) -> R {
+        let header_position = writer.position;
+        writer.write_byte(0);
+        let content_position = writer.position;
+
+        let result = write(writer);
+
+        let end_position = writer.position;
+        writer.position = header_position;
+        let size = (end_position - content_position) as i32;
+        let safety = (size & 0xFF) as u8;
+        writer.write_byte(safety);
+        writer.position = end_position;
+        result
+    }
+
+    // 有变化的增量压缩组件的快照，之后清除其脏标记
+    // 直到所有观察者的基线都追上快照前每帧继续生成，跳过某帧的连接之后仍能收到变化
+    fn delta_snapshots(&mut self) -> Vec<ComponentDeltaSnapshot> {
+        let mut snapshots = Vec::new();
+        for (i, network_behaviour_chain) in self.network_behaviours.iter().enumerate() {
+            if let Some(network_behaviour) = network_behaviour_chain.last().and_then(|x| x.get()) {
+                if !network_behaviour.is_delta_compressed() {
+                    continue;
+                }
+                let nth_bit = 1u64 << i;
+                if network_behaviour.is_dirty() {
+                    self.delta_pending |= nth_bit;
+                    network_behaviour.clear_all_dirty_bits();
+                }
+                if self.delta_pending & nth_bit == 0 {
+                    continue;
+                }
+
+                let snapshot = ComponentDeltaSnapshot {
+                    component_index: i as u8,
+                    snapshot: network_behaviour.sync_var_snapshot(),
+                    to_owner: network_behaviour.get_sync_direction().eq(&SyncDirection::ServerToClient),
+                    to_observers: network_behaviour.get_sync_mode().eq(&SyncMode::Observers),
+                };
+                let caught_up = self.observers.iter().all(|(connection_id, observer)| {
+                    let receives = match observer.ptr_eq(&self.connection) {
+                        true => snapshot.to_owner,
+                        false => snapshot.to_observers,
+                    };
+                    !receives
+                        || self
+                            .delta_baselines
+                            .get(connection_id)
+                            .and_then(|baselines| baselines.get(&snapshot.component_index))
+                            == Some(&snapshot.snapshot)
+                });
+                if caught_up {
+                    self.delta_pending &= !nth_bit;
+                    continue;
+                }
+                snapshots.push(snapshot);
+            }
+        }
+        snapshots
+    }
+
+    /// 带宽不足时的发送优先级，取各组件优先级的最大值
+    pub fn send_priority(&self) -> f32 {
+        self.network_behaviours
+            .iter()
+            .filter_map(|chain| chain.last().and_then(|x| x.get()))
+            .map(|network_behaviour| network_behaviour.get_priority())
+            .reduce(f32::max)
+            .unwrap_or(1.0)
+    }
+
+    /// 发送生成消息后，以当前状态作为该连接的增量压缩基线
+    pub(crate) fn reset_delta_baseline(&mut self, connection_id: u64) {
+        let mut baselines = HashMap::new();
+        for (i, network_behaviour_chain) in self.network_behaviours.iter().enumerate() {
+            if let Some(network_behaviour) = network_behaviour_chain.last().and_then(|x| x.get()) {
+                if network_behaviour.is_delta_compressed() {
+                    baselines.insert(i as u8, network_behaviour.sync_var_snapshot());
+                }
+            }
+        }
+        if baselines.is_empty() {
+            self.delta_baselines.remove(&connection_id);
+        } else {
+            self.delta_baselines.insert(connection_id, baselines);
+        }
+    }
+
+    /// 按连接的基线拼装本帧数据，存在增量压缩组件时使用，没有需要发送的数据时返回 None
+    pub(crate) fn serialize_delta_for_connection(
+        &mut self,
+        serialization: &NetworkIdentitySerialization,
+        connection_id: u64,
+        owned: bool,
+    ) -> Option<NetworkWriter> {
+        let (group_writer, ranges) = match owned {
+            true => (&serialization.owner_writer, &serialization.owner_ranges),
+            false => (&serialization.observers_writer, &serialization.observers_ranges),
+        };
+        let mut payloads = ranges
+            .iter()
+            .map(|(i, range)| (*i, group_writer.to_slice()[range.clone()].to_vec()))
+            .collect::<Vec<_>>();
+
+        let baselines = self.delta_baselines.entry(connection_id).or_default();
+        for delta in serialization.delta_snapshots.iter() {
+            if !(if owned { delta.to_owner } else { delta.to_observers }) {
+                continue;
+            }
+            let mut writer = NetworkWriter::new();
+            let baseline = baselines.get(&delta.component_index);
+            if Self::write_component(&mut writer, |writer| write_sync_var_delta(&delta.snapshot, baseline, writer)) {
+                baselines.insert(delta.component_index, delta.snapshot.clone());
+                payloads.push((delta.component_index, writer.to_vec()));
+            }
+        }
+
+        if payloads.is_empty() {
+            return None;
+        }
+        payloads.sort_by_key(|(i, _)| *i);
+
+        let mut writer = NetworkWriter::new();
+        writer.write_blittable_compress(payloads.iter().fold(0u64, |mask, (i, _)| mask | (1u64 << i)));
+        for (_, payload) in payloads.iter() {
+            writer.write_slice(payload, 0, payload.len());
+        }
+        Some(writer)
     }
 
     pub(crate) fn deserialize_server(&self, _reader: &mut NetworkReader) -> bool {
@@ -385,6 +551,8 @@ impl NetworkIdentity {
             }
         }
         self.observers.clear();
+        self.delta_baselines.clear();
+        self.delta_pending = 0;
     }
 
     pub fn reset_state(&mut self) {
@@ -441,6 +609,8 @@ impl NetworkIdentity {
     ) -> Self {
         let mut identity = Self {
             game_object: weak_game_object.clone(),
+            scene_id: settings.scene_id.to_u64(),
+            _asset_id: settings.asset_id,
             ..Default::default()
         };
         if let Some(game_object) = weak_game_object.get() {
@@ -526,6 +696,133 @@ impl NetworkIdentity {
     pub fn is_scene_object(&self) -> bool {
         self.scene_id != 0
     }
+
+    /// 场景对象没有 assetId，客户端通过 sceneId 找到自己场景中的对象
+    pub fn asset_id(&self) -> u32 {
+        if self.is_scene_object() {
+            return 0;
+        }
+        if self._asset_id == 0 {
+            if let Some(game_object) = self.game_object.get() {
+                return game_object.asset_id;
+            }
+        }
+        self._asset_id
+    }
+
+    /// 各组件的初始同步状态，对象池复用对象时用于恢复同步变量
+    pub(crate) fn component_initial_states(&self) -> Vec<Vec<u8>> {
+        self.network_behaviours
+            .iter()
+            .map(|network_behaviour_chain| {
+                let mut writer = NetworkWriter::new();
+                if let Some(comp) = network_behaviour_chain.last().and_then(|last| last.get()) {
+                    comp.on_serialize(&mut writer, true);
+                }
+                writer.to_vec()
+            })
+            .collect()
+    }
+
+    pub(crate) fn restore_component_states(&self, states: &[Vec<u8>]) {
+        for (network_behaviour_chain, state) in self.network_behaviours.iter().zip(states.iter()) {
+            if let Some(comp) = network_behaviour_chain.last().and_then(|last| last.get()) {
+                let mut reader = NetworkReader::new(state.clone());
+                comp.on_deserialize(&mut reader, true);
+                if reader.has_error() {
+                    log::warn!("{} failed to restore component state: {:?}", self.name(), reader.error());
+                }
+                comp.clear_all_dirty_bits();
+            }
+        }
+    }
+
+    pub(crate) fn set_asset_id(&mut self, asset_id: u32) {
+        self._asset_id = asset_id;
+    }
+
+    pub fn scene_id_conflict(&self) -> bool {
+        self.scene_id_conflict
+    }
+}
+
+// sceneId 注册
+impl NetworkIdentity {
+    /// 登记场景实例中的所有场景对象，sceneId 重复时报错并返回 false，重复的对象不会被生成
+    pub(crate) fn register_scene_objects(
+        scene_handle: u32,
+        scene_path: &str,
+        root_game_objects: &[RevelWeak<GameObject>],
+    ) -> Result<(), Vec<u64>> {
+        let mut conflicts = vec![];
+        let mut scene_ids = HashMap::new();
+        for weak_identity in NetworkServer::find_network_identities(root_game_objects) {
+            let Some(weak_identity) = weak_identity.downcast::<NetworkIdentity>() else {
+                continue;
+            };
+            let Some(identity) = weak_identity.get() else {
+                continue;
+            };
+            if !identity.is_scene_object() {
+                continue;
+            }
+            match scene_ids.get(&identity.scene_id) {
+                None => {
+                    identity.scene_id_conflict = false;
+                    scene_ids.insert(identity.scene_id, weak_identity.clone());
+                }
+                Some(existing) => {
+                    let existing: &RevelWeak<Box<NetworkIdentity>> = existing;
+                    log::error!(
+                        "Scene '{}' contains duplicate sceneId {:X} on '{}' and '{}'. '{}' will not be spawned. Please re-save the scene in Unity to regenerate the sceneIds.",
+                        scene_path,
+                        identity.scene_id,
+                        existing.get().map(|existing| existing.name()).unwrap_or_default(),
+                        identity.name(),
+                        identity.name()
+                    );
+                    identity.scene_id_conflict = true;
+                    if !conflicts.contains(&identity.scene_id) {
+                        conflicts.push(identity.scene_id);
+                    }
+                }
+            }
+        }
+        #[allow(static_mut_refs)]
+        unsafe {
+            SCENE_IDS.insert(scene_handle, scene_ids);
+        }
+        match conflicts.is_empty() {
+            true => Ok(()),
+            false => Err(conflicts),
+        }
+    }
+
+    pub(crate) fn unregister_scene_objects(scene_handle: u32) {
+        #[allow(static_mut_refs)]
+        unsafe {
+            SCENE_IDS.remove(&scene_handle);
+        }
+    }
+
+    pub(crate) fn clear_scene_objects() {
+        #[allow(static_mut_refs)]
+        unsafe {
+            SCENE_IDS.clear();
+        }
+    }
+
+    /// 按场景实例和 sceneId 查找场景对象
+    pub fn get_scene_object(scene_handle: u32, scene_id: u64) -> Option<RevelWeak<Box<NetworkIdentity>>> {
+        #[allow(static_mut_refs)]
+        unsafe {
+            SCENE_IDS
+                .get(&scene_handle)?
+                .get(&scene_id)
+                .filter(|weak_identity| weak_identity.upgradable())
+                .cloned()
+        }
+    }
 }
 
 impl NetworkIdentity {
@@ -575,3 +872,76 @@ impl NetworkIdentity {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mirror::{listen_in_scenes, network_prefab, DeltaBehaviour, NetworkBehaviourSerializer};
+    use crate::unity_engine::Time;
+    use std::time::Duration;
+
+    fn tick() {
+        Time::advance(Duration::from_millis(100));
+        Time::frame_add();
+        NetworkServer::broadcast();
+    }
+
+    #[test]
+    fn test_delta_baselines_per_observer() {
+        let transport = listen_in_scenes(vec![("Assets/Scenes/Test.unity", vec![])]);
+        let connection1 = transport.connect(1);
+        let mut connection2 = transport.connect(2);
+        NetworkServer::set_client_ready(connection1.clone());
+        NetworkServer::set_client_ready(connection2.clone());
+
+        let prefab = network_prefab("Delta", &["Tests.DeltaBehaviour"]).build().unwrap();
+        let game_object = GameObject::instantiate(&prefab);
+        NetworkServer::spawn(game_object.downgrade());
+        let identity = game_object.try_get_component2::<NetworkIdentity>().unwrap();
+        let mut behaviour = game_object.try_get_component2::<DeltaBehaviour>().unwrap();
+        let baseline = |connection_id: u64| identity.delta_baselines.get(&connection_id).and_then(|baselines| baselines.get(&0)).cloned();
+
+        // 生成时以初始状态作为两个连接的基线
+        assert_eq!(baseline(1), Some(behaviour.sync_var_snapshot()));
+        assert_eq!(baseline(2), Some(behaviour.sync_var_snapshot()));
+
+        behaviour.set_health(10);
+        tick();
+        assert_eq!(baseline(1), Some(behaviour.sync_var_snapshot()));
+        assert_eq!(baseline(2), Some(behaviour.sync_var_snapshot()));
+
+        // 连接 2 跳过这一帧，两个连接的基线不同
+        let health_10 = behaviour.sync_var_snapshot();
+        connection2.is_ready = false;
+        behaviour.set_health(20);
+        tick();
+        assert_eq!(baseline(1), Some(behaviour.sync_var_snapshot()));
+        assert_eq!(baseline(2), Some(health_10));
+
+        // 脏标记已清除，之后的帧仍补发给落后的连接
+        connection2.is_ready = true;
+        tick();
+        assert_eq!(baseline(2), Some(behaviour.sync_var_snapshot()));
+        tick();
+        assert_eq!(identity.delta_pending, 0);
+        assert!(identity.last_serialization.delta_snapshots.is_empty());
+
+        // 中途加入的观察者以生成时的状态作为基线
+        let connection3 = transport.connect(3);
+        NetworkServer::set_client_ready(connection3.clone());
+        identity.clone().add_observer(connection3.clone());
+        assert_eq!(baseline(3), Some(behaviour.sync_var_snapshot()));
+        behaviour.set_name("tank".to_string());
+        tick();
+        for connection_id in 1..=3 {
+            assert_eq!(baseline(connection_id), Some(behaviour.sync_var_snapshot()));
+        }
+
+        // 移除观察者时丢弃其基线
+        identity.clone().remove_observer(connection1.clone());
+        assert!(!identity.delta_baselines.contains_key(&1));
+        assert!(identity.delta_baselines.contains_key(&2));
+
+        NetworkServer.shutdown();
+    }
+}
@@ -1,12 +1,14 @@
 use crate::commons::action::SelfMutAction;
 use crate::mirror::NetworkServer;
-use once_cell::sync::Lazy;
+use crate::commons::thread_static;
 use std::ops::{Deref, DerefMut};
 
-static mut NETWORK_LOOP_STATIC: Lazy<NetworkLoopStatic> = Lazy::new(|| NetworkLoopStatic {
-    on_early_update: vec![],
-    on_late_update: vec![],
-});
+thread_static! {
+    static mut NETWORK_LOOP_STATIC: NetworkLoopStatic = NetworkLoopStatic {
+        on_early_update: vec![],
+        on_late_update: vec![],
+    };
+}
 pub struct NetworkLoopStatic {
     on_early_update: Vec<SelfMutAction<(), ()>>,
     on_late_update: Vec<SelfMutAction<(), ()>>,
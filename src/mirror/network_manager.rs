@@ -9,20 +9,21 @@ use crate::mirror::messages::scene_message::{SceneMessage, SceneOperation};
 use crate::mirror::snapshot_interpolation::snapshot_interpolation_settings::SnapshotInterpolationSettings;
 use crate::mirror::transport::{Transport, TransportChannel, TransportError, TransportManager};
 use crate::mirror::NetworkManagerInstance;
-use crate::mirror::{Authenticator, NetworkConnectionToClient, NetworkServer, TNetworkManager};
+use crate::mirror::{Authenticator, NetworkConnectionToClient, NetworkIdentity, NetworkServer, TNetworkManager};
 use crate::mirror::{AuthenticatorFactory, NetworkManagerFactory};
 use crate::transports::kcp2k2_transport::Kcp2kTransport;
 use crate::unity_engine::{GameObject, LoadSceneMode, MonoBehaviour, Time, Transform, WorldManager};
 use crate::{action, network_manager};
 use kcp2k::kcp2k_config::Kcp2KConfig;
-use once_cell::sync::Lazy;
+use crate::commons::thread_static;
 use rand::Rng;
 use std::any::{Any, TypeId};
 use std::collections::HashMap;
 
-static mut NETWORK_MANAGER: Lazy<Vec<RevelWeak<Box<dyn TNetworkManager>>>> =
-    Lazy::new(|| Vec::default());
-static mut NETWORK_MANAGER_MAPPING: Lazy<HashMap<TypeId, usize>> = Lazy::new(|| HashMap::default());
+thread_static! {
+    static mut NETWORK_MANAGER: Vec<RevelWeak<Box<dyn TNetworkManager>>> = Vec::default();
+    static mut NETWORK_MANAGER_MAPPING: HashMap<TypeId, usize> = HashMap::default();
+}
 
 impl NetworkManager {
     pub fn is_instance() -> bool {
@@ -76,6 +77,7 @@ pub struct NetworkManager {
     pub offline_scene: String,
     pub online_scene: String,
     network_scene_name: String,
+    additive_scenes: Vec<String>,
 
     pub offline_scene_load_delay: f32,
     pub player_prefab: String,
@@ -144,6 +146,10 @@ impl MonoBehaviour for NetworkManager {
     fn late_update(&mut self) {
         self.update_scene()
     }
+
+    fn on_destroy(&mut self) {
+        self.stop_server()
+    }
 }
 
 impl NetworkManagerInitialize for NetworkManager {
@@ -219,7 +225,8 @@ impl NetworkManager {
         if Self::is_instance() {
             return;
         }
-        let metadata = Metadata::get_network_manager(&prefab_path).unwrap();
+        let current_metadata = Metadata::current();
+        let metadata = current_metadata.get_network_manager(&prefab_path).unwrap();
         let full_name = metadata.get_final_full_name();
 
         let mut arc_game_object = RevelArc::new(GameObject::default());
@@ -354,6 +361,23 @@ impl NetworkManager {
         }
     }
 
+    pub fn stop_server(&mut self) {
+        if !NetworkServer.active {
+            return;
+        }
+
+        if let Some(ref mut authenticator) = self.authenticator {
+            authenticator.on_stop_server();
+        }
+
+        self.on_stop_server.call(());
+
+        NetworkServer.shutdown();
+
+        self.network_scene_name = String::new();
+        self.start_position_index = 0;
+    }
+
     // 场景管理
     #[action]
     pub fn server_change_scene(&mut self, new_scene_name: String) {
@@ -380,6 +404,7 @@ impl NetworkManager {
         NetworkServer.is_loading_scene = true;
 
         WorldManager::load_scene(&new_scene_name, LoadSceneMode::Single);
+        self.additive_scenes.clear();
 
         if NetworkServer.active {
             let message =
@@ -390,6 +415,149 @@ impl NetworkManager {
         self.start_position_index = 0;
     }
 
+    pub fn additive_scenes(&self) -> &Vec<String> {
+        &self.additive_scenes
+    }
+
+    pub fn load_additive_scene(&mut self, scene_path: &str) -> bool {
+        if !NetworkServer.active {
+            log::error!("LoadAdditiveScene can only be called on an active server.");
+            return false;
+        }
+        if WorldManager::get_scene_by_path(scene_path).upgradable() {
+            log::warn!("Scene {} is already loaded.", scene_path);
+            return false;
+        }
+
+        // 客户端加载完场景后会重新 Ready，此时才下发场景内的对象
+        let message =
+            SceneMessage::new(scene_path.to_string(), SceneOperation::LoadAdditive, false);
+        for connection in NetworkServer.connections.values_mut() {
+            if !connection.is_authenticated {
+                continue;
+            }
+            if connection.is_ready {
+                NetworkServer::set_client_not_ready(connection.clone());
+            }
+            connection.send_message(message.clone(), TransportChannel::Reliable);
+        }
+
+        WorldManager::load_scene(scene_path, LoadSceneMode::Additive);
+        self.additive_scenes.push(scene_path.to_string());
+        true
+    }
+
+    pub fn unload_additive_scene(&mut self, scene_path: &str) -> bool {
+        if !NetworkServer.active {
+            log::error!("UnloadAdditiveScene can only be called on an active server.");
+            return false;
+        }
+        let weak_world = WorldManager::get_scene_by_path(scene_path);
+        let Some(world) = weak_world.get() else {
+            log::warn!("Scene {} is not loaded.", scene_path);
+            return false;
+        };
+
+        for identity in NetworkServer::find_network_identities(&world.root_game_objects()) {
+            if let Some(identity) = identity.downcast::<NetworkIdentity>() {
+                if let Some(identity) = identity.get() {
+                    if identity.net_id() != 0 {
+                        NetworkServer::un_spawn_internal(identity.game_object.clone(), false);
+                    }
+                }
+            }
+        }
+
+        if !WorldManager::unload_scene(scene_path) {
+            return false;
+        }
+        self.additive_scenes.retain(|additive_scene| additive_scene != scene_path);
+
+        let message =
+            SceneMessage::new(scene_path.to_string(), SceneOperation::UnloadAdditive, false);
+        for connection in NetworkServer.connections.values_mut() {
+            if connection.is_authenticated {
+                connection.send_message(message.clone(), TransportChannel::Reliable);
+            }
+        }
+        true
+    }
+
+    /// 在服务器上加载一个隔离的场景实例，不通知客户端。
+    /// 返回实例句柄，之后通过 assign_connection_to_scene_instance 将连接分配进去
+    pub fn load_scene_instance(&mut self, scene_path: &str) -> Option<u32> {
+        if !NetworkServer.active {
+            log::error!("LoadSceneInstance can only be called on an active server.");
+            return None;
+        }
+        WorldManager::load_scene_instance(scene_path)
+            .get()
+            .map(|world| world.handle())
+    }
+
+    /// 将连接分配到场景实例，客户端会叠加加载该场景，重新 Ready 后只收到该实例中的对象
+    pub fn assign_connection_to_scene_instance(
+        &mut self,
+        mut connection: RevelArc<Box<NetworkConnectionToClient>>,
+        scene_handle: u32,
+    ) -> bool {
+        let Some(world) = WorldManager::get_scene_by_handle(scene_handle).upgrade() else {
+            log::error!("Scene instance {} is not loaded.", scene_handle);
+            return false;
+        };
+        if connection.scene_handle == scene_handle {
+            return true;
+        }
+
+        if connection.is_ready {
+            NetworkServer::set_client_not_ready(connection.clone());
+        }
+        if let Some(previous) = WorldManager::get_scene_by_handle(connection.scene_handle).get() {
+            if previous.is_instance() {
+                let message = SceneMessage::new(
+                    previous.get_scene_path(),
+                    SceneOperation::UnloadAdditive,
+                    false,
+                );
+                connection.send_message(message, TransportChannel::Reliable);
+            }
+        }
+        let message =
+            SceneMessage::new(world.get_scene_path(), SceneOperation::LoadAdditive, false);
+        connection.send_message(message, TransportChannel::Reliable);
+
+        NetworkServer::set_connection_scene(connection, scene_handle)
+    }
+
+    /// 卸载场景实例，其中的连接回到未分配状态
+    pub fn unload_scene_instance(&mut self, scene_handle: u32) -> bool {
+        let Some(world) = WorldManager::get_scene_by_handle(scene_handle).upgrade() else {
+            log::warn!("Scene instance {} is not loaded.", scene_handle);
+            return false;
+        };
+
+        for identity in NetworkServer::find_network_identities(&world.root_game_objects()) {
+            if let Some(identity) = identity.downcast::<NetworkIdentity>() {
+                if let Some(identity) = identity.get() {
+                    if identity.net_id() != 0 {
+                        NetworkServer::un_spawn_internal(identity.game_object.clone(), false);
+                    }
+                }
+            }
+        }
+
+        let message =
+            SceneMessage::new(world.get_scene_path(), SceneOperation::UnloadAdditive, false);
+        for connection in NetworkServer.connections.values_mut() {
+            if connection.scene_handle == scene_handle {
+                connection.scene_handle = 0;
+                connection.send_message(message.clone(), TransportChannel::Reliable);
+            }
+        }
+
+        WorldManager::unload_scene_by_handle(scene_handle)
+    }
+
     pub fn on_scene_loaded(&mut self, _: String, mode: LoadSceneMode) {
         if let LoadSceneMode::Additive = mode {
             if NetworkServer.active {
@@ -470,6 +638,12 @@ impl NetworkManager {
             conn.send_message(message, TransportChannel::Reliable);
         }
 
+        for additive_scene in self.additive_scenes.iter() {
+            let message =
+                SceneMessage::new(additive_scene.clone(), SceneOperation::LoadAdditive, false);
+            conn.send_message(message, TransportChannel::Reliable);
+        }
+
         self.on_server_connect.call((conn.clone(),));
     }
 
@@ -492,7 +666,7 @@ impl NetworkManager {
             log::error!("The PlayerPrefab is empty on the NetworkManager. Please setup a PlayerPrefab object.");
             return;
         }
-        if self.auto_create_player && Metadata::get_prefab(&self.player_prefab).is_none() {
+        if self.auto_create_player && Metadata::current().get_prefab(&self.player_prefab).is_none() {
             log::error!("The PlayerPrefab does not have a NetworkIdentity. Please add a NetworkIdentity to the player prefab.");
             return;
         }
@@ -521,10 +695,16 @@ impl NetworkManager {
 
     #[action]
     pub fn on_server_add_player(&mut self, connection: RevelArc<Box<NetworkConnectionToClient>>) {
-        if let Some(player_prefab) = Metadata::get_prefab(&self.player_prefab) {
-            let mut player = GameObject::instantiate(player_prefab);
+        if let Some(player_prefab) = Metadata::current().get_prefab(&self.player_prefab) {
+            // 已分配场景实例的连接，玩家生成在对应实例中
+            let mut player = match connection.scene_handle {
+                0 => GameObject::instantiate(player_prefab),
+                scene_handle => GameObject::instantiate_in_scene(player_prefab, scene_handle),
+            };
             if let Some(start_position) = self.get_start_position() {
-                player.transform = RevelArc::new(start_position);
+                player
+                    .transform
+                    .set_position_and_rotation(start_position.position(), start_position.rotation());
             }
             player.name = format!("{} [connId={}]", player.name, connection.connection_id);
             NetworkServer::add_player_for_connection(connection, player);
@@ -557,3 +737,129 @@ impl NetworkManager {
     #[action]
     pub fn on_stop_server(&mut self) {}
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata_settings::metadata_prefab::MetadataPrefab;
+    use crate::mirror::messages::object_spawn_started_message::ObjectSpawnStartedMessage;
+    use crate::mirror::{listen_in_scenes, network_prefab};
+
+    #[test]
+    fn test_scene_instances() {
+        let transport = listen_in_scenes(vec![("Assets/Scenes/Lobby.unity", vec![]), ("Assets/Scenes/Arena.unity", vec![])]);
+        let mut network_manager = NetworkManager::default();
+
+        let first = network_manager.load_scene_instance("Assets/Scenes/Arena.unity").unwrap();
+        let second = network_manager.load_scene_instance("Assets/Scenes/Arena.unity").unwrap();
+        assert_ne!(first, second);
+        assert!(WorldManager::is_scene_instance(first) && WorldManager::is_scene_instance(second));
+
+        let connections = [transport.connect(1), transport.connect(2), transport.connect(3)];
+        assert!(network_manager.assign_connection_to_scene_instance(connections[0].clone(), first));
+        assert!(network_manager.assign_connection_to_scene_instance(connections[1].clone(), second));
+        assert!(!network_manager.assign_connection_to_scene_instance(connections[2].clone(), 0xFFFF));
+        for connection in connections.iter() {
+            NetworkServer::set_client_ready(connection.clone());
+        }
+
+        let prefab = network_prefab("Pickup", &[]).build().unwrap();
+        let spawn = |scene_handle: u32| {
+            let game_object = GameObject::instantiate_in_scene(&prefab, scene_handle);
+            NetworkServer::spawn(game_object.downgrade());
+            game_object.try_get_component2::<NetworkIdentity>().unwrap()
+        };
+        let in_first = spawn(first);
+        let in_second = spawn(second);
+        let lobby = WorldManager::active_world().get().unwrap().handle();
+        let in_lobby = spawn(lobby);
+
+        // 实例中的对象只对分配到该实例的连接可见，未分配的连接看不到任何实例
+        assert!(NetworkServer::same_scene(&in_first, &connections[0]));
+        assert!(!NetworkServer::same_scene(&in_first, &connections[1]));
+        assert!(!NetworkServer::same_scene(&in_second, &connections[2]));
+        assert!(NetworkServer::same_scene(&in_lobby, &connections[2]));
+        assert!(!NetworkServer::same_scene(&in_lobby, &connections[0]));
+        assert_eq!(in_first.observers.keys().copied().collect::<Vec<_>>(), vec![1]);
+        assert_eq!(in_second.observers.keys().copied().collect::<Vec<_>>(), vec![2]);
+        assert_eq!(in_lobby.observers.keys().copied().collect::<Vec<_>>(), vec![3]);
+
+        // send_to_scene 只发给该实例中的就绪连接
+        let flush = || {
+            connections
+                .iter()
+                .map(|connection| {
+                    connection.clone().update();
+                    transport.sent_bytes(connection.connection_id)
+                })
+                .collect::<Vec<_>>()
+        };
+        let before = flush();
+        NetworkServer::send_to_scene(first, ObjectSpawnStartedMessage, TransportChannel::Reliable);
+        let after = flush();
+        assert!(after[0] > before[0]);
+        assert_eq!(&after[1..], &before[1..]);
+
+        // 卸载实例后其中的对象取消生成，连接回到未分配状态
+        let net_id = in_first.net_id();
+        assert!(network_manager.unload_scene_instance(first));
+        assert!(!NetworkServer.spawned.contains_key(&net_id));
+        assert_eq!(connections[0].scene_handle, 0);
+        assert!(!WorldManager::get_scene_by_handle(first).upgradable());
+        assert!(NetworkServer.spawned.contains_key(&in_second.net_id()));
+
+        NetworkServer.shutdown();
+    }
+
+    #[test]
+    fn test_additive_scene() {
+        let scene_object = |name: &str, scene_id: u64| MetadataPrefab::builder(name).scene_id(scene_id);
+        let transport = listen_in_scenes(vec![
+            ("Assets/Scenes/Lobby.unity", vec![]),
+            (
+                "Assets/Scenes/Arena.unity",
+                vec![
+                    scene_object("Door", 0xA1).build().unwrap(),
+                    scene_object("Chest", 0xB2).child(scene_object("Lid", 0xB3)).build().unwrap(),
+                ],
+            ),
+        ]);
+        let network_manager = RevelArc::new(Box::new(NetworkManager::default()));
+        WorldManager::set_scene_loaded(SelfMutAction::new(network_manager.downgrade(), NetworkManager::on_scene_loaded));
+
+        let mut connection = transport.connect(1);
+        connection.is_authenticated = true;
+        NetworkServer::set_client_ready(connection.clone());
+
+        // 加载后场景中的对象 (包括子对象) 被找到并生成，客户端需重新 Ready
+        assert!(network_manager.clone().load_additive_scene("Assets/Scenes/Arena.unity"));
+        assert!(!network_manager.clone().load_additive_scene("Assets/Scenes/Arena.unity"));
+        assert!(!connection.is_ready);
+        let world = WorldManager::get_scene_by_path("Assets/Scenes/Arena.unity");
+        let identities = NetworkServer::find_network_identities(&world.get().unwrap().root_game_objects())
+            .iter()
+            .filter_map(|identity| identity.downcast::<NetworkIdentity>().cloned())
+            .collect::<Vec<_>>();
+        assert_eq!(identities.len(), 3);
+        let net_ids = identities.iter().map(|identity| identity.get().unwrap().net_id()).collect::<Vec<_>>();
+        for net_id in net_ids.iter() {
+            assert_ne!(*net_id, 0);
+            assert!(NetworkServer.spawned.contains_key(net_id));
+        }
+
+        // 卸载后取消生成，并通知客户端
+        connection.clone().update();
+        let sent = transport.sent_bytes(1);
+        assert!(network_manager.clone().unload_additive_scene("Assets/Scenes/Arena.unity"));
+        for net_id in net_ids.iter() {
+            assert!(!NetworkServer.spawned.contains_key(net_id));
+        }
+        assert!(!world.upgradable());
+        assert!(network_manager.additive_scenes.is_empty());
+        connection.clone().update();
+        assert!(transport.sent_bytes(1) > sent);
+        assert!(!network_manager.clone().unload_additive_scene("Assets/Scenes/Arena.unity"));
+
+        NetworkServer.shutdown();
+    }
+}
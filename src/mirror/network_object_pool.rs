@@ -0,0 +1,320 @@
+use crate::commons::RevelArc;
+use crate::commons::RevelWeak;
+use crate::metadata_settings::Metadata;
+use crate::mirror::{NetworkIdentity, PrefabHandler};
+use crate::unity_engine::{GameObject, WorldManager};
+use nalgebra::{Quaternion, Vector3};
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+/// 对象池统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NetworkObjectPoolStats {
+    pub capacity: usize,
+    /// 池中可用的对象数
+    pub available: usize,
+    /// 已取出尚未放回的对象数
+    pub in_use: usize,
+    /// 构建的对象总数
+    pub created: usize,
+    /// 从池中复用的次数
+    pub reused: usize,
+    /// 放回池中的次数
+    pub returned: usize,
+    /// 池已满时被销毁的次数
+    pub discarded: usize,
+}
+
+struct PooledPrefab {
+    builder: PrefabHandler,
+    capacity: usize,
+    available: Vec<RevelArc<GameObject>>,
+    // 通过 get 取出尚未放回的对象 id，只接收这些对象放回
+    in_use: HashSet<u64>,
+    // 第一个实例构建后的组件同步状态和缩放，复用时恢复
+    initial_states: Vec<Vec<u8>>,
+    local_scale: Vector3<f32>,
+    stats: NetworkObjectPoolStats,
+}
+
+/// 按 assetId 缓存频繁生成的网络对象
+/// 通过 get 取出的对象由 NetworkServer::un_spawn / destroy 放回池中，而不是释放
+/// 池中的对象处于未激活状态且不在任何场景中，不会随场景切换销毁
+#[derive(Default)]
+pub struct NetworkObjectPool {
+    pools: HashMap<u32, PooledPrefab>,
+}
+
+impl NetworkObjectPool {
+    /// 注册对象池，builder 的要求同 NetworkServer.register_prefab
+    pub fn register<F>(&mut self, asset_id: u32, capacity: usize, builder: F)
+    where
+        F: Fn() -> RevelArc<GameObject> + 'static,
+    {
+        if asset_id == 0 {
+            log::error!("NetworkObjectPool.Register: assetId can not be 0.");
+            return;
+        }
+        if let Some(mut pool) = self.pools.remove(&asset_id) {
+            log::warn!("NetworkObjectPool.Register: replacing existing pool for assetId {}.", asset_id);
+            Self::destroy_available(&mut pool);
+        }
+        self.pools.insert(
+            asset_id,
+            PooledPrefab {
+                builder: Rc::new(builder),
+                capacity,
+                available: vec![],
+                in_use: Default::default(),
+                initial_states: vec![],
+                local_scale: Vector3::new(1.0, 1.0, 1.0),
+                stats: NetworkObjectPoolStats {
+                    capacity,
+                    ..Default::default()
+                },
+            },
+        );
+    }
+
+    /// 为导出的预制体注册对象池，返回预制体的 assetId
+    pub fn register_prefab(&mut self, prefab_path: &str, capacity: usize) -> Option<u32> {
+        let metadata = Metadata::current();
+        let Some(metadata_prefab) = metadata.get_prefab(prefab_path) else {
+            log::error!("NetworkObjectPool.RegisterPrefab: prefab {} not found.", prefab_path);
+            return None;
+        };
+        let asset_id = metadata_prefab.asset_id;
+        let prefab_path = prefab_path.to_string();
+        self.register(asset_id, capacity, move || {
+            GameObject::instantiate(Metadata::current().get_prefab(&prefab_path).unwrap())
+        });
+        Some(asset_id)
+    }
+
+    /// 注销并销毁池中的对象，已取出的对象之后按普通对象处理
+    pub fn unregister(&mut self, asset_id: u32) -> bool {
+        match self.pools.remove(&asset_id) {
+            None => false,
+            Some(mut pool) => {
+                Self::destroy_available(&mut pool);
+                true
+            }
+        }
+    }
+
+    pub fn is_registered(&self, asset_id: u32) -> bool {
+        asset_id != 0 && self.pools.contains_key(&asset_id)
+    }
+
+    /// 预先构建对象，池中可用对象最多 count 个 (不超过容量)
+    pub fn prewarm(&mut self, asset_id: u32, count: usize) {
+        let Some(pool) = self.pools.get_mut(&asset_id) else {
+            log::error!("NetworkObjectPool.Prewarm: no pool registered for assetId {}.", asset_id);
+            return;
+        };
+        while pool.available.len() < count.min(pool.capacity) {
+            let mut game_object = Self::create(asset_id, pool);
+            game_object.set_active(false);
+            WorldManager::take_root_game_object(&game_object.id);
+            pool.available.push(game_object);
+        }
+        pool.stats.available = pool.available.len();
+    }
+
+    /// 取出对象放入 scene_handle 对应的场景 (通常是生成者所在的场景)，重置 Transform 和同步变量，
+    /// 之后调用 NetworkServer::spawn 生成
+    pub fn get(
+        &mut self,
+        asset_id: u32,
+        position: Vector3<f32>,
+        rotation: Quaternion<f32>,
+        scene_handle: u32,
+    ) -> Option<RevelArc<GameObject>> {
+        let Some(pool) = self.pools.get_mut(&asset_id) else {
+            log::error!("NetworkObjectPool.Get: no pool registered for assetId {}.", asset_id);
+            return None;
+        };
+
+        let mut game_object = match pool.available.pop() {
+            Some(mut game_object) => {
+                pool.stats.reused += 1;
+                WorldManager::add_root_game_object(game_object.clone(), scene_handle);
+                if let Some(identity) = Self::network_identity(&game_object) {
+                    identity.restore_component_states(&pool.initial_states);
+                }
+                game_object.transform.set_local_scale(pool.local_scale);
+                game_object.set_active(true);
+                game_object
+            }
+            None => {
+                let game_object = Self::create(asset_id, pool);
+                if let Some(game_object) = WorldManager::take_root_game_object(&game_object.id) {
+                    WorldManager::add_root_game_object(game_object, scene_handle);
+                }
+                game_object
+            }
+        };
+        game_object.transform.set_position_and_rotation(position, rotation);
+
+        pool.in_use.insert(game_object.id);
+        pool.stats.in_use = pool.in_use.len();
+        pool.stats.available = pool.available.len();
+        Some(game_object)
+    }
+
+    /// 放回对象，池已满、对象不是根对象或不是通过 get 取出时返回 false，由调用方销毁
+    pub(crate) fn put(&mut self, game_object: RevelWeak<GameObject>) -> bool {
+        let Some(mut arc_game_object) = game_object.upgrade() else {
+            return false;
+        };
+        let Some(pool) = self.pools.get_mut(&arc_game_object.asset_id) else {
+            return false;
+        };
+        if pool
+            .available
+            .iter()
+            .any(|available| available.downgrade().ptr_eq(&game_object))
+        {
+            return true;
+        }
+        // 同一 assetId 但不是从池中取出的对象 (例如 instantiate 的)
+        if !pool.in_use.remove(&arc_game_object.id) {
+            return false;
+        }
+        pool.stats.in_use = pool.in_use.len();
+        if pool.available.len() >= pool.capacity || arc_game_object.parent.upgradable() {
+            pool.stats.discarded += 1;
+            return false;
+        }
+
+        // 取消放回前的 Invoke 和协程，复用的对象不会执行上一次生成时的回调
+        arc_game_object.clear_scheduler();
+        arc_game_object.set_active(false);
+        WorldManager::take_root_game_object(&arc_game_object.id);
+        pool.available.push(arc_game_object);
+        pool.stats.returned += 1;
+        pool.stats.available = pool.available.len();
+        true
+    }
+
+    pub fn stats(&self, asset_id: u32) -> Option<NetworkObjectPoolStats> {
+        self.pools.get(&asset_id).map(|pool| pool.stats)
+    }
+
+    /// 所有对象池的统计，按 assetId
+    pub fn all_stats(&self) -> HashMap<u32, NetworkObjectPoolStats> {
+        self.pools
+            .iter()
+            .map(|(asset_id, pool)| (*asset_id, pool.stats))
+            .collect()
+    }
+
+    /// 销毁所有池中的对象，保留注册
+    pub fn clear(&mut self) {
+        for pool in self.pools.values_mut() {
+            Self::destroy_available(pool);
+        }
+    }
+
+    fn create(asset_id: u32, pool: &mut PooledPrefab) -> RevelArc<GameObject> {
+        let builder = pool.builder.clone();
+        let mut game_object = builder();
+        game_object.asset_id = asset_id;
+        let identity = Self::network_identity(&game_object);
+        if let Some(mut identity) = identity.clone() {
+            identity.set_asset_id(asset_id);
+        }
+        if pool.stats.created == 0 {
+            pool.local_scale = game_object.transform.local_scale();
+            if let Some(identity) = identity {
+                pool.initial_states = identity.component_initial_states();
+            }
+        }
+        pool.stats.created += 1;
+        game_object
+    }
+
+    fn destroy_available(pool: &mut PooledPrefab) {
+        for mut game_object in pool.available.drain(..) {
+            game_object.on_destroy();
+        }
+        pool.stats.available = 0;
+    }
+
+    fn network_identity(game_object: &RevelArc<GameObject>) -> Option<RevelArc<Box<NetworkIdentity>>> {
+        game_object
+            .try_get_component::<NetworkIdentity>()?
+            .downcast::<NetworkIdentity>()?
+            .upgrade()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata_settings::metadata_prefab::MetadataPrefab;
+    use crate::mirror::network_prefab;
+    use crate::unity_engine::LoadSceneMode;
+
+    #[test]
+    fn test_object_pool() {
+        let prefab = network_prefab("Projectile", &[])
+            .local_scale([2.0, 2.0, 2.0])
+            .build()
+            .unwrap();
+        Metadata::load_scenes(vec![("Assets/Scenes/A.unity", vec![]), ("Assets/Scenes/B.unity", vec![])]);
+        WorldManager::load_scene("Assets/Scenes/A.unity", LoadSceneMode::Single);
+        let scene_handle = WorldManager::load_scene_instance("Assets/Scenes/B.unity").get().unwrap().handle();
+
+        let mut pool = NetworkObjectPool::default();
+        pool.register(7, 2, move || GameObject::instantiate(&prefab));
+        pool.prewarm(7, 3);
+        assert_eq!(pool.stats(7).unwrap().available, 2);
+
+        let position = Vector3::new(1.0, 2.0, 3.0);
+        let mut first = pool.get(7, position, Quaternion::identity(), scene_handle).unwrap();
+        assert!(first.is_active());
+        assert_eq!(first.scene_handle(), scene_handle);
+        assert_eq!(first.transform.position(), position);
+        assert_eq!(NetworkObjectPool::network_identity(&first).unwrap().asset_id(), 7);
+
+        // 放回后重置缩放并取消 Invoke
+        first.transform.set_local_scale(Vector3::new(5.0, 5.0, 5.0));
+        let handle = first.invoke(0.0, || {});
+        assert!(pool.put(first.downgrade()));
+        assert!(!first.is_active());
+        assert!(!first.is_invoking(handle));
+        let first = pool.get(7, position, Quaternion::identity(), scene_handle).unwrap();
+        assert_eq!(first.transform.local_scale(), Vector3::new(2.0, 2.0, 2.0));
+        assert_eq!(first.scene_handle(), scene_handle);
+
+        // 池中没有可用对象时构建新对象，池已满时不再放回
+        let second = pool.get(7, position, Quaternion::identity(), scene_handle).unwrap();
+        let third = pool.get(7, position, Quaternion::identity(), scene_handle).unwrap();
+        for game_object in [&first, &second, &third] {
+            pool.put(game_object.downgrade());
+        }
+        assert_eq!(
+            pool.stats(7).unwrap(),
+            NetworkObjectPoolStats {
+                capacity: 2,
+                available: 2,
+                in_use: 0,
+                created: 3,
+                reused: 3,
+                returned: 3,
+                discarded: 1,
+            }
+        );
+
+        // 不是从池中取出的对象不放回
+        let mut foreign = GameObject::instantiate(&MetadataPrefab::builder("Projectile").build().unwrap());
+        foreign.asset_id = 7;
+        assert!(!pool.put(foreign.downgrade()));
+        assert_eq!(pool.stats(7).unwrap().discarded, 1);
+        assert_eq!(pool.stats(7).unwrap().available, 2);
+
+        assert!(pool.unregister(7));
+        assert!(pool.get(7, position, Quaternion::identity(), scene_handle).is_none());
+    }
+}
@@ -2,9 +2,13 @@
 use crate::commons::to_hex_string::ToHexString;
 use crate::mirror::compress::Compress;
 use crate::mirror::NetworkWriter;
+use crate::mirror::network_writer::OA_DATE_UNIX_EPOCH_DAYS;
 use nalgebra::Vector4;
+use std::collections::{BTreeMap, HashMap};
 use std::error::Error;
 use std::fmt::{Display, Formatter};
+use std::hash::{BuildHasher, Hash};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 pub trait ReadCompress {
     fn decompress(reader: &mut NetworkReader) -> Self
@@ -153,12 +157,106 @@ impl ReadCompress for nalgebra::Quaternion<f32> {
     }
 }
 
-const ALLOCATION_LIMIT: i32 = 1024 * 1024 * 16;
+/// 单次读取的集合长度上限，防止恶意长度导致超大分配
+pub const ALLOCATION_LIMIT: usize = 1024 * 1024 * 16;
+
+/// 读取失败的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReadError {
+    /// 剩余数据不足
+    EndOfStream {
+        type_name: &'static str,
+        needed: usize,
+        remaining: usize,
+    },
+    /// 长度超过上限
+    TooLarge {
+        type_name: &'static str,
+        size: usize,
+        limit: usize,
+    },
+    InvalidUtf8,
+    /// 数据不是该类型的合法值
+    InvalidValue { type_name: &'static str },
+}
+
+impl Display for ReadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReadError::EndOfStream {
+                type_name,
+                needed,
+                remaining,
+            } => write!(
+                f,
+                "not enough data to read {} ({} bytes needed, {} remaining)",
+                type_name, needed, remaining
+            ),
+            ReadError::TooLarge {
+                type_name,
+                size,
+                limit,
+            } => write!(f, "{} length {} exceeds limit {}", type_name, size, limit),
+            ReadError::InvalidUtf8 => write!(f, "invalid UTF8 string"),
+            ReadError::InvalidValue { type_name } => write!(f, "invalid value for {}", type_name),
+        }
+    }
+}
+
+impl Error for ReadError {}
+
+/// 可以按内存布局直接读取的类型
+///
+/// # Safety
+/// 不能包含指针、引用或填充字节，且全零是合法值；
+/// 不是所有位模式都合法的类型需要覆盖 is_valid
+pub unsafe trait Pod: Copy + 'static {
+    fn is_valid(#[allow(unused)] bytes: &[u8]) -> bool {
+        true
+    }
+
+    fn zeroed() -> Self {
+        unsafe { std::mem::zeroed() }
+    }
+}
+
+macro_rules! impl_pod {
+    ($($typ:ty),*) => {
+        $(unsafe impl Pod for $typ {})*
+    };
+}
+
+impl_pod!(
+    u8,
+    u16,
+    u32,
+    u64,
+    i8,
+    i16,
+    i32,
+    i64,
+    f32,
+    f64,
+    nalgebra::Vector2<f32>,
+    nalgebra::Vector3<f32>,
+    nalgebra::Vector4<f32>,
+    nalgebra::Quaternion<f32>,
+    nalgebra::Matrix4<f32>
+);
 
+unsafe impl Pod for bool {
+    fn is_valid(bytes: &[u8]) -> bool {
+        bytes[0] <= 1
+    }
+}
+
+/// 读取失败后 reader 保持失败状态，后续读取都返回错误 (或默认值)，直到 reset / set_vec
+/// 不返回 Result 的读取函数在失败时记录错误并返回默认值，调用方可通过 error() 检查
 #[derive(Debug, Default)]
 pub struct NetworkReader {
     buffer: Vec<u8>,
     pub position: usize,
+    error: Option<ReadError>,
 }
 
 impl Display for NetworkReader {
@@ -178,17 +276,18 @@ impl NetworkReader {
         Self {
             buffer: segment,
             position: 0,
+            error: None,
         }
     }
 
     pub fn set_vec(&mut self, segment: Vec<u8>) {
         self.buffer = segment;
-        self.position = 0;
+        self.reset();
     }
 
     pub fn set_slice(&mut self, segment: &[u8]) {
         self.buffer = segment.to_vec();
-        self.position = 0;
+        self.reset();
     }
 
     pub fn to_slice(&self) -> &[u8] {
@@ -199,25 +298,79 @@ impl NetworkReader {
         self.buffer[self.position..].to_vec()
     }
 
-    pub fn read_blittable<T>(&mut self) -> T {
-        let size = size_of::<T>();
-        if self.remaining() < size {
-            log::error!(
-                "ReadBlittable<{}> not enough data in buffer to read {} bytes: {:?}",
-                std::any::type_name::<T>(),
-                size,
-                self
-            );
-            return unsafe { std::mem::zeroed() };
+    /// 第一次读取失败的原因
+    pub fn error(&self) -> Option<&ReadError> {
+        self.error.as_ref()
+    }
+
+    pub fn has_error(&self) -> bool {
+        self.error.is_some()
+    }
+
+    fn check(&self) -> Result<(), ReadError> {
+        match &self.error {
+            None => Ok(()),
+            Some(err) => Err(err.clone()),
         }
+    }
 
-        let value = unsafe {
-            let ptr = self.buffer.as_ptr().add(self.position) as *const T;
-            ptr.read_unaligned()
-        };
-        self.position += size;
+    /// 记录读取失败，自定义反序列化遇到非法数据时调用
+    pub fn fail(&mut self, err: ReadError) -> ReadError {
+        if self.error.is_none() {
+            self.error = Some(err.clone());
+        }
+        err
+    }
 
-        value
+    fn log_error(&self, err: &ReadError) {
+        log::error!("NetworkReader: {} at position {}, {} bytes remaining", err, self.position, self.remaining());
+    }
+
+    fn try_take(&mut self, count: usize, type_name: &'static str) -> Result<&[u8], ReadError> {
+        self.check()?;
+        if self.remaining() < count {
+            let err = ReadError::EndOfStream {
+                type_name,
+                needed: count,
+                remaining: self.remaining(),
+            };
+            return Err(self.fail(err));
+        }
+        let start = self.position;
+        self.position += count;
+        Ok(&self.buffer[start..start + count])
+    }
+
+    /// 用不返回 Result 的读取函数读取，期间的失败转为 Err
+    pub fn try_with<T>(&mut self, read: impl FnOnce(&mut Self) -> T) -> Result<T, ReadError> {
+        self.check()?;
+        let value = read(self);
+        self.check()?;
+        Ok(value)
+    }
+
+    pub fn try_read<T: DataTypeDeserializer>(&mut self) -> Result<T, ReadError> {
+        self.try_with(T::deserialize)
+    }
+
+    pub fn try_read_blittable<T: Pod>(&mut self) -> Result<T, ReadError> {
+        let type_name = std::any::type_name::<T>();
+        let bytes = self.try_take(size_of::<T>(), type_name)?;
+        if !T::is_valid(bytes) {
+            return Err(self.fail(ReadError::InvalidValue { type_name }));
+        }
+        Ok(unsafe { (bytes.as_ptr() as *const T).read_unaligned() })
+    }
+
+    pub fn read_blittable<T: Pod>(&mut self) -> T {
+        self.try_read_blittable().unwrap_or_else(|err| {
+            self.log_error(&err);
+            T::zeroed()
+        })
+    }
+
+    pub fn try_read_blittable_compress<T: ReadCompress>(&mut self) -> Result<T, ReadError> {
+        self.try_with(T::decompress)
     }
 
     pub fn read_blittable_compress<T>(&mut self) -> T
@@ -227,61 +380,107 @@ impl NetworkReader {
         T::decompress(self)
     }
 
-    pub fn read_blittable_nullable<T>(&mut self) -> T
-    where
-        T: DataTypeDeserializer,
-    {
-        if self.read_byte() != 0 {
-            return self.read_blittable();
+    pub fn try_read_blittable_nullable<T: Pod>(&mut self) -> Result<Option<T>, ReadError> {
+        if self.try_read_blittable::<u8>()? != 0 {
+            return self.try_read_blittable().map(Some);
         }
-        unsafe { std::mem::zeroed() }
+        Ok(None)
+    }
+
+    pub fn read_blittable_nullable<T: Pod>(&mut self) -> Option<T> {
+        self.try_read_blittable_nullable().unwrap_or_else(|err| {
+            self.log_error(&err);
+            None
+        })
     }
 
     pub fn read_byte(&mut self) -> u8 {
         self.read_blittable()
     }
+
+    pub fn try_read_slice(&mut self, count: usize) -> Result<&[u8], ReadError> {
+        self.try_take(count, "[u8]")
+    }
+
     pub fn read_slice(&mut self, count: usize) -> &[u8] {
-        if self.remaining() < count {
-            log::error!(
-                "ReadBytesSegment can't read {} bytes because it would read past the end of the stream. {}",
-                count,
-                self
-            );
+        if let Err(err) = self.try_take(count, "[u8]") {
+            self.log_error(&err);
             return &[];
         }
-        let x = &self.buffer[self.position..self.position + count];
-        self.position += count;
-        x
+        &self.buffer[self.position - count..self.position]
     }
-    pub fn read_string(&mut self) -> String {
-        let size = self.read_blittable::<u16>();
+
+    pub fn try_read_string(&mut self) -> Result<String, ReadError> {
+        let size = self.try_read_blittable::<u16>()?;
         if size == 0 {
-            return "".to_string();
+            return Ok("".to_string());
         }
         let real_size = size - 1;
 
         if real_size > NetworkWriter::max_string_length() {
-            log::error!(
-                "NetworkReader.ReadString - Value too long: {} bytes. Limit is: {} bytes",
-                real_size,
-                NetworkWriter::max_string_length()
-            );
-            return "".to_string();
+            return Err(self.fail(ReadError::TooLarge {
+                type_name: "String",
+                size: real_size as usize,
+                limit: NetworkWriter::max_string_length() as usize,
+            }));
+        }
+
+        let bytes_segment = self.try_take(real_size as usize, "String")?;
+        match String::from_utf8(bytes_segment.to_vec()) {
+            Ok(value) => Ok(value),
+            Err(_) => Err(self.fail(ReadError::InvalidUtf8)),
         }
+    }
 
-        let bytes_segment = self.read_slice(real_size as usize);
-        String::from_utf8(bytes_segment.to_vec()).unwrap_or_else(|err| {
-            log::error!("NetworkReader.ReadString - Invalid UTF8: {}", err);
+    pub fn read_string(&mut self) -> String {
+        self.try_read_string().unwrap_or_else(|err| {
+            self.log_error(&err);
             "".to_string()
         })
     }
+
+    pub fn try_read_slice_and_size(&mut self) -> Result<&[u8], ReadError> {
+        let count = self.try_read_blittable_compress::<u32>()? as usize;
+        if count == 0 {
+            return Ok(&[]);
+        }
+        if count - 1 > ALLOCATION_LIMIT {
+            return Err(self.fail(ReadError::TooLarge {
+                type_name: "[u8]",
+                size: count - 1,
+                limit: ALLOCATION_LIMIT,
+            }));
+        }
+        self.try_take(count - 1, "[u8]")
+    }
+
     pub fn read_slice_and_size(&mut self) -> &[u8] {
-        let count: u32 = self.read_blittable_compress();
+        match self.try_read_slice_and_size().map(|slice| slice.len()) {
+            Ok(len) => &self.buffer[self.position - len..self.position],
+            Err(err) => {
+                self.log_error(&err);
+                &[]
+            }
+        }
+    }
+
+    /// 读取集合长度 (0 表示 null)，超过 ALLOCATION_LIMIT 时失败
+    pub fn try_read_collection_size(&mut self, type_name: &'static str) -> Result<Option<usize>, ReadError> {
+        let count = self.try_read_blittable_compress::<u64>()?;
         if count == 0 {
-            return &[];
+            return Ok(None);
         }
-        self.read_slice(count as usize - 1)
+        let size = count - 1;
+        if size > ALLOCATION_LIMIT as u64 {
+            return Err(self.fail(ReadError::TooLarge {
+                type_name,
+                size: size as usize,
+                limit: ALLOCATION_LIMIT,
+            }));
+        }
+        Ok(Some(size as usize))
     }
+
     pub fn remaining(&self) -> usize {
         self.buffer.len() - self.position
     }
@@ -290,6 +489,7 @@ impl NetworkReader {
     }
     pub fn reset(&mut self) {
         self.position = 0;
+        self.error = None;
     }
 }
 
@@ -325,32 +525,330 @@ data_type_deserialize!(
         f32,
         f64,
         bool,
+        nalgebra::Vector2<f32>,
         nalgebra::Vector3<f32>,
         nalgebra::Vector4<f32>,
-        nalgebra::Quaternion<f32>
+        nalgebra::Quaternion<f32>,
+        nalgebra::Matrix4<f32>
     ),
     { |reader| reader.read_blittable() }
 );
 
 impl<T: DataTypeDeserializer> DataTypeDeserializer for Vec<T> {
     fn deserialize(#[allow(unused)] reader: &mut NetworkReader) -> Self {
-        let size = reader.read_blittable_compress::<u64>() as usize - 1;
-        let mut result = Vec::with_capacity(size);
+        let size = match reader.try_read_collection_size(std::any::type_name::<Self>()) {
+            Ok(Some(size)) => size,
+            Ok(None) => return Vec::new(),
+            Err(err) => {
+                reader.log_error(&err);
+                return Vec::new();
+            }
+        };
+        // 长度来自对端，不按其预分配
+        let mut result = Vec::with_capacity(size.min(reader.remaining()));
         for _ in 0..size {
             result.push(T::deserialize(reader));
+            if reader.has_error() {
+                return Vec::new();
+            }
+        }
+        result
+    }
+}
+
+impl<T: DataTypeDeserializer> DataTypeDeserializer for Option<T> {
+    fn deserialize(reader: &mut NetworkReader) -> Self {
+        if bool::deserialize(reader) {
+            return Some(T::deserialize(reader));
+        }
+        None
+    }
+}
+
+impl<T: DataTypeDeserializer, const N: usize> DataTypeDeserializer for [T; N] {
+    fn deserialize(reader: &mut NetworkReader) -> Self {
+        let size = match reader.try_read_collection_size(std::any::type_name::<Self>()) {
+            Ok(size) => size.unwrap_or(0),
+            Err(err) => {
+                reader.log_error(&err);
+                N
+            }
+        };
+        if size != N {
+            let err = reader.fail(ReadError::InvalidValue {
+                type_name: std::any::type_name::<Self>(),
+            });
+            reader.log_error(&err);
+        }
+        // 失败后的读取都返回默认值
+        std::array::from_fn(|_| T::deserialize(reader))
+    }
+}
+
+impl<K, V, S> DataTypeDeserializer for HashMap<K, V, S>
+where
+    K: DataTypeDeserializer + Eq + Hash,
+    V: DataTypeDeserializer,
+    S: BuildHasher + Default,
+{
+    fn deserialize(reader: &mut NetworkReader) -> Self {
+        let mut result = HashMap::default();
+        let size = match reader.try_read_collection_size(std::any::type_name::<Self>()) {
+            Ok(size) => size.unwrap_or(0),
+            Err(err) => {
+                reader.log_error(&err);
+                return result;
+            }
+        };
+        for _ in 0..size {
+            let key = K::deserialize(reader);
+            let value = V::deserialize(reader);
+            if reader.has_error() {
+                return HashMap::default();
+            }
+            result.insert(key, value);
         }
         result
     }
 }
 
-impl<T: DataTypeDeserializer> DataTypeDeserializer for &[T] {
+impl<K: DataTypeDeserializer + Ord, V: DataTypeDeserializer> DataTypeDeserializer for BTreeMap<K, V> {
     fn deserialize(reader: &mut NetworkReader) -> Self {
-        let size = reader.read_blittable_compress::<u64>() as usize - 1;
-        let mut result = Vec::with_capacity(size);
-        for i in 0..size {
-            let value = T::deserialize(reader);
-            result.push(value);
+        let mut result = BTreeMap::new();
+        let size = match reader.try_read_collection_size(std::any::type_name::<Self>()) {
+            Ok(size) => size.unwrap_or(0),
+            Err(err) => {
+                reader.log_error(&err);
+                return result;
+            }
+        };
+        for _ in 0..size {
+            let key = K::deserialize(reader);
+            let value = V::deserialize(reader);
+            if reader.has_error() {
+                return BTreeMap::new();
+            }
+            result.insert(key, value);
         }
-        unsafe { std::mem::transmute(result.as_slice()) }
+        result
+    }
+}
+
+macro_rules! tuple_deserialize {
+    ($(($($name:ident),+)),+) => {
+        $(
+            impl<$($name: DataTypeDeserializer),+> DataTypeDeserializer for ($($name,)+) {
+                fn deserialize(reader: &mut NetworkReader) -> Self {
+                    ($($name::deserialize(reader),)+)
+                }
+            }
+        )+
+    };
+}
+
+tuple_deserialize!(
+    (A),
+    (A, B),
+    (A, B, C),
+    (A, B, C, D),
+    (A, B, C, D, E),
+    (A, B, C, D, E, F),
+    (A, B, C, D, E, F, G)
+);
+
+impl DataTypeDeserializer for SystemTime {
+    fn deserialize(reader: &mut NetworkReader) -> Self {
+        let oa_date = reader.read_blittable::<f64>();
+        let seconds = (oa_date - OA_DATE_UNIX_EPOCH_DAYS) * 86400.0;
+        let time = Duration::try_from_secs_f64(seconds.abs()).ok().and_then(|duration| {
+            if seconds >= 0.0 {
+                UNIX_EPOCH.checked_add(duration)
+            } else {
+                UNIX_EPOCH.checked_sub(duration)
+            }
+        });
+        match time {
+            Some(time) => time,
+            None => {
+                let err = reader.fail(ReadError::InvalidValue { type_name: "SystemTime" });
+                reader.log_error(&err);
+                UNIX_EPOCH
+            }
+        }
+    }
+}
+
+impl DataTypeDeserializer for http::Uri {
+    fn deserialize(reader: &mut NetworkReader) -> Self {
+        let value = reader.read_string();
+        if value.trim().is_empty() {
+            return http::Uri::default();
+        }
+        value.parse().unwrap_or_else(|_| {
+            let err = reader.fail(ReadError::InvalidValue { type_name: "Uri" });
+            reader.log_error(&err);
+            http::Uri::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_malformed_input() {
+        // 数据不足时返回错误，之后的读取保持失败
+        let mut reader = NetworkReader::new(vec![1, 2, 3]);
+        assert!(matches!(
+            reader.try_read_blittable::<u32>(),
+            Err(ReadError::EndOfStream { needed: 4, remaining: 3, .. })
+        ));
+        assert_eq!(reader.position, 0);
+        assert!(reader.try_read_blittable::<u8>().is_err());
+        assert_eq!(reader.read_byte(), 0);
+        reader.reset();
+        assert_eq!(reader.try_read::<u8>(), Ok(1));
+
+        // bool 只接受 0 / 1
+        let mut reader = NetworkReader::new(vec![2]);
+        assert!(matches!(reader.try_read::<bool>(), Err(ReadError::InvalidValue { .. })));
+
+        // 超过 ALLOCATION_LIMIT 的长度不分配
+        let mut writer = NetworkWriter::new();
+        writer.write_blittable_compress(ALLOCATION_LIMIT as u64 + 2);
+        let mut reader = NetworkReader::new(writer.to_vec());
+        assert!(matches!(reader.try_read::<Vec<u32>>(), Err(ReadError::TooLarge { .. })));
+
+        // 长度合法但元素不足
+        let mut writer = NetworkWriter::new();
+        writer.write_blittable_compress(1001u64);
+        let mut reader = NetworkReader::new(writer.to_vec());
+        assert!(reader.try_read::<Vec<String>>().is_err());
+
+        let mut reader = NetworkReader::new(vec![3, 0, 0xff, 0xfe]);
+        assert_eq!(reader.try_read_string(), Err(ReadError::InvalidUtf8));
+        assert_eq!(reader.read_string(), "");
+    }
+
+    use crate::macro_namespace::*;
+    use crate::macro_network_serialize::*;
+    use crate::mirror::round_trip;
+
+    #[namespace(prefix = "Game")]
+    #[derive(Debug, PartialEq, Default, NetworkSerialize, NetworkDeserialize)]
+    struct Loadout {
+        slot: u8,
+        items: Vec<String>,
+        #[network(skip)]
+        cached: u32,
+    }
+
+    #[namespace(prefix = "Game")]
+    #[derive(Debug, PartialEq, NetworkSerialize, NetworkDeserialize)]
+    struct Damage(f32, i32);
+
+    #[namespace(prefix = "Game")]
+    #[derive(Debug, PartialEq, NetworkSerialize, NetworkDeserialize)]
+    #[repr(u8)]
+    enum Team {
+        Red = 1,
+        Blue,
+    }
+
+    #[namespace(prefix = "Game")]
+    #[derive(Debug, PartialEq, NetworkSerialize, NetworkDeserialize)]
+    #[repr(u8)]
+    enum Action {
+        Idle,
+        Attack { team: Team, damage: Damage },
+    }
+
+    #[derive(Debug, PartialEq, NetworkSerialize, NetworkDeserialize)]
+    struct Wrapper<T> {
+        v: T,
+    }
+
+    #[derive(Debug, PartialEq, NetworkSerialize, NetworkDeserialize)]
+    #[repr(u8)]
+    enum Either<L, R> {
+        Left(L),
+        Right(R),
+    }
+
+    #[test]
+    fn test_derive_serialize() {
+        let loadout = Loadout {
+            slot: 2,
+            items: vec!["sword".to_string()],
+            cached: 9,
+        };
+        let (bytes, result) = round_trip(&loadout);
+        // 按字段声明顺序，跳过的字段不写
+        let mut writer = NetworkWriter::new();
+        writer.write_blittable(2u8);
+        vec!["sword".to_string()].serialize(&mut writer);
+        assert_eq!(bytes, writer.to_vec());
+        assert_eq!(result, Loadout { cached: 0, ..loadout });
+
+        let action = Action::Attack {
+            team: Team::Blue,
+            damage: Damage(1.5, -3),
+        };
+        let (bytes, result) = round_trip(&action);
+        assert_eq!(&bytes[..2], &[1, 2]);
+        assert_eq!(result, action);
+        assert_eq!(round_trip(&Action::Idle).0, vec![0]);
+
+        // 未知标签
+        let mut reader = NetworkReader::new(vec![7]);
+        assert!(matches!(reader.try_read::<Team>(), Err(ReadError::InvalidValue { .. })));
+
+        // 泛型参数按实际类型读写
+        let wrapper = Wrapper { v: Damage(2.0, 1) };
+        assert_eq!(round_trip(&wrapper).1, wrapper);
+        let either = Either::<u8, String>::Right("right".to_string());
+        assert_eq!(round_trip(&either).1, either);
+    }
+    #[test]
+    fn test_builtin_serializers() {
+        use crate::commons::{Decimal, Guid};
+        use crate::unity_engine::Color;
+        use std::collections::{BTreeMap, HashMap};
+        use std::time::{Duration, UNIX_EPOCH};
+
+        // Option 先写是否有值
+        assert_eq!(round_trip(&Some(5u8)).0, vec![1, 5]);
+        assert_eq!(round_trip(&None::<u8>), (vec![0], None));
+
+        let (bytes, result) = round_trip(&[1u8, 2, 3]);
+        assert_eq!(bytes, vec![4, 1, 2, 3]);
+        assert_eq!(result, [1, 2, 3]);
+        // 数组长度不符
+        let mut reader = NetworkReader::new(bytes);
+        assert!(matches!(reader.try_read::<[u8; 2]>(), Err(ReadError::InvalidValue { .. })));
+
+        let map = HashMap::from([(1u32, "a".to_string()), (2, "b".to_string())]);
+        assert_eq!(round_trip(&map).1, map);
+        let map = BTreeMap::from([("x".to_string(), 1.5f32)]);
+        assert_eq!(round_trip(&map).1, map);
+        let tuple = (1u8, "two".to_string(), 3.0f64);
+        assert_eq!(round_trip(&tuple).1, tuple);
+
+        let guid = Guid::new_v4();
+        assert_eq!(round_trip(&guid), (guid.0.to_vec(), guid));
+        let decimal = Decimal::new(-12345, 2).unwrap();
+        assert_eq!(round_trip(&decimal).1, decimal);
+
+        // DateTime 以 OADate 传输，精度到毫秒
+        let time = UNIX_EPOCH + Duration::from_millis(1_700_000_000_000);
+        let result = round_trip(&time).1;
+        let delta = result.duration_since(time).unwrap_or_else(|err| err.duration());
+        assert!(delta < Duration::from_millis(1));
+
+        let uri: http::Uri = "kcp://127.0.0.1:7777".parse().unwrap();
+        assert_eq!(round_trip(&uri).1, uri);
+        let color = Color::new(0.1, 0.2, 0.3, 1.0);
+        assert_eq!(round_trip(&color).1, color);
     }
 }
@@ -0,0 +1,305 @@
+use crate::commons::RevelWeak;
+use crate::mirror::{
+    DataTypeDeserializer, DataTypeSerializer, NetworkIdentity, NetworkReader, NetworkServer, NetworkWriter,
+    TNetworkBehaviour,
+};
+use crate::unity_engine::GameObject;
+
+// 网络对象引用按 Mirror 的格式写为 netId (空引用为 0)，NetworkBehaviour 额外写组件索引
+// 读取时从 NetworkServer.spawned 查找，找不到时为空引用
+
+/// 网络对象引用的标识，对应 Mirror 同步变量的 ___xxxNetId 字段
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct NetworkReferenceId {
+    pub net_id: u32,
+    pub component_index: u8,
+}
+
+impl NetworkReferenceId {
+    pub fn is_null(&self) -> bool {
+        self.net_id == 0
+    }
+}
+
+/// 可作为同步变量的网络对象引用，同步变量只保存 NetworkReferenceId，读取时再解析
+pub trait SyncVarReference: Default + Clone {
+    fn reference_id(&self) -> NetworkReferenceId;
+
+    fn resolve(id: &NetworkReferenceId) -> Self;
+
+    fn write_reference_id(id: &NetworkReferenceId, writer: &mut NetworkWriter) {
+        id.net_id.serialize(writer);
+    }
+
+    fn read_reference_id(reader: &mut NetworkReader) -> NetworkReferenceId {
+        NetworkReferenceId {
+            net_id: u32::deserialize(reader),
+            component_index: 0,
+        }
+    }
+}
+
+fn spawned_identity(net_id: u32) -> RevelWeak<Box<NetworkIdentity>> {
+    if net_id == 0 {
+        return RevelWeak::default();
+    }
+    NetworkServer.spawned.get(&net_id).cloned().unwrap_or_default()
+}
+
+fn identity_net_id(identity: &RevelWeak<Box<NetworkIdentity>>) -> u32 {
+    identity.get().map_or(0, |identity| identity.net_id())
+}
+
+fn behaviour_reference_id<T: TNetworkBehaviour + ?Sized + 'static>(behaviour: &RevelWeak<Box<T>>) -> NetworkReferenceId {
+    match behaviour.get() {
+        None => NetworkReferenceId::default(),
+        Some(behaviour) => NetworkReferenceId {
+            net_id: identity_net_id(behaviour.get_network_identity()),
+            component_index: behaviour.get_component_index(),
+        },
+    }
+}
+
+fn write_behaviour_reference_id(id: &NetworkReferenceId, writer: &mut NetworkWriter) {
+    id.net_id.serialize(writer);
+    if !id.is_null() {
+        id.component_index.serialize(writer);
+    }
+}
+
+fn read_behaviour_reference_id(reader: &mut NetworkReader) -> NetworkReferenceId {
+    let net_id = u32::deserialize(reader);
+    if net_id == 0 {
+        return NetworkReferenceId::default();
+    }
+    NetworkReferenceId {
+        net_id,
+        component_index: u8::deserialize(reader),
+    }
+}
+
+// 组件索引上的继承链 (基类在前)
+fn behaviour_chain(id: &NetworkReferenceId) -> Vec<RevelWeak<Box<dyn TNetworkBehaviour>>> {
+    match spawned_identity(id.net_id).get() {
+        None => Vec::new(),
+        Some(identity) => identity
+            .network_behaviours()
+            .get(id.component_index as usize)
+            .cloned()
+            .unwrap_or_default(),
+    }
+}
+
+impl SyncVarReference for RevelWeak<Box<NetworkIdentity>> {
+    fn reference_id(&self) -> NetworkReferenceId {
+        NetworkReferenceId {
+            net_id: identity_net_id(self),
+            component_index: 0,
+        }
+    }
+
+    fn resolve(id: &NetworkReferenceId) -> Self {
+        spawned_identity(id.net_id)
+    }
+}
+
+impl SyncVarReference for RevelWeak<GameObject> {
+    fn reference_id(&self) -> NetworkReferenceId {
+        let net_id = match self.get() {
+            None => 0,
+            Some(game_object) => match game_object.try_get_component2::<NetworkIdentity>() {
+                Some(identity) => identity.net_id(),
+                None => {
+                    log::warn!(
+                        "Attempted to sync a GameObject ({}) which isn't networked. GameObject SyncVars must have a NetworkIdentity component.",
+                        game_object.name
+                    );
+                    0
+                }
+            },
+        };
+        NetworkReferenceId {
+            net_id,
+            component_index: 0,
+        }
+    }
+
+    fn resolve(id: &NetworkReferenceId) -> Self {
+        match spawned_identity(id.net_id).get() {
+            None => RevelWeak::default(),
+            Some(identity) => identity.game_object.clone(),
+        }
+    }
+}
+
+impl SyncVarReference for RevelWeak<Box<dyn TNetworkBehaviour>> {
+    fn reference_id(&self) -> NetworkReferenceId {
+        behaviour_reference_id(self)
+    }
+
+    fn resolve(id: &NetworkReferenceId) -> Self {
+        // 继承链最后一个为实际组件
+        behaviour_chain(id).last().cloned().unwrap_or_default()
+    }
+
+    fn write_reference_id(id: &NetworkReferenceId, writer: &mut NetworkWriter) {
+        write_behaviour_reference_id(id, writer);
+    }
+
+    fn read_reference_id(reader: &mut NetworkReader) -> NetworkReferenceId {
+        read_behaviour_reference_id(reader)
+    }
+}
+
+impl<T: TNetworkBehaviour + 'static> SyncVarReference for RevelWeak<Box<T>> {
+    fn reference_id(&self) -> NetworkReferenceId {
+        behaviour_reference_id(self)
+    }
+
+    fn resolve(id: &NetworkReferenceId) -> Self {
+        behaviour_chain(id)
+            .iter()
+            .rev()
+            .find_map(|behaviour| behaviour.downcast::<T>().cloned())
+            .unwrap_or_default()
+    }
+
+    fn write_reference_id(id: &NetworkReferenceId, writer: &mut NetworkWriter) {
+        write_behaviour_reference_id(id, writer);
+    }
+
+    fn read_reference_id(reader: &mut NetworkReader) -> NetworkReferenceId {
+        read_behaviour_reference_id(reader)
+    }
+}
+
+macro_rules! reference_serialize {
+    ($($ty:ty $(where $param:ident)?),* $(,)?) => {
+        $(
+            impl$(<$param: TNetworkBehaviour + 'static>)? DataTypeSerializer for $ty {
+                fn serialize(&self, writer: &mut NetworkWriter) {
+                    <Self as SyncVarReference>::write_reference_id(&self.reference_id(), writer);
+                }
+            }
+
+            impl$(<$param: TNetworkBehaviour + 'static>)? DataTypeDeserializer for $ty {
+                fn deserialize(reader: &mut NetworkReader) -> Self {
+                    let id = <Self as SyncVarReference>::read_reference_id(reader);
+                    <Self as SyncVarReference>::resolve(&id)
+                }
+            }
+        )*
+    };
+}
+
+reference_serialize!(
+    RevelWeak<Box<NetworkIdentity>>,
+    RevelWeak<GameObject>,
+    RevelWeak<Box<dyn TNetworkBehaviour>>,
+    RevelWeak<Box<T>> where T,
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commons::RevelArc;
+    use crate::mirror::{
+        listen_in_scenes, network_prefab, round_trip, DeltaBehaviour, HookBehaviour, NetworkBehaviourOnDeserializer,
+        NetworkBehaviourOnSerializer,
+    };
+
+    fn instantiate(name: &str) -> RevelArc<GameObject> {
+        GameObject::instantiate(&network_prefab(name, &["Tests.HookBehaviour"]).build().unwrap())
+    }
+
+    #[test]
+    fn test_reference_sync_var() {
+        let _transport = listen_in_scenes(vec![("Assets/Scenes/Test.unity", vec![])]);
+
+        let holder = instantiate("Holder");
+        let target = instantiate("Target");
+        NetworkServer::spawn(holder.downgrade());
+        NetworkServer::spawn(target.downgrade());
+        let target_net_id = target.try_get_component2::<NetworkIdentity>().unwrap().net_id();
+
+        let mut behaviour = holder.try_get_component2::<HookBehaviour>().unwrap();
+        behaviour.set_target(target.downgrade());
+        assert!(behaviour.get_target().ptr_eq(&target.downgrade()));
+        assert_eq!(behaviour.get_target_reference_id().net_id, target_net_id);
+
+        // 另一实例按 netId 解析，hook 收到解析后的旧值和新值
+        let receiver = instantiate("Receiver");
+        let mut received = receiver.try_get_component2::<HookBehaviour>().unwrap();
+        let mut writer = NetworkWriter::new();
+        behaviour.on_serialize(&mut writer, false);
+        received.on_deserialize(&mut NetworkReader::new(writer.to_vec()), false);
+        assert!(received.get_target().ptr_eq(&target.downgrade()));
+        assert_eq!(received.target_changes.len(), 1);
+        let (old_value, new_value) = &received.target_changes[0];
+        assert!(!old_value.upgradable());
+        assert!(new_value.ptr_eq(&target.downgrade()));
+
+        // 目标销毁后解析为空，netId 保留
+        NetworkServer::destroy(target.downgrade());
+        assert!(!behaviour.get_target().upgradable());
+        assert!(!received.get_target().upgradable());
+        assert_eq!(received.get_target_reference_id().net_id, target_net_id);
+
+        NetworkServer.shutdown();
+    }
+
+    fn net_id_bytes(net_id: u32) -> Vec<u8> {
+        let mut writer = NetworkWriter::new();
+        net_id.serialize(&mut writer);
+        writer.to_vec()
+    }
+
+    #[test]
+    fn test_reference_round_trip() {
+        let _transport = listen_in_scenes(vec![("Assets/Scenes/Test.unity", vec![])]);
+
+        let prefab = network_prefab("Tank", &["Tests.DeltaBehaviour", "Tests.HookBehaviour"])
+            .build()
+            .unwrap();
+        let game_object = GameObject::instantiate(&prefab);
+        NetworkServer::spawn(game_object.downgrade());
+        let identity = game_object.try_get_component2::<NetworkIdentity>().unwrap();
+        let net_id = identity.net_id();
+
+        // NetworkIdentity 和 GameObject 只写 netId
+        let (bytes, value) = round_trip(&identity.downgrade());
+        assert_eq!(bytes, net_id_bytes(net_id));
+        assert!(value.ptr_eq(&identity.downgrade()));
+
+        let (bytes, value) = round_trip(&game_object.downgrade());
+        assert_eq!(bytes, net_id_bytes(net_id));
+        assert!(value.ptr_eq(&game_object.downgrade()));
+
+        // NetworkBehaviour 写 netId 和组件索引
+        let behaviour = game_object.try_get_component2::<HookBehaviour>().unwrap().downgrade();
+        let mut expected = net_id_bytes(net_id);
+        expected.push(1);
+        let (bytes, value) = round_trip(&behaviour);
+        assert_eq!(bytes, expected);
+        assert!(value.ptr_eq(&behaviour));
+
+        let dyn_behaviour = identity.network_behaviours()[1].last().cloned().unwrap();
+        let (bytes, value) = round_trip(&dyn_behaviour);
+        assert_eq!(bytes, expected);
+        assert!(value.ptr_eq(&dyn_behaviour));
+
+        // 按类型解析时索引上不是该组件则为空
+        let mut reader = NetworkReader::new(expected.clone());
+        assert!(!RevelWeak::<Box<DeltaBehaviour>>::deserialize(&mut reader).upgradable());
+
+        // 空引用只写 0，不写组件索引
+        let (bytes, value) = round_trip(&RevelWeak::<Box<HookBehaviour>>::default());
+        assert_eq!(bytes, net_id_bytes(0));
+        assert!(!value.upgradable());
+        let (bytes, value) = round_trip(&RevelWeak::<GameObject>::default());
+        assert_eq!(bytes, net_id_bytes(0));
+        assert!(!value.upgradable());
+
+        NetworkServer.shutdown();
+    }
+}
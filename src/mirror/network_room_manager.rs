@@ -164,7 +164,7 @@ impl NetworkRoomManager {
 
             let new_room_game_object = self.on_room_server_create_room_player(connection.clone())
                 .map_or_else(|| {
-                    if let Some(prefab) = Metadata::get_prefab(&self.room_player_prefab) {
+                    if let Some(prefab) = Metadata::current().get_prefab(&self.room_player_prefab) {
                         return Some(GameObject::instantiate(prefab));
                     }
                     None
@@ -252,12 +252,13 @@ impl NetworkRoomManager {
         let mut game_player = self.on_room_server_create_game_player(connection.clone(), room_player.clone());
 
         if game_player.is_none() {
-            if let Some(prefab) = Metadata::get_prefab(&self.player_prefab) {
+            if let Some(prefab) = Metadata::current().get_prefab(&self.player_prefab) {
                 let mut game_object = GameObject::instantiate(prefab);
                 // 设置位置
                 if let Some(start_position) = self.get_start_position() {
-                    game_object.transform.local_position = start_position.position;
-                    game_object.transform.local_rotation = start_position.rotation;
+                    game_object
+                        .transform
+                        .set_position_and_rotation(start_position.position(), start_position.rotation());
                 }
                 game_player = Some(game_object);
             } else {
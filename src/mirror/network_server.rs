@@ -25,6 +25,8 @@ use crate::mirror::stable_hash::StableHash;
 use crate::mirror::transport::{
     CallbackProcessor, TransportChannel, TransportError, TransportManager,
 };
+use crate::mirror::EntityStateUpdate;
+use crate::mirror::NetworkObjectPool;
 use crate::mirror::NetworkReader;
 use crate::mirror::NetworkReaderPool;
 use crate::mirror::NetworkTime;
@@ -32,10 +34,11 @@ use crate::mirror::NetworkWriter;
 use crate::mirror::NetworkWriterPool;
 use crate::mirror::Visibility;
 use crate::mirror::{NetworkConnectionToClient, NetworkIdentity, RemoteCallType};
-use crate::unity_engine::{GameObject, Time, WorldManager};
-use once_cell::sync::Lazy;
+use crate::unity_engine::{GameObject, MonoBehaviour, Time, WorldManager};
+use crate::commons::thread_static;
 use std::collections::HashMap;
 use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
 
 #[allow(unused)]
 pub struct NetworkServerStatic {
@@ -53,6 +56,11 @@ pub struct NetworkServerStatic {
     pub(crate) disconnect_inactive_connections: bool,
     pub(crate) disconnect_inactive_timeout: f32,
 
+    // 每个连接每秒可发送的实体状态字节数，0 表示不限制，可通过 connection.entity_states.max_bandwidth 单独设置
+    pub max_bandwidth_per_connection: usize,
+    // 优先级随与玩家距离衰减，距离为该值时优先级减半
+    pub priority_distance: f32,
+
     actual_tick_rate_counter: i32,
     actual_tick_rate_start: f64,
     actual_tick_rate: i32,
@@ -69,6 +77,12 @@ pub struct NetworkServerStatic {
 
     // Identity
     pub spawned: HashMap<u32, RevelWeak<Box<NetworkIdentity>>>,
+    // 到期的延迟销毁，在 LateUpdate 中统一执行
+    pending_destroys: Vec<RevelWeak<GameObject>>,
+    // 运行时注册的预制体 assetId -> 构建函数
+    prefab_handlers: HashMap<u32, PrefabHandler>,
+    // 按 assetId 复用的网络对象
+    pub object_pool: NetworkObjectPool,
 
     // State
     pub active: bool,
@@ -99,35 +113,45 @@ pub struct NetworkServerStatic {
     >,
 }
 
-static mut CONFIG: Lazy<NetworkServerStatic> = Lazy::new(|| NetworkServerStatic {
-    initialized: false,
-    address: "0.0.0.0",
-    port: 7777,
-    listen: true,
-    max_connections: 0,
-    tick_rate: 30,
-    full_update_duration: TimeSample::new(30),
-    late_send_time: 0.0,
-    disconnect_inactive_connections: false,
-    disconnect_inactive_timeout: 60.0,
-    actual_tick_rate_counter: 0,
-    actual_tick_rate_start: 0.0,
-    actual_tick_rate: 0,
-    early_update_duration: TimeSample::new(0),
-    late_update_duration: TimeSample::new(0),
-    is_loading_scene: false,
-    exceptions_disconnect: true,
-    client_snapshot_settings: SnapshotInterpolationSettings::new(),
-    connections: Default::default(),
-    message_handlers: Default::default(),
-    next_network_id: 1,
-    spawned: Default::default(),
-    active: false,
-    on_connected_event: SelfMutAction::default(),
-    on_disconnected_event: SelfMutAction::default(),
-    on_error_event: SelfMutAction::default(),
-    on_transport_exception_event: SelfMutAction::default(),
-});
+thread_static! {
+    static mut CONFIG: NetworkServerStatic = NetworkServerStatic {
+        initialized: false,
+        address: "0.0.0.0",
+        port: 7777,
+        listen: true,
+        max_connections: 0,
+        tick_rate: 30,
+        full_update_duration: TimeSample::new(30),
+        late_send_time: 0.0,
+        disconnect_inactive_connections: false,
+        disconnect_inactive_timeout: 60.0,
+        max_bandwidth_per_connection: 0,
+        priority_distance: 20.0,
+        actual_tick_rate_counter: 0,
+        actual_tick_rate_start: 0.0,
+        actual_tick_rate: 0,
+        early_update_duration: TimeSample::new(0),
+        late_update_duration: TimeSample::new(0),
+        is_loading_scene: false,
+        exceptions_disconnect: true,
+        client_snapshot_settings: SnapshotInterpolationSettings::new(),
+        connections: Default::default(),
+        message_handlers: Default::default(),
+        next_network_id: 1,
+        spawned: Default::default(),
+        pending_destroys: Default::default(),
+        prefab_handlers: Default::default(),
+        object_pool: Default::default(),
+        active: false,
+        on_connected_event: SelfMutAction::default(),
+        on_disconnected_event: SelfMutAction::default(),
+        on_error_event: SelfMutAction::default(),
+        on_transport_exception_event: SelfMutAction::default(),
+    };
+}
+
+/// 运行时预制体的构建函数，返回已加入场景的对象
+pub type PrefabHandler = Rc<dyn Fn() -> RevelArc<GameObject>>;
 
 #[allow(unused)]
 pub enum ReplacePlayerOptions {
@@ -167,12 +191,26 @@ impl NetworkServer {
             false => 0.0,
         }
     }
+
+    pub fn address(&self) -> &'static str {
+        self.address
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// 监听地址，需在 listen 之前设置。同一进程中的多个服务器实例使用不同端口
+    pub fn set_address(&mut self, address: &'static str, port: u16) {
+        self.address = address;
+        self.port = port;
+    }
 }
 
-static mut NETWORK_SERVER: Lazy<RevelArc<Box<NetworkServer>>> =
-    Lazy::new(|| RevelArc::new(Box::new(NetworkServer)));
-static mut NETWORK_TIME: Lazy<RevelArc<Box<NetworkTime>>> =
-    Lazy::new(|| RevelArc::new(Box::new(NetworkTime)));
+thread_static! {
+    static mut NETWORK_SERVER: RevelArc<Box<NetworkServer>> = RevelArc::new(Box::new(NetworkServer));
+    static mut NETWORK_TIME: RevelArc<Box<NetworkTime>> = RevelArc::new(Box::new(NetworkTime));
+}
 
 impl NetworkServer {
     pub fn listen(&mut self, max_connections: i32) {
@@ -340,6 +378,8 @@ impl NetworkServer {
                     return;
                 }
 
+                // 消息格式错误时连接已断开，不再处理剩余的消息
+                let mut malformed = false;
                 while let Some((message, remote_timestamp)) = un_batcher.get_next_message() {
                     NetworkReaderPool::get_with_slice_return(message, |reader| {
                         if reader.remaining() < ID_SIZE {
@@ -360,7 +400,9 @@ impl NetworkServer {
 
                         conn.remote_time_stamp = remote_timestamp;
 
-                        if !Self.unpack_and_invoke(conn.clone(), reader, channel) {
+                        let handled = Self.unpack_and_invoke(conn.clone(), reader, channel);
+                        malformed = reader.has_error();
+                        if !handled {
                             if Self.exceptions_disconnect {
                                 log::error!(
                                 "NetworkServer: connectionId:{} received message with unknown type, disconnecting.",
@@ -376,9 +418,12 @@ impl NetworkServer {
                             return;
                         }
                     });
+                    if malformed {
+                        break;
+                    }
                 }
 
-                if !Self.is_loading_scene && un_batcher.batches_count() > 0 {
+                if !malformed && !Self.is_loading_scene && un_batcher.batches_count() > 0 {
                     log::error!("NetworkServer: connectionId:{} has unprocessed batches, skipping message processing.",conn_id);
                 }
             });
@@ -509,6 +554,9 @@ impl NetworkServer {
 
         for weak_identity in Self.spawned.values_mut() {
             if let Some(mut identity) = weak_identity.upgrade() {
+                if !Self::same_scene(&identity, &connection) {
+                    continue;
+                }
                 match identity.visibility {
                     Visibility::Normal => {
                         identity.add_observer(connection.clone());
@@ -612,8 +660,15 @@ impl NetworkServer {
                                 message.function_hash,
                                 RemoteCallType::Command,
                                 reader,
-                                connection,
+                                connection.clone(),
                             );
+                            if let Some(err) = reader.error() {
+                                let mut connection = connection;
+                                connection.disconnect_with_reason(&format!(
+                                    "malformed command {} arguments: {}",
+                                    message.function_hash, err
+                                ));
+                            }
                         },
                     );
                 }
@@ -718,7 +773,14 @@ impl NetworkServer {
                 }
                 Some(handler) => {
                     connection.last_message_time = NetworkTime.local_time() as f32;
-                    handler.invoke(connection, reader, channel);
+                    handler.invoke(connection.clone(), reader, channel);
+                    // 消息格式错误的客户端一律断开，不受 exceptions_disconnect 影响
+                    if let Some(err) = reader.error() {
+                        connection.disconnect_with_reason(&format!(
+                            "malformed message {}: {}",
+                            msg_type, err
+                        ));
+                    }
                     true
                 }
             };
@@ -792,8 +854,13 @@ impl NetworkServer {
     }
 
     pub fn shutdown(&mut self) {
+        self.shutdown_with_reason("Server shutdown")
+    }
+
+    /// 关闭服务器，以 reason 断开所有客户端
+    pub fn shutdown_with_reason(&mut self, reason: &str) {
         if self.initialized {
-            self.disconnect_all();
+            self.disconnect_all(reason);
 
             TransportManager.active.server_stop();
 
@@ -809,6 +876,7 @@ impl NetworkServer {
         self.connections.clear();
         self.message_handlers.clear();
         self.cleanup_spawned();
+        self.object_pool.clear();
         self.active = false;
         NetworkIdentity::reset_server_statics();
 
@@ -818,15 +886,17 @@ impl NetworkServer {
         self.on_transport_exception_event.reset();
     }
 
-    fn disconnect_all(&mut self) {
+    fn disconnect_all(&mut self, reason: &str) {
         for conn in self.connections.values_mut() {
-            conn.disconnect.call(());
+            conn.disconnect_with_reason(reason);
         }
         self.connections.clear();
     }
 
     fn cleanup_spawned(&mut self) {
-        for (_, identity) in Self.spawned.iter() {
+        // destroy 会修改 spawned，先取出
+        let spawned = Self.spawned.values().cloned().collect::<Vec<_>>();
+        for identity in spawned {
             if let Some(identity) = identity.upgrade() {
                 Self::destroy(identity.game_object.clone())
             }
@@ -952,7 +1022,7 @@ impl NetworkServer {
     }
 
     fn send_spawn_message(
-        identity: RevelArc<Box<NetworkIdentity>>,
+        mut identity: RevelArc<Box<NetworkIdentity>>,
         mut connection: RevelArc<Box<NetworkConnectionToClient>>,
     ) {
         if identity.server_only {
@@ -972,6 +1042,7 @@ impl NetworkServer {
         );
         NetworkWriterPool::return_(owner_writer.into_inner());
         NetworkWriterPool::return_(observers_writer.into_inner());
+        identity.reset_delta_baseline(connection.connection_id);
 
         if let Some(identity_game_object) = identity.game_object.upgrade() {
             let spawn_message = SpawnMessage::new(
@@ -979,10 +1050,10 @@ impl NetworkServer {
                 is_local_player,
                 is_owner,
                 identity.scene_id,
-                identity.game_object.get().unwrap().asset_id,
-                identity_game_object.transform.local_position,
-                identity_game_object.transform.local_rotation,
-                identity_game_object.transform.local_scale,
+                identity.asset_id(),
+                identity_game_object.transform.local_position(),
+                identity_game_object.transform.local_rotation(),
+                identity_game_object.transform.local_scale(),
                 payload,
             );
 
@@ -1016,18 +1087,12 @@ impl NetworkServer {
             return false;
         }
 
-        let mut identities = vec![];
-
-        for root_game_object in WorldManager::root_game_objects().iter() {
-            if let Some(game_object) = root_game_object.get() {
-                identities = game_object.get_components::<NetworkIdentity>();
-            }
-        }
+        let identities = Self::find_network_identities(&WorldManager::all_root_game_objects());
 
         for identity in identities.iter() {
             if let Some(weak_identity) = identity.downcast::<NetworkIdentity>() {
                 if let Some(real_identity) = weak_identity.get() {
-                    if real_identity.is_scene_object() && real_identity.net_id() == 0 {
+                    if real_identity.is_scene_object() && real_identity.net_id() == 0 && !real_identity.scene_id_conflict() {
                         if let Some(game_object) = real_identity.game_object.get() {
                             game_object.set_active(true);
                         }
@@ -1039,6 +1104,9 @@ impl NetworkServer {
         for identity in identities.iter() {
             if let Some(weak_identity) = identity.downcast::<NetworkIdentity>() {
                 if let Some(real_identity) = weak_identity.get() {
+                    if real_identity.is_scene_object() && real_identity.scene_id_conflict() {
+                        continue;
+                    }
                     if real_identity.is_scene_object() && real_identity.net_id() == 0 && Self::valid_parent(real_identity) {
                         if real_identity.game_object.upgradable() {
                             Self::spawn_with_connection(real_identity.game_object.clone(), real_identity.connection());
@@ -1052,6 +1120,79 @@ impl NetworkServer {
     }
 
 
+    /// 递归收集对象及其子对象上的 NetworkIdentity
+    pub fn find_network_identities(
+        game_objects: &[RevelWeak<GameObject>],
+    ) -> Vec<RevelWeak<Box<dyn MonoBehaviour>>> {
+        let mut identities = vec![];
+        for weak_game_object in game_objects.iter() {
+            if let Some(game_object) = weak_game_object.get() {
+                identities.extend(game_object.get_components::<NetworkIdentity>());
+                let children = game_object
+                    .children
+                    .values()
+                    .map(|child| child.downgrade())
+                    .collect::<Vec<_>>();
+                identities.extend(Self::find_network_identities(&children));
+            }
+        }
+        identities
+    }
+
+    /// 注册运行时预制体，对应 Mirror 的 RegisterSpawnHandler
+    /// builder 在代码中创建对象 (例如通过 MetadataPrefab::builder 和 GameObject::instantiate)，
+    /// 创建后的对象使用 asset_id 生成，客户端需注册相同 assetId 的生成处理
+    pub fn register_prefab<F>(&mut self, asset_id: u32, builder: F)
+    where
+        F: Fn() -> RevelArc<GameObject> + 'static,
+    {
+        if asset_id == 0 {
+            log::error!("NetworkServer.RegisterPrefab: assetId can not be 0.");
+            return;
+        }
+        if self.prefab_handlers.insert(asset_id, Rc::new(builder)).is_some() {
+            log::warn!("NetworkServer.RegisterPrefab: replacing existing prefab for assetId {}.", asset_id);
+        }
+    }
+
+    pub fn unregister_prefab(&mut self, asset_id: u32) -> bool {
+        self.prefab_handlers.remove(&asset_id).is_some()
+    }
+
+    pub fn is_prefab_registered(&self, asset_id: u32) -> bool {
+        self.prefab_handlers.contains_key(&asset_id)
+    }
+
+    /// 通过注册的构建函数创建对象，对象及其 NetworkIdentity 使用注册的 assetId
+    pub fn instantiate_prefab(&self, asset_id: u32) -> Option<RevelArc<GameObject>> {
+        let Some(builder) = self.prefab_handlers.get(&asset_id).cloned() else {
+            log::error!("NetworkServer.InstantiatePrefab: no prefab registered for assetId {}.", asset_id);
+            return None;
+        };
+        let mut game_object = builder();
+        game_object.asset_id = asset_id;
+        match Self::get_network_identity(game_object.downgrade()) {
+            Some(mut identity) => identity.set_asset_id(asset_id),
+            None => log::warn!(
+                "NetworkServer.InstantiatePrefab: {} built for assetId {} has no NetworkIdentity and can not be spawned.",
+                game_object.name,
+                asset_id
+            ),
+        }
+        Some(game_object)
+    }
+
+    /// 创建并生成运行时预制体，connection 为空时没有所有者
+    pub fn spawn_prefab(
+        &self,
+        asset_id: u32,
+        connection: RevelWeak<Box<NetworkConnectionToClient>>,
+    ) -> Option<RevelArc<GameObject>> {
+        let game_object = self.instantiate_prefab(asset_id)?;
+        Self::spawn_with_connection(game_object.downgrade(), connection);
+        Some(game_object)
+    }
+
     pub fn spawn(
         game_object: RevelWeak<GameObject>,
     ) {
@@ -1087,6 +1228,15 @@ impl NetworkServer {
                 Some(network_identity) => {
                     if let Some(weak_network_identity) = network_identity.downcast::<NetworkIdentity>() {
                         if let Some(identity) = weak_network_identity.get() {
+                            if identity.scene_id_conflict() {
+                                log::error!(
+                                    "SpawnObject {} has a duplicate sceneId {:X} in its scene and can not be spawned.",
+                                    identity.name(),
+                                    identity.scene_id
+                                );
+                                return;
+                            }
+
                             if Self.spawned.contains_key(&identity.net_id()) {
                                 log::warn!(
                                     "{} [netId={}] was already spawned.",
@@ -1168,12 +1318,64 @@ impl NetworkServer {
                 }
                 Some(identity) => {
                     if let Some(serialization) = Self::serialize_for_connection(weak_identity.clone(), connection.downgrade()) {
-                        let message = EntityStateMessage::new(identity.net_id(), serialization.to_vec());
-                        connection.send_message(message, TransportChannel::Reliable)
+                        connection.entity_states.push(identity.net_id(), serialization.to_vec());
                     }
                 }
             }
         }
+
+        Self::send_entity_states(connection);
+    }
+
+    // 按优先级在带宽预算内发送实体状态，其余推迟到之后的帧
+    fn send_entity_states(mut connection: RevelArc<Box<NetworkConnectionToClient>>) {
+        let max_bandwidth = connection
+            .entity_states
+            .max_bandwidth
+            .unwrap_or(Self.max_bandwidth_per_connection);
+
+        if max_bandwidth > 0 {
+            for net_id in connection.entity_states.pending_net_ids() {
+                match Self.spawned.get(&net_id).and_then(|identity| identity.get()) {
+                    None => connection.entity_states.remove(net_id),
+                    Some(identity) => {
+                        let priority = Self::send_priority(identity, &connection);
+                        connection.entity_states.accumulate(net_id, priority);
+                    }
+                }
+            }
+        }
+
+        for (net_id, update) in connection.entity_states.take(Time::unscaled_time_f64(), max_bandwidth) {
+            match update {
+                EntityStateUpdate::Delta(payload) => {
+                    connection.send_message(EntityStateMessage::new(net_id, payload), TransportChannel::Reliable);
+                }
+                // 客户端对已生成的对象按初始状态应用 SpawnMessage
+                EntityStateUpdate::Resync => {
+                    if let Some(identity) = Self.spawned.get(&net_id).and_then(|identity| identity.upgrade()) {
+                        Self::send_spawn_message(identity, connection.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    // 组件优先级按与连接玩家的距离衰减，连接拥有的对象不衰减
+    fn send_priority(identity: &NetworkIdentity, connection: &NetworkConnectionToClient) -> f32 {
+        let priority = identity.send_priority();
+        if identity.connection().ptr_eq(&connection.self_weak) {
+            return priority;
+        }
+
+        let player = connection.identity.get().and_then(|player| player.game_object.get());
+        match (player, identity.game_object.get()) {
+            (Some(player), Some(game_object)) => {
+                let distance = (player.transform.position() - game_object.transform.position()).norm();
+                priority / (1.0 + distance / Self.priority_distance.max(f32::EPSILON))
+            }
+            _ => priority,
+        }
     }
 
     fn serialize_for_connection(identity: RevelWeak<Box<NetworkIdentity>>, connection: RevelWeak<Box<NetworkConnectionToClient>>) -> Option<RevelArc<NetworkWriter>> {
@@ -1182,6 +1384,14 @@ impl NetworkServer {
 
             let owned = identity.connection().ptr_eq(&connection);
 
+            // 存在增量压缩组件时按连接拼装
+            if !serialization.delta_snapshots.is_empty() {
+                let connection_id = connection.get()?.connection_id;
+                return identity
+                    .serialize_delta_for_connection(&serialization, connection_id, owned)
+                    .map(RevelArc::new);
+            }
+
             match owned {
                 true => {
                     if serialization.owner_writer.position > 0 {
@@ -1246,7 +1456,15 @@ impl NetworkServer {
             if identity.scene_id != 0 {
                 Self::un_spawn_internal(game_object.clone(), true);
             } else {
-                Self::un_spawn_internal(game_object.clone(), false);
+                if Self.object_pool.is_registered(identity.asset_id()) {
+                    // 放回对象池，池已满或不是从池中取出的对象时销毁
+                    Self::un_spawn_internal(game_object.clone(), true);
+                    if Self.object_pool.put(game_object.clone()) {
+                        return;
+                    }
+                } else {
+                    Self::un_spawn_internal(game_object.clone(), false);
+                }
                 identity.destroy_called = true;
 
                 if let Some(go) = game_object.get() {
@@ -1259,6 +1477,17 @@ impl NetworkServer {
         }
     }
 
+    /// 延迟 delay 秒后销毁，对应 Unity 的 Destroy(obj, t)
+    pub fn destroy_after(game_object: RevelWeak<GameObject>, delay: f32) {
+        let Some(real_game_object) = game_object.get() else {
+            log::info!("NetworkServer.Destroy(): object is null");
+            return;
+        };
+        // 回调在对象自身的调度器中执行，不能在此时释放对象
+        let target = game_object.clone();
+        real_game_object.invoke(delay, move || Self.pending_destroys.push(target.clone()));
+    }
+
     pub fn rebuild_observers(identity: RevelArc<Box<NetworkIdentity>>, initialize: bool) {
         // TODO aoi sys
         if identity.visibility == Visibility::ForceShown || true {
@@ -1283,12 +1512,122 @@ impl NetworkServer {
 
     fn add_all_ready_server_connections_to_observers(mut identity: RevelArc<Box<NetworkIdentity>>) {
         for connection in Self.connections.values_mut() {
-            if connection.is_ready {
+            if connection.is_ready && Self::same_scene(&identity, connection) {
                 identity.add_observer(connection.clone());
             }
         }
     }
 
+    /// 对象与连接是否处于同一场景实例。
+    /// 隔离实例中的对象只对分配到该实例的连接可见；
+    /// 分配到实例的连接只能看到该实例以及 DontDestroyOnLoad 中的对象
+    pub fn same_scene(identity: &NetworkIdentity, connection: &NetworkConnectionToClient) -> bool {
+        let Some(game_object) = identity.game_object.get() else {
+            return false;
+        };
+        let scene_handle = game_object.scene_handle();
+        if connection.scene_handle == 0 {
+            return scene_handle == 0 || !WorldManager::is_scene_instance(scene_handle);
+        }
+        scene_handle == 0 || scene_handle == connection.scene_handle
+    }
+
+    /// 将连接分配到某个场景实例。玩家对象会一起移动过去，
+    /// 并且只能观察到该场景实例中的对象
+    pub fn set_connection_scene(
+        mut connection: RevelArc<Box<NetworkConnectionToClient>>,
+        scene_handle: u32,
+    ) -> bool {
+        if scene_handle != 0 && !WorldManager::get_scene_by_handle(scene_handle).upgradable() {
+            log::error!("SetConnectionScene: scene instance {} is not loaded.", scene_handle);
+            return false;
+        }
+        connection.scene_handle = scene_handle;
+
+        if let Some(player) = connection.identity.get() {
+            if scene_handle != 0 {
+                WorldManager::move_game_object_to_scene(player.game_object.clone(), scene_handle);
+            }
+        }
+
+        // 先移除其他场景实例中的对象，再补上新场景中的对象
+        let observing = connection.observing.iter().cloned().collect::<Vec<_>>();
+        for weak_identity in observing {
+            if let Some(mut identity) = weak_identity.upgrade() {
+                if !Self::same_scene(&identity, &connection) {
+                    identity.remove_observer(connection.clone());
+                    connection.remove_from_observing(identity, false);
+                }
+            }
+        }
+
+        // 玩家对象所在场景发生变化，其他连接的观察关系也要更新
+        if let Some(player) = connection.identity.upgrade() {
+            for other in Self.connections.values_mut() {
+                if other.connection_id == connection.connection_id || !other.is_ready {
+                    continue;
+                }
+                if Self::same_scene(&player, other) {
+                    if player.visibility != Visibility::ForceHidden {
+                        let mut player = player.clone();
+                        player.add_observer(other.clone());
+                    }
+                } else if player.observers.contains_key(&other.connection_id) {
+                    let mut player = player.clone();
+                    player.remove_observer(other.clone());
+                    other.remove_from_observing(player, false);
+                }
+            }
+        }
+
+        if connection.is_ready {
+            for weak_identity in Self.spawned.values() {
+                if let Some(mut identity) = weak_identity.upgrade() {
+                    if identity.visibility != Visibility::ForceHidden
+                        && Self::same_scene(&identity, &connection)
+                    {
+                        identity.add_observer(connection.clone());
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    /// 向某个场景实例中的所有就绪连接发送消息
+    pub fn send_to_scene<T: NetworkMessage>(
+        scene_handle: u32,
+        mut message: T,
+        channel: TransportChannel,
+    ) {
+        if !Self.active {
+            log::warn!("Can not send using NetworkServer.SendToScene<T>(T msg) because NetworkServer is not active");
+            return;
+        }
+
+        NetworkWriterPool::get_by_closure(|writer| {
+            message.serialize(writer);
+            let segment = writer.to_vec();
+
+            let max = max_message_size(channel);
+            if writer.position > max {
+                log::error!(
+                    "NetworkServer.SendToScene: message of type {} with a size of {} bytes is larger than the max allowed message size in one batch: {}.\nThe message was dropped, please make it smaller.",
+                    T::get_full_name(),
+                    writer.position,
+                    max
+                );
+                return;
+            }
+
+            for connection in Self.connections.values_mut() {
+                if connection.is_ready && connection.scene_handle == scene_handle {
+                    connection.send(&segment, channel);
+                }
+            }
+        })
+    }
+
     fn get_network_identity(
         game_object: RevelWeak<GameObject>,
     ) -> Option<RevelArc<Box<NetworkIdentity>>> {
@@ -1332,8 +1671,12 @@ impl NetworkServer {
             }
         }
     }
+    /// 对象池中的对象取消生成后放回池中
     pub fn un_spawn(game_object: RevelWeak<GameObject>) {
-        Self::un_spawn_internal(game_object, true)
+        Self::un_spawn_internal(game_object.clone(), true);
+        if Self.active {
+            Self.object_pool.put(game_object);
+        }
     }
 
     pub fn un_spawn_internal(game_object: RevelWeak<GameObject>, reset_state: bool) {
@@ -1352,6 +1695,11 @@ impl NetworkServer {
 
         if let Some(mut identity) = Self::get_network_identity(game_object) {
             Self.spawned.remove(&identity.net_id());
+            // 取消生成后不再执行之前安排的 Invoke 和协程 (例如 destroy_after)
+            if let Some(game_object) = identity.game_object.get() {
+                game_object.cancel_all_invokes();
+                game_object.stop_all_coroutines();
+            }
             if let Some(conn) = identity.connection().get() {
                 conn.remove_owned_object(identity.clone())
             }
@@ -1436,6 +1784,10 @@ impl NetworkServer {
             Self.late_update_duration.begin();
         }
 
+        for game_object in std::mem::take(&mut Self.pending_destroys) {
+            Self::destroy(game_object);
+        }
+
         let send_interval_elapsed = AccurateInterval::elapsed(
             NetworkTime.local_time(),
             Self.send_interval(),
@@ -1491,3 +1843,192 @@ impl DerefMut for NetworkServer {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata_settings::metadata_prefab::MetadataPrefab;
+    use crate::mirror::batching::batcher::Batcher;
+    use crate::mirror::messages::message::MessageSerializer;
+    use crate::mirror::{listen_in_scenes, network_prefab, TestTransport};
+
+    #[test]
+    fn test_register_prefab() {
+        let half_sqrt2 = std::f32::consts::FRAC_1_SQRT_2;
+        let prefab = network_prefab("Loot", &[])
+            .local_position([1.0, 0.0, 2.0])
+            .local_rotation([0.0, half_sqrt2, 0.0, half_sqrt2])
+            .child(MetadataPrefab::builder("Model").local_position([0.0, 0.0, 1.0]))
+            .build()
+            .unwrap();
+        // 子对象的世界坐标由父节点推算
+        let model = &prefab.children[0].transform;
+        assert!((nalgebra::Vector3::from(model.position) - nalgebra::Vector3::new(2.0, 0.0, 2.0)).norm() < 1e-5);
+        assert_eq!(model.local_position, [0.0, 0.0, 1.0]);
+        NetworkServer.register_prefab(0x4C4F4F54, move || GameObject::instantiate(&prefab));
+        assert!(NetworkServer.is_prefab_registered(0x4C4F4F54));
+        assert!(NetworkServer.instantiate_prefab(1).is_none());
+
+        let game_object = NetworkServer.instantiate_prefab(0x4C4F4F54).unwrap();
+        assert_eq!(game_object.asset_id, 0x4C4F4F54);
+        assert_eq!(game_object.children.len(), 1);
+        assert_eq!(game_object.transform.position(), nalgebra::Vector3::new(1.0, 0.0, 2.0));
+        let model = game_object.children.values().next().unwrap();
+        assert!((model.transform.position() - nalgebra::Vector3::new(2.0, 0.0, 2.0)).norm() < 1e-5);
+        let identity = NetworkServer::get_network_identity(game_object.downgrade()).unwrap();
+        assert_eq!(identity.asset_id(), 0x4C4F4F54);
+        assert_eq!(identity.network_behaviours().len(), 1);
+
+        assert!(NetworkServer.unregister_prefab(0x4C4F4F54));
+        assert!(NetworkServer.instantiate_prefab(0x4C4F4F54).is_none());
+    }
+
+    #[test]
+    fn test_destroy_returns_to_object_pool() {
+        let transport = listen_in_scenes(vec![("Assets/Scenes/Test.unity", vec![])]);
+        let connection = transport.connect(1);
+        NetworkServer::set_client_ready(connection.clone());
+
+        let prefab = network_prefab("Projectile", &[]).build().unwrap();
+        NetworkServer.object_pool.register(11, 4, move || GameObject::instantiate(&prefab));
+        let scene_handle = WorldManager::active_world().get().unwrap().handle();
+
+        let get = || {
+            NetworkServer
+                .object_pool
+                .get(11, nalgebra::Vector3::zeros(), nalgebra::Quaternion::identity(), scene_handle)
+                .unwrap()
+        };
+        let game_object = get();
+        NetworkServer::spawn(game_object.downgrade());
+        let identity = NetworkServer::get_network_identity(game_object.downgrade()).unwrap();
+        let net_id = identity.net_id();
+        assert_ne!(net_id, 0);
+        assert!(identity.observers.contains_key(&1));
+        assert!(connection.observing.contains(&identity.downgrade()));
+
+        // 销毁后放回池中，状态重置
+        NetworkServer::destroy(game_object.downgrade());
+        assert!(game_object.downgrade().upgradable());
+        assert!(!game_object.is_active());
+        assert!(!NetworkServer.spawned.contains_key(&net_id));
+        assert!(!connection.observing.contains(&identity.downgrade()));
+        assert_eq!(NetworkServer.object_pool.stats(11).unwrap().available, 1);
+
+        let reused = get();
+        assert!(reused.downgrade().ptr_eq(&game_object.downgrade()));
+        assert_eq!(identity.net_id(), 0);
+        assert!(!identity.is_server);
+        assert!(identity.observers.is_empty());
+        assert!(!identity.connection().upgradable());
+
+        // 再次生成时分配新的 netId，un_spawn 同样放回池中
+        NetworkServer::spawn(reused.downgrade());
+        assert!(identity.net_id() != 0 && identity.net_id() != net_id);
+        NetworkServer::un_spawn(reused.downgrade());
+        assert_eq!(identity.net_id(), 0);
+        assert_eq!(NetworkServer.object_pool.stats(11).unwrap().returned, 2);
+
+        NetworkServer.shutdown();
+    }
+
+    #[test]
+    fn test_malformed_message_stops_batch() {
+        let transport = TestTransport::listen();
+        let mut connection = transport.connect(1);
+        connection.is_authenticated = true;
+
+        // 截断的 CommandMessage 之后跟一条 ReadyMessage
+        let mut command = NetworkWriter::new();
+        CommandMessage::new(1, 0, 0, vec![1, 2, 3, 4]).serialize(&mut command);
+        let command = command.to_vec();
+        let mut ready = NetworkWriter::new();
+        ReadyMessage.serialize(&mut ready);
+
+        let mut batcher = Batcher::new(1200);
+        batcher.add_message(&command[..command.len() - 2], 1.0);
+        batcher.add_message(ready.to_slice(), 1.0);
+        let mut batch = NetworkWriter::new();
+        assert!(batcher.get_batcher_writer(&mut batch));
+        transport.receive(1, batch.to_slice());
+
+        assert_eq!(transport.disconnected(), vec![1]);
+        assert!(connection.disconnect_reason.is_some());
+        assert!(!connection.is_ready);
+
+        NetworkServer.shutdown();
+    }
+
+    // 与 Kcp2kTransport 一样通过 #[derive(CallbackProcessor)] 保存回调的传输
+    mod loopback {
+        use crate::macro_callback_processor::*;
+        use crate::mirror::Transport;
+
+        #[derive(Default, CallbackProcessor)]
+        pub(super) struct LoopbackTransport;
+
+        impl LoopbackTransport {
+            pub(super) fn connect(connection_id: u64) {
+                on_server_connected_with_address(connection_id, "127.0.0.1");
+            }
+        }
+
+        impl Transport for LoopbackTransport {
+            fn init(&mut self, callback_processor: CallbackProcessor) {
+                init_loopback_transport_callback_processor(callback_processor)
+            }
+            fn available(&self) -> bool {
+                true
+            }
+            fn server_uri(&self) -> http::Uri {
+                http::Uri::from_static("loopback://localhost")
+            }
+            fn server_active(&self) -> bool {
+                true
+            }
+            fn server_start(&mut self, _: (&str, u16)) {}
+            fn server_send(&self, _: u64, _: &[u8], _: TransportChannel) {}
+            fn server_disconnect(&self, _: u64) {}
+            fn server_get_client_address(&self, _: u64) -> Option<String> {
+                None
+            }
+            fn server_stop(&self) {}
+            fn get_max_packet_size(&self, _: TransportChannel) -> usize {
+                1200
+            }
+            fn server_early_update(&self) {}
+            fn server_late_update(&self) {}
+            fn shutdown(&self) {}
+        }
+    }
+
+    #[test]
+    fn test_transport_callbacks_per_server() {
+        use crate::mirror::Transport;
+        use loopback::LoopbackTransport;
+        use std::sync::{Arc, Barrier};
+
+        // 两个服务器都启动后关闭第一个，第二个仍能收到连接
+        let barrier = Arc::new(Barrier::new(2));
+        let run = |stop: bool, barrier: Arc<Barrier>| {
+            std::thread::spawn(move || {
+                Time::start_virtual();
+                TransportManager.active = RevelArc::new(Box::new(LoopbackTransport) as Box<dyn Transport>).into();
+                NetworkServer.listen(16);
+                barrier.wait();
+                if stop {
+                    NetworkServer.shutdown();
+                }
+                barrier.wait();
+                LoopbackTransport::connect(7);
+                let connected = NetworkServer.connections.contains_key(&7);
+                NetworkServer.shutdown();
+                connected
+            })
+        };
+        let stopped = run(true, barrier.clone());
+        let running = run(false, barrier);
+        assert!(!stopped.join().unwrap());
+        assert!(running.join().unwrap());
+    }
+}
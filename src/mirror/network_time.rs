@@ -5,22 +5,24 @@ use crate::mirror::transport::TransportChannel;
 use crate::mirror::transport::TransportChannel::Reliable;
 use crate::mirror::{NetworkConnectionToClient, NetworkServer};
 use crate::unity_engine::{ExponentialMovingAverage, Time};
-use once_cell::sync::Lazy;
+use crate::commons::thread_static;
 use std::ops::{Deref, DerefMut};
 
 const DEFAULT_PING_INTERVAL: f32 = 0.1;
 pub const PING_WINDOW_SIZE: i32 = 50;
 
-static mut NETWORK_TIME_STATIC: Lazy<NetworkTimeStatic> = Lazy::new(|| NetworkTimeStatic {
-    ping_interval: DEFAULT_PING_INTERVAL,
-    last_ping_time: 0.0,
-    rtt: ExponentialMovingAverage::new(PING_WINDOW_SIZE as u32),
-    // local_time: 0.0,
-    prediction_error_window_size: 20,
-    prediction_error_unadjusted: ExponentialMovingAverage::new(20),
-    prediction_error_adjusted: 0.0,
-    predicted_time: 0.0,
-});
+thread_static! {
+    static mut NETWORK_TIME_STATIC: NetworkTimeStatic = NetworkTimeStatic {
+        ping_interval: DEFAULT_PING_INTERVAL,
+        last_ping_time: 0.0,
+        rtt: ExponentialMovingAverage::new(PING_WINDOW_SIZE as u32),
+        // local_time: 0.0,
+        prediction_error_window_size: 20,
+        prediction_error_unadjusted: ExponentialMovingAverage::new(20),
+        prediction_error_adjusted: 0.0,
+        predicted_time: 0.0,
+    };
+}
 
 #[allow(unused)]
 pub struct NetworkTimeStatic {
@@ -1,6 +1,8 @@
 use crate::commons::to_hex_string::ToHexString;
 use crate::mirror::compress::Compress;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::{Display, Formatter};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 pub trait WriteCompress {
     fn compress(&self, writer: &mut NetworkWriter);
@@ -298,9 +300,11 @@ data_type_serialize!(
         f32,
         f64,
         bool,
+        nalgebra::Vector2<f32>,
         nalgebra::Vector3<f32>,
         nalgebra::Vector4<f32>,
-        nalgebra::Quaternion<f32>
+        nalgebra::Quaternion<f32>,
+        nalgebra::Matrix4<f32>
     ),
     |value, writer| writer.write_blittable(*value)
 );
@@ -327,3 +331,92 @@ impl<T: DataTypeSerializer> DataTypeSerializer for &[T] {
         }
     }
 }
+
+// Nullable: bool 标记后跟值
+impl<T: DataTypeSerializer> DataTypeSerializer for Option<T> {
+    fn serialize(&self, writer: &mut NetworkWriter) {
+        match self {
+            None => false.serialize(writer),
+            Some(value) => {
+                true.serialize(writer);
+                value.serialize(writer);
+            }
+        }
+    }
+}
+
+// 定长数组与 C# 数组格式相同
+impl<T: DataTypeSerializer, const N: usize> DataTypeSerializer for [T; N] {
+    fn serialize(&self, writer: &mut NetworkWriter) {
+        writer.write_blittable_compress::<u64>(N as u64 + 1);
+        for item in self {
+            item.serialize(writer);
+        }
+    }
+}
+
+// 字典：数量 + 1，随后依次为键、值
+impl<K: DataTypeSerializer, V: DataTypeSerializer, S> DataTypeSerializer for HashMap<K, V, S> {
+    fn serialize(&self, writer: &mut NetworkWriter) {
+        writer.write_blittable_compress::<u64>(self.len() as u64 + 1);
+        for (key, value) in self {
+            key.serialize(writer);
+            value.serialize(writer);
+        }
+    }
+}
+
+impl<K: DataTypeSerializer, V: DataTypeSerializer> DataTypeSerializer for BTreeMap<K, V> {
+    fn serialize(&self, writer: &mut NetworkWriter) {
+        writer.write_blittable_compress::<u64>(self.len() as u64 + 1);
+        for (key, value) in self {
+            key.serialize(writer);
+            value.serialize(writer);
+        }
+    }
+}
+
+// ValueTuple：按 Item1..ItemN 顺序
+macro_rules! tuple_serialize {
+    ($(($($name:ident),+)),+) => {
+        $(
+            impl<$($name: DataTypeSerializer),+> DataTypeSerializer for ($($name,)+) {
+                #[allow(non_snake_case)]
+                fn serialize(&self, writer: &mut NetworkWriter) {
+                    let ($($name,)+) = self;
+                    $($name.serialize(writer);)+
+                }
+            }
+        )+
+    };
+}
+
+tuple_serialize!(
+    (A),
+    (A, B),
+    (A, B, C),
+    (A, B, C, D),
+    (A, B, C, D, E),
+    (A, B, C, D, E, F),
+    (A, B, C, D, E, F, G)
+);
+
+/// 1899-12-30 到 1970-01-01 的天数
+pub(crate) const OA_DATE_UNIX_EPOCH_DAYS: f64 = 25569.0;
+
+// DateTime 按 OLE 自动化日期 (ToOADate) 写为 double
+impl DataTypeSerializer for SystemTime {
+    fn serialize(&self, writer: &mut NetworkWriter) {
+        let seconds = match self.duration_since(UNIX_EPOCH) {
+            Ok(duration) => duration.as_secs_f64(),
+            Err(err) => -err.duration().as_secs_f64(),
+        };
+        writer.write_blittable(seconds / 86400.0 + OA_DATE_UNIX_EPOCH_DAYS);
+    }
+}
+
+impl DataTypeSerializer for http::Uri {
+    fn serialize(&self, writer: &mut NetworkWriter) {
+        writer.write_str(&self.to_string());
+    }
+}
@@ -0,0 +1,97 @@
+use crate::commons::Object;
+use crate::macro_namespace::*;
+use crate::macro_network_behaviour::*;
+use crate::macro_network_serialize::*;
+use crate::metadata_settings::MetadataNetworkBehaviourWrapper;
+use crate::metadata_settings::Settings;
+use crate::mirror::TNetworkBehaviour;
+use crate::settings_wrapper_register;
+use crate::unity_engine::{GameObject, MonoBehaviour};
+use serde::Deserialize;
+
+#[namespace(prefix = "Tests", rename = "DeltaBehaviour")]
+#[derive(Deserialize, Clone)]
+pub struct MetadataDeltaBehaviour {}
+settings_wrapper_register!(MetadataDeltaBehaviour as MetadataNetworkBehaviourWrapper);
+
+/// 测试用的增量压缩组件
+#[namespace(prefix = "Tests")]
+#[network_behaviour(parent(NetworkBehaviour), metadata(MetadataDeltaBehaviour), delta_compression)]
+pub struct DeltaBehaviour {
+    #[sync_var]
+    health: i32,
+    #[sync_var]
+    name: String,
+}
+
+impl DeltaBehaviourOnChangeCallback for DeltaBehaviour {}
+
+impl MonoBehaviour for DeltaBehaviour {}
+
+impl TNetworkBehaviour for DeltaBehaviour {
+    fn new(_weak_game_object: RevelWeak<GameObject>, _metadata: &MetadataNetworkBehaviourWrapper) -> Self
+    where
+        Self: Sized,
+    {
+        Self::default()
+    }
+}
+
+/// 测试用的派生类型，作为 Command 参数
+#[namespace(prefix = "Tests")]
+#[derive(Debug, PartialEq, Clone, NetworkSerialize, NetworkDeserialize)]
+pub struct Loot {
+    pub item: String,
+    pub count: u32,
+}
+
+#[namespace(prefix = "Tests", rename = "HookBehaviour")]
+#[derive(Deserialize, Clone)]
+pub struct MetadataHookBehaviour {}
+settings_wrapper_register!(MetadataHookBehaviour as MetadataNetworkBehaviourWrapper);
+
+/// 测试用组件，记录 hook 的调用
+#[namespace(prefix = "Tests")]
+#[network_behaviour(parent(NetworkBehaviour), metadata(MetadataHookBehaviour))]
+pub struct HookBehaviour {
+    #[sync_var(hook = on_health)]
+    health: i32,
+    #[sync_var(hook = on_name, hook_on_initial)]
+    name: String,
+    #[sync_var(hook = on_target)]
+    target: RevelWeak<GameObject>,
+
+    pub health_changes: Vec<(i32, i32)>,
+    pub name_changes: Vec<(String, String)>,
+    pub target_changes: Vec<(RevelWeak<GameObject>, RevelWeak<GameObject>)>,
+}
+
+impl HookBehaviourOnChangeCallback for HookBehaviour {}
+
+impl HookBehaviour {
+    fn on_health(&mut self, old_value: &i32, new_value: &i32) {
+        self.health_changes.push((*old_value, *new_value));
+    }
+
+    fn on_name(&mut self, old_value: &String, new_value: &String) {
+        self.name_changes.push((old_value.clone(), new_value.clone()));
+    }
+
+    fn on_target(&mut self, old_value: &RevelWeak<GameObject>, new_value: &RevelWeak<GameObject>) {
+        self.target_changes.push((old_value.clone(), new_value.clone()));
+    }
+
+    #[command(HookBehaviour)]
+    pub fn cmd_pick_up(&mut self, _loot: Loot, _bag: Vec<Loot>, _dropped: &[Loot]) {}
+}
+
+impl MonoBehaviour for HookBehaviour {}
+
+impl TNetworkBehaviour for HookBehaviour {
+    fn new(_weak_game_object: RevelWeak<GameObject>, _metadata: &MetadataNetworkBehaviourWrapper) -> Self
+    where
+        Self: Sized,
+    {
+        Self::default()
+    }
+}
@@ -0,0 +1,104 @@
+use crate::commons::RevelArc;
+use crate::mirror::{CallbackProcessor, NetworkConnectionToClient, NetworkServer, Transport, TransportChannel, TransportManager};
+use crate::unity_engine::Time;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[derive(Default)]
+struct TestTransportState {
+    callbacks: Option<CallbackProcessor>,
+    sent: Vec<(u64, Vec<u8>, TransportChannel)>,
+    disconnected: Vec<u64>,
+}
+
+/// 测试用传输，记录发送的数据和断开的连接，由测试驱动连接和收包
+#[derive(Clone, Default)]
+pub(crate) struct TestTransport {
+    state: Rc<RefCell<TestTransportState>>,
+}
+
+impl TestTransport {
+    /// 设为当前线程的传输
+    pub(crate) fn install() -> Self {
+        let transport = Self::default();
+        TransportManager.active = RevelArc::new(Box::new(transport.clone()) as Box<dyn Transport>).into();
+        transport
+    }
+
+    /// 设为当前线程的传输并使用虚拟时钟启动服务器
+    pub(crate) fn listen() -> Self {
+        if !Time::is_started() {
+            Time::start_virtual();
+        }
+        let transport = Self::install();
+        NetworkServer.listen(16);
+        transport
+    }
+
+    pub(crate) fn connect(&self, conn_id: u64) -> RevelArc<Box<NetworkConnectionToClient>> {
+        let on_connected = self.callbacks().on_server_connected_with_address;
+        on_connected(conn_id, "127.0.0.1");
+        NetworkServer.connections[&conn_id].clone()
+    }
+
+    pub(crate) fn receive(&self, conn_id: u64, data: &[u8]) {
+        let on_data = self.callbacks().on_server_data_received;
+        on_data(conn_id, data, TransportChannel::Reliable);
+    }
+
+    /// 发送给连接的字节数
+    pub(crate) fn sent_bytes(&self, conn_id: u64) -> usize {
+        self.state
+            .borrow()
+            .sent
+            .iter()
+            .filter(|(id, _, _)| *id == conn_id)
+            .map(|(_, data, _)| data.len())
+            .sum()
+    }
+
+    pub(crate) fn disconnected(&self) -> Vec<u64> {
+        self.state.borrow().disconnected.clone()
+    }
+
+    fn callbacks(&self) -> CallbackProcessor {
+        let state = self.state.borrow();
+        let callbacks = state.callbacks.as_ref().expect("NetworkServer is not listening");
+        CallbackProcessor { ..*callbacks }
+    }
+}
+
+impl Transport for TestTransport {
+    fn init(&mut self, callback_processor: CallbackProcessor) {
+        self.state.borrow_mut().callbacks = Some(callback_processor);
+    }
+    fn available(&self) -> bool {
+        true
+    }
+    fn server_uri(&self) -> http::Uri {
+        http::Uri::from_static("test://localhost")
+    }
+    fn server_active(&self) -> bool {
+        true
+    }
+    fn server_start(&mut self, _: (&str, u16)) {}
+    fn server_send(&self, connection_id: u64, segment: &[u8], channel_id: TransportChannel) {
+        self.state
+            .borrow_mut()
+            .sent
+            .push((connection_id, segment.to_vec(), channel_id));
+    }
+    fn server_disconnect(&self, connection_id: u64) {
+        self.state.borrow_mut().disconnected.push(connection_id);
+    }
+    fn server_get_client_address(&self, _: u64) -> Option<String> {
+        Some("127.0.0.1".to_string())
+    }
+    fn server_stop(&self) {}
+    fn get_max_packet_size(&self, _: TransportChannel) -> usize {
+        1200
+    }
+    fn server_early_update(&self) {}
+    fn server_late_update(&self) {}
+    fn shutdown(&self) {}
+}
@@ -0,0 +1,56 @@
+use crate::metadata_settings::metadata_prefab::{MetadataPrefab, MetadataPrefabBuilder};
+use crate::metadata_settings::Metadata;
+use crate::mirror::messages::message::NetworkMessage;
+use crate::mirror::stable_hash::StableHash;
+use crate::mirror::{DataTypeDeserializer, DataTypeSerializer, NetworkReader, NetworkWriter, TestTransport};
+use crate::unity_engine::{LoadSceneMode, WorldManager};
+
+/// 写入后再读回，要求读取没有错误且正好读完，返回写入的字节和读回的值
+pub(crate) fn round_trip<T: DataTypeSerializer + DataTypeDeserializer>(value: &T) -> (Vec<u8>, T) {
+    let mut writer = NetworkWriter::new();
+    value.serialize(&mut writer);
+    let mut reader = NetworkReader::new(writer.to_vec());
+    let value = reader.try_read::<T>().unwrap();
+    assert_eq!(reader.remaining(), 0);
+    (writer.to_vec(), value)
+}
+
+/// 同 round_trip，消息先写类型名的 hash
+pub(crate) fn round_trip_message<M: NetworkMessage>(mut message: M) -> (Vec<u8>, M) {
+    let mut writer = NetworkWriter::new();
+    message.serialize(&mut writer);
+    let mut reader = NetworkReader::new(writer.to_vec());
+    assert_eq!(reader.read_blittable::<u16>(), M::get_full_name().hash16());
+    let message = M::deserialize(&mut reader);
+    assert!(!reader.has_error());
+    assert_eq!(reader.remaining(), 0);
+    (writer.to_vec(), message)
+}
+
+/// 当前线程只使用给定的场景，第一个场景作为活动场景，然后用 TestTransport 启动服务器
+pub(crate) fn listen_in_scenes(scenes: Vec<(&str, Vec<MetadataPrefab>)>) -> TestTransport {
+    let active_scene = scenes.first().map(|(scene_path, _)| scene_path.to_string());
+    Metadata::load_scenes(scenes);
+    if let Some(active_scene) = active_scene {
+        WorldManager::load_scene(&active_scene, LoadSceneMode::Single);
+    }
+    TestTransport::listen()
+}
+
+/// 带 NetworkIdentity 的预制体，每个 behaviour (NetworkBehaviour 子类的全名) 一条组件链
+/// behaviours 为空时只有一个 Mirror.NetworkBehaviour
+pub(crate) fn network_prefab(name: &str, behaviours: &[&str]) -> MetadataPrefabBuilder {
+    let base = || {
+        (
+            "Mirror.NetworkBehaviour",
+            serde_json::json!({ "syncDirection": 0, "syncMode": 0, "syncInterval": 0.0 }),
+        )
+    };
+    let builder = MetadataPrefab::builder(name);
+    if behaviours.is_empty() {
+        return builder.network_behaviour(vec![base()]);
+    }
+    behaviours.iter().fold(builder, |builder, behaviour| {
+        builder.network_behaviour(vec![base(), (behaviour, serde_json::json!({}))])
+    })
+}
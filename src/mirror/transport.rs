@@ -1,5 +1,5 @@
 use crate::commons::RevelArc;
-use once_cell::sync::Lazy;
+use crate::commons::thread_static;
 use std::fmt::{Display, Formatter};
 use std::hash::{Hash, Hasher};
 use std::ops::{Deref, DerefMut};
@@ -54,7 +54,9 @@ impl Display for TransportError {
     }
 }
 
-static mut TRANSPORT_STATIC: Lazy<TransportStatic> = Lazy::new(|| TransportStatic { active: TransportStaticAction(None) });
+thread_static! {
+    static mut TRANSPORT_STATIC: TransportStatic = TransportStatic { active: TransportStaticAction(None) };
+}
 
 pub struct TransportStaticAction(Option<RevelArc<Box<dyn Transport>>>);
 
@@ -88,6 +90,12 @@ pub struct TransportStatic {
     pub(crate) active: TransportStaticAction,
 }
 
+impl TransportStatic {
+    pub fn is_initialized(&self) -> bool {
+        self.active.0.is_some()
+    }
+}
+
 pub struct TransportManager;
 
 impl Deref for TransportManager {
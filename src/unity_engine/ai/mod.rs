@@ -0,0 +1,5 @@
+mod nav_mesh;
+pub use nav_mesh::*;
+
+mod nav_mesh_agent;
+pub use nav_mesh_agent::*;
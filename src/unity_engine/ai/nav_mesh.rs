@@ -0,0 +1,579 @@
+use crate::metadata_settings::metadata_nav_mesh::MetadataNavMesh;
+use crate::metadata_settings::Metadata;
+use crate::unity_engine::WorldManager;
+use nalgebra::Vector3;
+use crate::commons::thread_static;
+use ordered_float::OrderedFloat;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::sync::Arc;
+
+thread_static! {
+    // 场景路径 -> 构建好的 NavMesh（None 表示该场景没有 NavMesh）
+    static mut NAV_MESH_DATA: HashMap<String, Option<Arc<NavMeshData>>> = HashMap::new();
+}
+
+// 顶点焊接精度（1mm）
+const WELD_PRECISION: f32 = 1000.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NavMeshPathStatus {
+    PathComplete,
+    PathPartial,
+    PathInvalid,
+}
+
+#[derive(Debug, Clone)]
+pub struct NavMeshPath {
+    pub corners: Vec<Vector3<f32>>,
+    pub status: NavMeshPathStatus,
+}
+
+impl Default for NavMeshPath {
+    fn default() -> Self {
+        Self {
+            corners: vec![],
+            status: NavMeshPathStatus::PathInvalid,
+        }
+    }
+}
+
+pub struct NavMesh;
+
+impl NavMesh {
+    pub const ALL_AREAS: i32 = -1;
+
+    /// 在当前激活场景的 NavMesh 上计算路径
+    pub fn calculate_path(
+        source: Vector3<f32>,
+        target: Vector3<f32>,
+        area_mask: i32,
+    ) -> NavMeshPath {
+        Self::calculate_path_in_instance(0, source, target, area_mask)
+    }
+
+    /// 在场景实例的 NavMesh 上计算路径，同一场景的所有实例共用导出的 NavMesh
+    /// 句柄为 0 (DontDestroyOnLoad 中的对象) 时使用活动场景
+    pub fn calculate_path_in_instance(
+        scene_handle: u32,
+        source: Vector3<f32>,
+        target: Vector3<f32>,
+        area_mask: i32,
+    ) -> NavMeshPath {
+        match Self::scene_path(scene_handle) {
+            None => NavMeshPath::default(),
+            Some(scene_path) => Self::calculate_path_in_scene(&scene_path, source, target, area_mask),
+        }
+    }
+
+    pub fn calculate_path_in_scene(
+        scene_path: &str,
+        source: Vector3<f32>,
+        target: Vector3<f32>,
+        area_mask: i32,
+    ) -> NavMeshPath {
+        match Self::get_nav_mesh_data(scene_path) {
+            None => NavMeshPath::default(),
+            Some(nav_mesh_data) => nav_mesh_data.calculate_path(source, target, area_mask),
+        }
+    }
+
+    /// 在 max_distance 范围内查找 NavMesh 上离 source 最近的点
+    pub fn sample_position(
+        source: Vector3<f32>,
+        max_distance: f32,
+        area_mask: i32,
+    ) -> Option<Vector3<f32>> {
+        Self::sample_position_in_instance(0, source, max_distance, area_mask)
+    }
+
+    /// 在场景实例的 NavMesh 上查找 max_distance 范围内离 source 最近的点
+    pub fn sample_position_in_instance(
+        scene_handle: u32,
+        source: Vector3<f32>,
+        max_distance: f32,
+        area_mask: i32,
+    ) -> Option<Vector3<f32>> {
+        let nav_mesh_data = Self::get_nav_mesh_data(&Self::scene_path(scene_handle)?)?;
+        let (_, point) = nav_mesh_data.find_nearest(&source, area_mask)?;
+        if (point - source).norm() <= max_distance {
+            return Some(point);
+        }
+        None
+    }
+
+    fn scene_path(scene_handle: u32) -> Option<String> {
+        let world = match scene_handle {
+            0 => WorldManager::active_world(),
+            _ => WorldManager::get_scene_by_handle(scene_handle),
+        };
+        Some(world.get()?.get_scene_path())
+    }
+
+    pub fn get_nav_mesh_data(scene_path: &str) -> Option<Arc<NavMeshData>> {
+        #[allow(static_mut_refs)]
+        unsafe {
+            if let Some(nav_mesh_data) = NAV_MESH_DATA.get(scene_path) {
+                return nav_mesh_data.clone();
+            }
+            let nav_mesh_data = Metadata::get_nav_mesh(scene_path)
+                .map(|metadata| Arc::new(NavMeshData::new(&metadata)));
+            NAV_MESH_DATA.insert(scene_path.to_string(), nav_mesh_data.clone());
+            nav_mesh_data
+        }
+    }
+
+    // 重新加载 NavMesh 元数据后清空缓存
+    pub(crate) fn clear_nav_mesh_data() {
+        #[allow(static_mut_refs)]
+        unsafe {
+            NAV_MESH_DATA.clear();
+        }
+    }
+}
+
+pub struct NavMeshData {
+    vertices: Vec<Vector3<f32>>,
+    triangles: Vec<[usize; 3]>,
+    areas: Vec<i32>,
+    centers: Vec<Vector3<f32>>,
+    // 相邻三角形及共享边 (triangle, vertex_a, vertex_b)
+    neighbours: Vec<Vec<(usize, usize, usize)>>,
+}
+
+impl NavMeshData {
+    pub fn new(metadata: &MetadataNavMesh) -> Self {
+        // Unity 导出的三角化数据顶点并不共享，需要先按位置焊接
+        let mut welded = HashMap::<(i64, i64, i64), usize>::new();
+        let mut vertices = vec![];
+        let mut remap = vec![];
+        for vertex in metadata.vertices.iter() {
+            let key = (
+                (vertex[0] * WELD_PRECISION).round() as i64,
+                (vertex[1] * WELD_PRECISION).round() as i64,
+                (vertex[2] * WELD_PRECISION).round() as i64,
+            );
+            let index = *welded.entry(key).or_insert_with(|| {
+                vertices.push(Vector3::new(vertex[0], vertex[1], vertex[2]));
+                vertices.len() - 1
+            });
+            remap.push(index);
+        }
+
+        let mut triangles = vec![];
+        let mut areas = vec![];
+        let mut invalid = 0;
+        for (i, indices) in metadata.indices.chunks_exact(3).enumerate() {
+            // 顶点索引越界的三角形跳过
+            let (Some(&a), Some(&b), Some(&c)) = (
+                remap.get(indices[0] as usize),
+                remap.get(indices[1] as usize),
+                remap.get(indices[2] as usize),
+            ) else {
+                invalid += 1;
+                continue;
+            };
+            let triangle = [a, b, c];
+            if triangle[0] == triangle[1] || triangle[1] == triangle[2] || triangle[0] == triangle[2]
+            {
+                continue;
+            }
+            triangles.push(triangle);
+            areas.push(metadata.areas.get(i).cloned().unwrap_or(0));
+        }
+        if invalid > 0 {
+            log::warn!(
+                "NavMesh: skipped {} triangles with vertex indices out of range ({} vertices).",
+                invalid,
+                metadata.vertices.len()
+            );
+        }
+
+        let centers = triangles
+            .iter()
+            .map(|[a, b, c]| (vertices[*a] + vertices[*b] + vertices[*c]) / 3.0)
+            .collect::<Vec<_>>();
+
+        let mut edges = HashMap::<(usize, usize), Vec<usize>>::new();
+        for (index, triangle) in triangles.iter().enumerate() {
+            for i in 0..3 {
+                let (a, b) = (triangle[i], triangle[(i + 1) % 3]);
+                edges.entry((a.min(b), a.max(b))).or_default().push(index);
+            }
+        }
+        let mut neighbours = vec![vec![]; triangles.len()];
+        for ((a, b), shared) in edges.iter() {
+            for &from in shared.iter() {
+                for &to in shared.iter() {
+                    if from != to {
+                        neighbours[from].push((to, *a, *b));
+                    }
+                }
+            }
+        }
+
+        Self {
+            vertices,
+            triangles,
+            areas,
+            centers,
+            neighbours,
+        }
+    }
+
+    fn walkable(&self, triangle: usize, area_mask: i32) -> bool {
+        let area = self.areas[triangle];
+        (0..32).contains(&area) && area_mask & (1 << area) != 0
+    }
+
+    /// 查找离 point 最近的可行走三角形及其上的最近点
+    pub fn find_nearest(&self, point: &Vector3<f32>, area_mask: i32) -> Option<(usize, Vector3<f32>)> {
+        let mut nearest: Option<(usize, Vector3<f32>, f32)> = None;
+        for (index, [a, b, c]) in self.triangles.iter().enumerate() {
+            if !self.walkable(index, area_mask) {
+                continue;
+            }
+            let closest = closest_point_on_triangle(
+                point,
+                &self.vertices[*a],
+                &self.vertices[*b],
+                &self.vertices[*c],
+            );
+            let distance = (closest - point).norm_squared();
+            if nearest.is_none_or(|(_, _, nearest_distance)| distance < nearest_distance) {
+                nearest = Some((index, closest, distance));
+            }
+        }
+        nearest.map(|(index, closest, _)| (index, closest))
+    }
+
+    pub fn calculate_path(
+        &self,
+        source: Vector3<f32>,
+        target: Vector3<f32>,
+        area_mask: i32,
+    ) -> NavMeshPath {
+        let (Some((start, start_point)), Some((end, end_point))) = (
+            self.find_nearest(&source, area_mask),
+            self.find_nearest(&target, area_mask),
+        ) else {
+            return NavMeshPath::default();
+        };
+
+        if start == end {
+            return NavMeshPath {
+                corners: vec![start_point, end_point],
+                status: NavMeshPathStatus::PathComplete,
+            };
+        }
+
+        // A* 搜索三角形通道
+        let mut costs = vec![f32::INFINITY; self.triangles.len()];
+        let mut parents = vec![None; self.triangles.len()];
+        let mut open = BinaryHeap::new();
+        costs[start] = 0.0;
+        open.push(Reverse((OrderedFloat((self.centers[start] - end_point).norm()), start)));
+        // 无法到达终点时，退而求其次走到离终点最近的三角形
+        let mut best = (start, (self.centers[start] - end_point).norm());
+        let mut reached = false;
+
+        while let Some(Reverse((_, current))) = open.pop() {
+            if current == end {
+                reached = true;
+                break;
+            }
+            for &(neighbour, _, _) in self.neighbours[current].iter() {
+                if !self.walkable(neighbour, area_mask) {
+                    continue;
+                }
+                let cost =
+                    costs[current] + (self.centers[neighbour] - self.centers[current]).norm();
+                if cost < costs[neighbour] {
+                    costs[neighbour] = cost;
+                    parents[neighbour] = Some(current);
+                    let heuristic = (self.centers[neighbour] - end_point).norm();
+                    if heuristic < best.1 {
+                        best = (neighbour, heuristic);
+                    }
+                    open.push(Reverse((OrderedFloat(cost + heuristic), neighbour)));
+                }
+            }
+        }
+
+        let (goal, goal_point, status) = if reached {
+            (end, end_point, NavMeshPathStatus::PathComplete)
+        } else {
+            let [a, b, c] = self.triangles[best.0];
+            let point = closest_point_on_triangle(
+                &end_point,
+                &self.vertices[a],
+                &self.vertices[b],
+                &self.vertices[c],
+            );
+            (best.0, point, NavMeshPathStatus::PathPartial)
+        };
+
+        let mut corridor = vec![goal];
+        while let Some(parent) = parents[*corridor.last().unwrap()] {
+            corridor.push(parent);
+        }
+        corridor.reverse();
+
+        // 构建通道的 portal，左右以前进方向为准
+        let mut portals = vec![(start_point, start_point)];
+        for window in corridor.windows(2) {
+            let (from, to) = (window[0], window[1]);
+            if let Some(&(_, a, b)) = self.neighbours[from].iter().find(|(t, _, _)| *t == to) {
+                let (va, vb) = (self.vertices[a], self.vertices[b]);
+                if tri_area2(&self.centers[from], &va, &vb) > 0.0 {
+                    portals.push((va, vb));
+                } else {
+                    portals.push((vb, va));
+                }
+            }
+        }
+        portals.push((goal_point, goal_point));
+
+        NavMeshPath {
+            corners: string_pull(&portals),
+            status,
+        }
+    }
+}
+
+// XZ 平面上的有向面积（两倍）
+fn tri_area2(a: &Vector3<f32>, b: &Vector3<f32>, c: &Vector3<f32>) -> f32 {
+    let (ax, az) = (b.x - a.x, b.z - a.z);
+    let (bx, bz) = (c.x - a.x, c.z - a.z);
+    bx * az - ax * bz
+}
+
+fn v_equal(a: &Vector3<f32>, b: &Vector3<f32>) -> bool {
+    (a - b).norm_squared() < 1e-6
+}
+
+/// Simple Stupid Funnel Algorithm
+fn string_pull(portals: &[(Vector3<f32>, Vector3<f32>)]) -> Vec<Vector3<f32>> {
+    let mut corners = vec![portals[0].0];
+    let (mut apex, mut left, mut right) = (portals[0].0, portals[0].0, portals[0].1);
+    let (mut apex_index, mut left_index, mut right_index) = (0, 0, 0);
+
+    let mut i = 1;
+    while i < portals.len() {
+        let (new_left, new_right) = portals[i];
+
+        // 收紧右边界
+        if tri_area2(&apex, &right, &new_right) <= 0.0 {
+            if v_equal(&apex, &right) || tri_area2(&apex, &left, &new_right) > 0.0 {
+                right = new_right;
+                right_index = i;
+            } else {
+                // 右边界越过左边界，左边界点成为新的拐点
+                apex = left;
+                apex_index = left_index;
+                push_corner(&mut corners, apex);
+                (left, right) = (apex, apex);
+                (left_index, right_index) = (apex_index, apex_index);
+                i = apex_index + 1;
+                continue;
+            }
+        }
+
+        // 收紧左边界
+        if tri_area2(&apex, &left, &new_left) >= 0.0 {
+            if v_equal(&apex, &left) || tri_area2(&apex, &right, &new_left) < 0.0 {
+                left = new_left;
+                left_index = i;
+            } else {
+                apex = right;
+                apex_index = right_index;
+                push_corner(&mut corners, apex);
+                (left, right) = (apex, apex);
+                (left_index, right_index) = (apex_index, apex_index);
+                i = apex_index + 1;
+                continue;
+            }
+        }
+
+        i += 1;
+    }
+
+    push_corner(&mut corners, portals[portals.len() - 1].0);
+    corners
+}
+
+// 相邻 portal 共享顶点时同一拐点可能被重复添加
+fn push_corner(corners: &mut Vec<Vector3<f32>>, corner: Vector3<f32>) {
+    if !corners.last().is_some_and(|last| v_equal(last, &corner)) {
+        corners.push(corner);
+    }
+}
+
+/// 三角形上离 p 最近的点（Real-Time Collision Detection 5.1.5）
+fn closest_point_on_triangle(
+    p: &Vector3<f32>,
+    a: &Vector3<f32>,
+    b: &Vector3<f32>,
+    c: &Vector3<f32>,
+) -> Vector3<f32> {
+    let ab = b - a;
+    let ac = c - a;
+    let ap = p - a;
+    let d1 = ab.dot(&ap);
+    let d2 = ac.dot(&ap);
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return *a;
+    }
+
+    let bp = p - b;
+    let d3 = ab.dot(&bp);
+    let d4 = ac.dot(&bp);
+    if d3 >= 0.0 && d4 <= d3 {
+        return *b;
+    }
+
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return a + ab * (d1 / (d1 - d3));
+    }
+
+    let cp = p - c;
+    let d5 = ab.dot(&cp);
+    let d6 = ac.dot(&cp);
+    if d6 >= 0.0 && d5 <= d6 {
+        return *c;
+    }
+
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return a + ac * (d2 / (d2 - d6));
+    }
+
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+        return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+
+    let denom = 1.0 / (va + vb + vc);
+    a + ab * (vb * denom) + ac * (vc * denom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 三个单位方格组成的 L 形通道
+    fn l_shape() -> NavMeshData {
+        let vertices = [
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [2.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0],
+            [1.0, 0.0, 1.0],
+            [2.0, 0.0, 1.0],
+            [1.0, 0.0, 2.0],
+            [2.0, 0.0, 2.0],
+        ];
+        NavMeshData::new(&MetadataNavMesh {
+            vertices: vertices.to_vec(),
+            indices: vec![0, 1, 4, 0, 4, 3, 1, 2, 5, 1, 5, 4, 4, 5, 7, 4, 7, 6],
+            areas: vec![0, 0, 0, 0, 1, 1],
+        })
+    }
+
+    #[test]
+    fn test_calculate_path_in_instance() {
+        use crate::metadata_settings::metadata_prefab::MetadataPrefab;
+        use crate::unity_engine::ai::nav_mesh_agent::NavMeshAgent;
+        use crate::unity_engine::{GameObject, LoadSceneMode, WorldManager};
+
+        Metadata::load_scenes(vec![("Assets/Scenes/Lobby.unity", vec![]), ("Assets/Scenes/Arena.unity", vec![])]);
+        WorldManager::load_scene("Assets/Scenes/Lobby.unity", LoadSceneMode::Single);
+        let arena = WorldManager::load_scene_instance("Assets/Scenes/Arena.unity").get().unwrap().handle();
+        #[allow(static_mut_refs)]
+        unsafe {
+            NAV_MESH_DATA.insert("Assets/Scenes/Arena.unity".to_string(), Some(Arc::new(l_shape())));
+            NAV_MESH_DATA.insert("Assets/Scenes/Lobby.unity".to_string(), None);
+        }
+
+        let source = Vector3::new(0.5, 0.0, 0.5);
+        let target = Vector3::new(1.5, 0.0, 1.5);
+        let path = NavMesh::calculate_path_in_instance(arena, source, target, NavMesh::ALL_AREAS);
+        assert_eq!(path.status, NavMeshPathStatus::PathComplete);
+        assert!(NavMesh::sample_position_in_instance(arena, source, 0.1, NavMesh::ALL_AREAS).is_some());
+
+        // 活动场景没有 NavMesh，未加载的实例同样无法寻路
+        let path = NavMesh::calculate_path(source, target, NavMesh::ALL_AREAS);
+        assert_eq!(path.status, NavMeshPathStatus::PathInvalid);
+
+        // 代理在自己所在的场景实例中寻路
+        let agent = MetadataPrefab::builder("Agent")
+            .local_position([0.5, 0.0, 0.5])
+            .component(vec![(
+                "UnityEngine.AI.NavMeshAgent",
+                serde_json::json!({
+                    "instanceId": 0,
+                    "speed": 3.5,
+                    "angularSpeed": 120.0,
+                    "acceleration": 8.0,
+                    "stoppingDistance": 0.0,
+                    "autoBraking": true,
+                    "areaMask": -1
+                }),
+            )])
+            .build()
+            .unwrap();
+        let in_arena = GameObject::instantiate_in_scene(&agent, arena);
+        let in_lobby = GameObject::instantiate(&agent);
+        let mut agent = in_arena.try_get_component2::<NavMeshAgent>().unwrap();
+        assert!(agent.set_destination(target));
+        let mut agent = in_lobby.try_get_component2::<NavMeshAgent>().unwrap();
+        assert!(!agent.set_destination(target));
+        WorldManager::unload_scene_by_handle(arena);
+        let path = NavMesh::calculate_path_in_instance(arena, source, target, NavMesh::ALL_AREAS);
+        assert_eq!(path.status, NavMeshPathStatus::PathInvalid);
+    }
+
+    #[test]
+    fn test_out_of_range_indices() {
+        // 第二个三角形引用了不存在的顶点
+        let nav_mesh = NavMeshData::new(&MetadataNavMesh {
+            vertices: vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]],
+            indices: vec![0, 1, 2, 0, 2, 7],
+            areas: vec![0, 0],
+        });
+        assert_eq!(nav_mesh.triangles.len(), 1);
+        assert_eq!(nav_mesh.areas.len(), 1);
+    }
+
+    #[test]
+    fn test_calculate_path() {
+        let nav_mesh = l_shape();
+
+        let path = nav_mesh.calculate_path(
+            Vector3::new(0.9, 0.0, 0.1),
+            Vector3::new(0.1, 0.0, 0.9),
+            NavMesh::ALL_AREAS,
+        );
+        assert_eq!(path.status, NavMeshPathStatus::PathComplete);
+        assert_eq!(path.corners.len(), 2);
+
+        let path = nav_mesh.calculate_path(
+            Vector3::new(0.2, 0.0, 0.5),
+            Vector3::new(1.5, 0.0, 1.8),
+            NavMesh::ALL_AREAS,
+        );
+        assert_eq!(path.status, NavMeshPathStatus::PathComplete);
+        assert_eq!(path.corners.len(), 3);
+        assert!(v_equal(&path.corners[1], &Vector3::new(1.0, 0.0, 1.0)));
+
+        // 屏蔽 area 1 后终点被投影到可行走区域的边缘
+        let path = nav_mesh.calculate_path(
+            Vector3::new(0.2, 0.0, 0.5),
+            Vector3::new(1.5, 0.0, 1.8),
+            1,
+        );
+        assert_ne!(path.status, NavMeshPathStatus::PathInvalid);
+        assert!(path.corners.last().unwrap().z <= 1.0);
+    }
+}
@@ -0,0 +1,249 @@
+use crate::commons::RevelArc;
+use crate::commons::RevelWeak;
+use crate::macro_namespace::*;
+use crate::metadata_settings::metadata_nav_mesh::{
+    MetadataNavMeshAgent, MetadataNavMeshAgentWrapper,
+};
+use crate::unity_engine::ai::nav_mesh::{NavMesh, NavMeshPath, NavMeshPathStatus};
+use crate::unity_engine::mono_behaviour::MonoBehaviour;
+use crate::unity_engine::mono_behaviour_factory::MonoBehaviourFactory;
+use crate::unity_engine::{GameObject, Time};
+use nalgebra::{UnitQuaternion, Vector3};
+use std::any::Any;
+
+// 判定到达拐点的距离
+const CORNER_THRESHOLD: f32 = 0.01;
+
+#[ctor::ctor]
+fn static_init() {
+    MonoBehaviourFactory::register::<NavMeshAgent>(|weak_game_object, metadata| {
+        let wrapper = metadata
+            .as_any()
+            .downcast_ref::<MetadataNavMeshAgentWrapper>()
+            .unwrap();
+
+        let agent = NavMeshAgent::instance(weak_game_object, wrapper.get::<MetadataNavMeshAgent>());
+        let type_id = agent.type_id();
+
+        let arc_agent = RevelArc::new(Box::new(agent) as Box<dyn MonoBehaviour>);
+
+        vec![(arc_agent, type_id)]
+    });
+}
+
+#[namespace(prefix = "UnityEngine.AI")]
+pub struct NavMeshAgent {
+    game_object: RevelWeak<GameObject>,
+    pub speed: f32,
+    pub angular_speed: f32,
+    pub acceleration: f32,
+    pub stopping_distance: f32,
+    pub auto_braking: bool,
+    pub area_mask: i32,
+    pub is_stopped: bool,
+    velocity: Vector3<f32>,
+    destination: Option<Vector3<f32>>,
+    path: NavMeshPath,
+    corner_index: usize,
+}
+
+impl MonoBehaviour for NavMeshAgent {
+    fn update(&mut self) {
+        let delta_time = Time::delta_time();
+        if delta_time > 0.0 {
+            self.move_along_path(delta_time);
+        }
+    }
+}
+
+impl NavMeshAgent {
+    fn instance(weak_game_object: RevelWeak<GameObject>, settings: &MetadataNavMeshAgent) -> Self {
+        Self {
+            game_object: weak_game_object,
+            speed: settings.speed,
+            angular_speed: settings.angular_speed,
+            acceleration: settings.acceleration,
+            stopping_distance: settings.stopping_distance,
+            auto_braking: settings.auto_braking,
+            area_mask: settings.area_mask,
+            is_stopped: false,
+            velocity: Vector3::zeros(),
+            destination: None,
+            path: NavMeshPath::default(),
+            corner_index: 0,
+        }
+    }
+
+    /// 计算到 target 的路径并开始移动，无法寻路时返回 false
+    pub fn set_destination(&mut self, target: Vector3<f32>) -> bool {
+        let Some(position) = self.position() else {
+            return false;
+        };
+        // 在代理所在的场景 (实例) 中寻路
+        let scene_handle = self.game_object.get().map_or(0, |game_object| game_object.scene_handle());
+        let path = NavMesh::calculate_path_in_instance(scene_handle, position, target, self.area_mask);
+        if path.status == NavMeshPathStatus::PathInvalid {
+            return false;
+        }
+        self.set_path(path);
+        self.destination = Some(target);
+        true
+    }
+
+    pub fn set_path(&mut self, path: NavMeshPath) -> bool {
+        if path.status == NavMeshPathStatus::PathInvalid || path.corners.is_empty() {
+            return false;
+        }
+        self.destination = path.corners.last().cloned();
+        self.path = path;
+        self.corner_index = 1.min(self.path.corners.len() - 1);
+        true
+    }
+
+    pub fn reset_path(&mut self) {
+        self.path = NavMeshPath::default();
+        self.corner_index = 0;
+        self.destination = None;
+    }
+
+    /// 瞬移到 position，会清除当前路径
+    pub fn warp(&mut self, position: Vector3<f32>) {
+        self.reset_path();
+        self.velocity = Vector3::zeros();
+        self.apply_position(position);
+    }
+
+    pub fn has_path(&self) -> bool {
+        self.path.status != NavMeshPathStatus::PathInvalid && !self.path.corners.is_empty()
+    }
+
+    pub fn path(&self) -> &NavMeshPath {
+        &self.path
+    }
+
+    pub fn destination(&self) -> Option<Vector3<f32>> {
+        self.destination
+    }
+
+    pub fn velocity(&self) -> Vector3<f32> {
+        self.velocity
+    }
+
+    pub fn remaining_distance(&self) -> f32 {
+        match self.position() {
+            None => 0.0,
+            Some(position) => self.remaining_distance_from(&position),
+        }
+    }
+
+    fn remaining_distance_from(&self, position: &Vector3<f32>) -> f32 {
+        if !self.has_path() {
+            return 0.0;
+        }
+        let corners = &self.path.corners[self.corner_index..];
+        let mut distance = (corners[0] - position).norm();
+        for window in corners.windows(2) {
+            distance += (window[1] - window[0]).norm();
+        }
+        distance
+    }
+
+    fn position(&self) -> Option<Vector3<f32>> {
+        Some(self.game_object.get()?.transform.position())
+    }
+
+    fn apply_position(&mut self, position: Vector3<f32>) {
+        if let Some(game_object) = self.game_object.get() {
+            game_object.transform.set_position(position);
+        }
+    }
+
+    fn apply_rotation(&mut self, delta_time: f32) {
+        let direction = Vector3::new(self.velocity.x, 0.0, self.velocity.z);
+        if direction.norm_squared() < 1e-6 {
+            return;
+        }
+        if let Some(game_object) = self.game_object.get() {
+            let transform = &mut game_object.transform;
+            let current = UnitQuaternion::from_quaternion(transform.rotation());
+            let target = UnitQuaternion::face_towards(&direction, &Vector3::y());
+            let angle = current.angle_to(&target);
+            let max_angle = self.angular_speed.to_radians() * delta_time;
+            let rotation = if angle <= max_angle {
+                target
+            } else {
+                current.slerp(&target, max_angle / angle)
+            };
+            transform.set_rotation(*rotation.quaternion());
+        }
+    }
+
+    fn move_along_path(&mut self, delta_time: f32) {
+        let Some(mut position) = self.position() else {
+            return;
+        };
+
+        if self.is_stopped || !self.has_path() {
+            // 没有路径时按加速度减速
+            self.velocity =
+                move_towards(&self.velocity, &Vector3::zeros(), self.acceleration * delta_time);
+            if self.velocity.norm_squared() > 0.0 {
+                self.apply_position(position + self.velocity * delta_time);
+            }
+            return;
+        }
+
+        let last_index = self.path.corners.len() - 1;
+        while self.corner_index < last_index
+            && (self.path.corners[self.corner_index] - position).norm() < CORNER_THRESHOLD
+        {
+            self.corner_index += 1;
+        }
+
+        let remaining_distance = self.remaining_distance_from(&position);
+        if remaining_distance <= self.stopping_distance.max(CORNER_THRESHOLD) {
+            self.velocity = Vector3::zeros();
+            self.reset_path();
+            return;
+        }
+
+        let corner = self.path.corners[self.corner_index];
+        let offset = corner - position;
+        let direction = offset.normalize();
+
+        let mut desired_speed = self.speed;
+        if self.auto_braking && self.acceleration > 0.0 {
+            let braking_distance = (remaining_distance - self.stopping_distance).max(0.0);
+            desired_speed = desired_speed.min((2.0 * self.acceleration * braking_distance).sqrt());
+        }
+
+        self.velocity = move_towards(
+            &self.velocity,
+            &(direction * desired_speed),
+            self.acceleration * delta_time,
+        );
+
+        // 不越过当前拐点
+        let step = self.velocity * delta_time;
+        if step.norm() >= offset.norm() {
+            position = corner;
+            if self.corner_index < last_index {
+                self.corner_index += 1;
+            }
+        } else {
+            position += step;
+        }
+
+        self.apply_position(position);
+        self.apply_rotation(delta_time);
+    }
+}
+
+fn move_towards(current: &Vector3<f32>, target: &Vector3<f32>, max_delta: f32) -> Vector3<f32> {
+    let offset = target - current;
+    let distance = offset.norm();
+    if distance <= max_delta || distance == 0.0 {
+        return *target;
+    }
+    current + offset / distance * max_delta
+}
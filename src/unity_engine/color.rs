@@ -0,0 +1,56 @@
+use crate::macro_namespace::*;
+use crate::macro_network_serialize::*;
+
+/// UnityEngine.Color，分量范围 0..1
+#[namespace(prefix = "UnityEngine")]
+#[derive(Debug, Clone, Copy, PartialEq, Default, NetworkSerialize, NetworkDeserialize)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl Color {
+    pub const WHITE: Color = Color::new(1.0, 1.0, 1.0, 1.0);
+    pub const BLACK: Color = Color::new(0.0, 0.0, 0.0, 1.0);
+    pub const CLEAR: Color = Color::new(0.0, 0.0, 0.0, 0.0);
+
+    pub const fn new(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self { r, g, b, a }
+    }
+}
+
+impl From<Color32> for Color {
+    fn from(value: Color32) -> Self {
+        Self::new(
+            value.r as f32 / 255.0,
+            value.g as f32 / 255.0,
+            value.b as f32 / 255.0,
+            value.a as f32 / 255.0,
+        )
+    }
+}
+
+/// UnityEngine.Color32
+#[namespace(prefix = "UnityEngine")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, NetworkSerialize, NetworkDeserialize)]
+pub struct Color32 {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Color32 {
+    pub const fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+}
+
+impl From<Color> for Color32 {
+    fn from(value: Color) -> Self {
+        let to_byte = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+        Self::new(to_byte(value.r), to_byte(value.g), to_byte(value.b), to_byte(value.a))
+    }
+}
@@ -0,0 +1,421 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll, Waker};
+
+// 调度器的当前时间 (秒) 和所处阶段，由 PlayerLooper 在每次更新前设置
+thread_local! {
+    static SCHEDULER_TIME: Cell<f64> = const { Cell::new(0.0) };
+    static SCHEDULER_PHASE: Cell<SchedulerPhase> = const { Cell::new(SchedulerPhase::Update) };
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum SchedulerPhase {
+    FixedUpdate,
+    Update,
+}
+
+pub(crate) fn set_scheduler_time(time: f64) {
+    SCHEDULER_TIME.set(time);
+}
+
+pub(crate) fn scheduler_time() -> f64 {
+    SCHEDULER_TIME.get()
+}
+
+fn set_scheduler_phase(phase: SchedulerPhase) {
+    SCHEDULER_PHASE.set(phase);
+}
+
+fn scheduler_phase() -> SchedulerPhase {
+    SCHEDULER_PHASE.get()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct InvokeHandle(u64);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CoroutineHandle(u64);
+
+struct Invoke {
+    id: u64,
+    next_time: f64,
+    interval: Option<f64>,
+    callback: Box<dyn FnMut()>,
+}
+
+struct Coroutine {
+    id: u64,
+    future: Pin<Box<dyn Future<Output = ()>>>,
+}
+
+/// 每个 GameObject 上的 Invoke / 协程调度器。
+/// 回调和协程执行期间可能再次访问调度器，甚至销毁调度器所在的 GameObject，
+/// 所以执行前先把到期的任务取出，执行时只持有状态的共享引用，不再访问调度器本身
+#[derive(Default)]
+pub struct Scheduler {
+    state: Rc<RefCell<SchedulerState>>,
+}
+
+#[derive(Default)]
+struct SchedulerState {
+    next_id: u64,
+    invokes: Vec<Invoke>,
+    coroutines: Vec<Coroutine>,
+    // 执行中被取消的任务
+    cancelled: HashSet<u64>,
+    // 全部清空时递增，丢弃执行中的任务
+    invoke_generation: u64,
+    coroutine_generation: u64,
+}
+
+impl SchedulerState {
+    fn next_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+}
+
+impl Scheduler {
+    pub fn invoke(&mut self, delay: f32, callback: impl FnMut() + 'static) -> InvokeHandle {
+        let mut state = self.state.borrow_mut();
+        let id = state.next_id();
+        state.invokes.push(Invoke {
+            id,
+            next_time: scheduler_time() + delay.max(0.0) as f64,
+            interval: None,
+            callback: Box::new(callback),
+        });
+        InvokeHandle(id)
+    }
+
+    pub fn invoke_repeating(
+        &mut self,
+        delay: f32,
+        interval: f32,
+        callback: impl FnMut() + 'static,
+    ) -> InvokeHandle {
+        if interval <= 0.0 {
+            log::warn!("InvokeRepeating: interval must be greater than 0, got {}.", interval);
+        }
+        let mut state = self.state.borrow_mut();
+        let id = state.next_id();
+        state.invokes.push(Invoke {
+            id,
+            next_time: scheduler_time() + delay.max(0.0) as f64,
+            interval: Some(interval.max(f32::EPSILON) as f64),
+            callback: Box::new(callback),
+        });
+        InvokeHandle(id)
+    }
+
+    pub fn is_invoking(&self, handle: InvokeHandle) -> bool {
+        self.state.borrow().invokes.iter().any(|invoke| invoke.id == handle.0)
+    }
+
+    pub fn cancel_invoke(&mut self, handle: InvokeHandle) {
+        let mut state = self.state.borrow_mut();
+        state.invokes.retain(|invoke| invoke.id != handle.0);
+        state.cancelled.insert(handle.0);
+    }
+
+    pub fn cancel_all_invokes(&mut self) {
+        let state = &mut *self.state.borrow_mut();
+        for invoke in state.invokes.iter() {
+            state.cancelled.insert(invoke.id);
+        }
+        state.invokes.clear();
+        state.invoke_generation += 1;
+    }
+
+    /// 启动协程，立即执行到第一个等待点
+    pub fn start_coroutine(&mut self, future: impl Future<Output = ()> + 'static) -> CoroutineHandle {
+        let id = self.state.borrow_mut().next_id();
+        let mut coroutine = Coroutine {
+            id,
+            future: Box::pin(future),
+        };
+        // 首次执行期间不持有借用，协程中可以再次访问调度器
+        if !Self::poll_coroutine(&mut coroutine) {
+            self.state.borrow_mut().coroutines.push(coroutine);
+        }
+        CoroutineHandle(id)
+    }
+
+    /// 未启动的协程句柄
+    pub(crate) fn inactive_coroutine(&mut self) -> CoroutineHandle {
+        CoroutineHandle(self.state.borrow_mut().next_id())
+    }
+
+    pub fn is_running(&self, handle: CoroutineHandle) -> bool {
+        self.state.borrow().coroutines.iter().any(|coroutine| coroutine.id == handle.0)
+    }
+
+    pub fn stop_coroutine(&mut self, handle: CoroutineHandle) {
+        let mut state = self.state.borrow_mut();
+        state.coroutines.retain(|coroutine| coroutine.id != handle.0);
+        state.cancelled.insert(handle.0);
+    }
+
+    pub fn stop_all_coroutines(&mut self) {
+        let state = &mut *self.state.borrow_mut();
+        for coroutine in state.coroutines.iter() {
+            state.cancelled.insert(coroutine.id);
+        }
+        state.coroutines.clear();
+        state.coroutine_generation += 1;
+    }
+
+    pub fn clear(&mut self) {
+        self.cancel_all_invokes();
+        self.stop_all_coroutines();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        let state = self.state.borrow();
+        state.invokes.is_empty() && state.coroutines.is_empty()
+    }
+
+    fn poll_coroutine(coroutine: &mut Coroutine) -> bool {
+        let mut context = Context::from_waker(Waker::noop());
+        coroutine.future.as_mut().poll(&mut context).is_ready()
+    }
+
+    /// 执行到期的 Invoke 和协程
+    /// 回调中可能销毁调度器所在的对象，所以先克隆状态的引用，之后不再访问 self
+    pub(crate) fn run(&self, phase: SchedulerPhase) {
+        let state = self.state.clone();
+        set_scheduler_phase(phase);
+        if phase == SchedulerPhase::Update {
+            Self::run_invokes(&state);
+        }
+        Self::run_coroutines(&state);
+        set_scheduler_phase(SchedulerPhase::Update);
+    }
+
+    fn run_invokes(state: &RefCell<SchedulerState>) {
+        let now = scheduler_time();
+        let (due, generation) = {
+            let state = &mut *state.borrow_mut();
+            if state.invokes.is_empty() {
+                return;
+            }
+            state.cancelled.clear();
+            let (due, pending) = std::mem::take(&mut state.invokes)
+                .into_iter()
+                .partition::<Vec<_>, _>(|invoke| invoke.next_time <= now);
+            state.invokes = pending;
+            (due, state.invoke_generation)
+        };
+
+        for mut invoke in due {
+            {
+                let state = state.borrow();
+                if state.invoke_generation != generation || state.cancelled.contains(&invoke.id) {
+                    continue;
+                }
+            }
+            (invoke.callback)();
+            if let Some(interval) = invoke.interval {
+                let mut state = state.borrow_mut();
+                if state.invoke_generation != generation || state.cancelled.contains(&invoke.id) {
+                    continue;
+                }
+                invoke.next_time += interval;
+                // 落后太多时不补帧
+                if invoke.next_time <= now {
+                    invoke.next_time = now + interval;
+                }
+                state.invokes.push(invoke);
+            }
+        }
+    }
+
+    fn run_coroutines(state: &RefCell<SchedulerState>) {
+        let (coroutines, generation) = {
+            let state = &mut *state.borrow_mut();
+            if state.coroutines.is_empty() {
+                return;
+            }
+            state.cancelled.clear();
+            (std::mem::take(&mut state.coroutines), state.coroutine_generation)
+        };
+
+        let mut pending = vec![];
+        for mut coroutine in coroutines {
+            {
+                let state = state.borrow();
+                if state.coroutine_generation != generation || state.cancelled.contains(&coroutine.id) {
+                    continue;
+                }
+            }
+            if !Self::poll_coroutine(&mut coroutine) {
+                pending.push(coroutine);
+            }
+        }
+
+        let state = &mut *state.borrow_mut();
+        if state.coroutine_generation != generation {
+            return;
+        }
+        pending.retain(|coroutine| !state.cancelled.contains(&coroutine.id));
+        // 执行期间新启动的协程排在后面
+        pending.append(&mut state.coroutines);
+        state.coroutines = pending;
+    }
+}
+
+/// 等待指定秒数，在 Update 之后恢复
+pub struct WaitForSeconds {
+    seconds: f32,
+    deadline: Option<f64>,
+}
+
+impl WaitForSeconds {
+    pub fn new(seconds: f32) -> Self {
+        Self {
+            seconds,
+            deadline: None,
+        }
+    }
+}
+
+impl Future for WaitForSeconds {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Self::Output> {
+        let now = scheduler_time();
+        let seconds = self.seconds;
+        let deadline = *self.deadline.get_or_insert(now + seconds as f64);
+        if scheduler_phase() == SchedulerPhase::Update && now >= deadline {
+            return Poll::Ready(());
+        }
+        Poll::Pending
+    }
+}
+
+/// 等待下一次 FixedUpdate 之后恢复
+#[derive(Default)]
+pub struct WaitForFixedUpdate {
+    polled: bool,
+}
+
+impl WaitForFixedUpdate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Future for WaitForFixedUpdate {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.polled && scheduler_phase() == SchedulerPhase::FixedUpdate {
+            return Poll::Ready(());
+        }
+        self.polled = true;
+        Poll::Pending
+    }
+}
+
+/// 等待下一帧，对应 Unity 的 yield return null
+#[derive(Default)]
+pub struct WaitForNextFrame {
+    polled: bool,
+}
+
+impl WaitForNextFrame {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Future for WaitForNextFrame {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.polled && scheduler_phase() == SchedulerPhase::Update {
+            return Poll::Ready(());
+        }
+        self.polled = true;
+        Poll::Pending
+    }
+}
+
+/// 每帧 Update 之后检查条件，条件成立时恢复
+pub struct WaitUntil<F: FnMut() -> bool> {
+    predicate: F,
+}
+
+impl<F: FnMut() -> bool> WaitUntil<F> {
+    pub fn new(predicate: F) -> Self {
+        Self { predicate }
+    }
+}
+
+impl<F: FnMut() -> bool + Unpin> Future for WaitUntil<F> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Self::Output> {
+        if scheduler_phase() == SchedulerPhase::Update && (self.predicate)() {
+            return Poll::Ready(());
+        }
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn frame(scheduler: &mut Scheduler, time: f64) {
+        set_scheduler_time(time);
+        scheduler.run(SchedulerPhase::FixedUpdate);
+        scheduler.run(SchedulerPhase::Update);
+    }
+
+    #[test]
+    fn test_invoke_and_coroutine() {
+        set_scheduler_time(0.0);
+        let mut scheduler = Scheduler::default();
+        let events = Rc::new(RefCell::new(vec![]));
+
+        let events_clone = events.clone();
+        scheduler.invoke(1.0, move || events_clone.borrow_mut().push("invoke"));
+        let events_clone = events.clone();
+        let repeating = scheduler.invoke_repeating(0.5, 0.5, move || {
+            events_clone.borrow_mut().push("repeat")
+        });
+
+        let events_clone = events.clone();
+        let flag = Rc::new(RefCell::new(false));
+        let flag_clone = flag.clone();
+        scheduler.start_coroutine(async move {
+            events_clone.borrow_mut().push("begin");
+            WaitForSeconds::new(0.75).await;
+            events_clone.borrow_mut().push("waited");
+            WaitForFixedUpdate::new().await;
+            events_clone.borrow_mut().push("fixed");
+            WaitUntil::new(move || *flag_clone.borrow()).await;
+            events_clone.borrow_mut().push("until");
+        });
+        assert_eq!(*events.borrow(), vec!["begin"]);
+
+        frame(&mut scheduler, 0.5);
+        frame(&mut scheduler, 1.0);
+        assert_eq!(*events.borrow(), vec!["begin", "repeat", "invoke", "repeat", "waited"]);
+
+        scheduler.cancel_invoke(repeating);
+        frame(&mut scheduler, 1.5);
+        assert_eq!(events.borrow().last(), Some(&"fixed"));
+
+        *flag.borrow_mut() = true;
+        frame(&mut scheduler, 2.0);
+        assert_eq!(events.borrow().last(), Some(&"until"));
+        assert!(scheduler.is_empty());
+    }
+}
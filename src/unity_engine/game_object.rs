@@ -2,17 +2,22 @@ use crate::commons::RevelArc;
 use crate::commons::RevelWeak;
 use crate::metadata_settings::metadata_component::MetadataComponentWrapper;
 use crate::metadata_settings::metadata_prefab::MetadataPrefab;
+use crate::mirror::NetworkIdentity;
+use crate::unity_engine::coroutine::{CoroutineHandle, InvokeHandle, Scheduler, SchedulerPhase};
 use crate::unity_engine::mono_behaviour::MonoBehaviour;
 use crate::unity_engine::mono_behaviour_factory::MonoBehaviourFactory;
 use crate::unity_engine::transform::Transform;
 use crate::unity_engine::WorldManager;
-use once_cell::sync::Lazy;
+use nalgebra::{Quaternion, Vector3};
+use crate::commons::thread_static;
 use rand::RngCore;
 use std::any::TypeId;
 use std::collections::HashMap;
+use std::future::Future;
 
-static mut COMPONENT_LOADING: Lazy<Vec<(RevelWeak<GameObject>, MetadataComponentWrapper)>> =
-    Lazy::new(|| vec![]);
+thread_static! {
+    static mut COMPONENT_LOADING: Vec<(RevelWeak<GameObject>, MetadataComponentWrapper)> = vec![];
+}
 
 fn append_component_loading(
     weak_game_object: RevelWeak<GameObject>,
@@ -44,6 +49,10 @@ pub struct GameObject {
     pub layer: i32,
     pub is_static: bool,
     is_active: bool,
+    // 已调用 on_destroy，同一帧中剩余的更新不再执行
+    destroyed: bool,
+    // 所属场景实例句柄，0 表示 DontDestroyOnLoad 或尚未加入场景
+    scene_handle: u32,
     pub transform: RevelArc<Transform>,
     pub parent: RevelWeak<GameObject>,
     pub children: HashMap<u64, RevelArc<GameObject>>,
@@ -51,6 +60,29 @@ pub struct GameObject {
     component_mapping: HashMap<TypeId, Vec<usize>>,
     // pub components: Vec<RevelArc<Box<dyn MonoBehaviour>>>>,
     pub components: Vec<Vec<RevelArc<Box<dyn MonoBehaviour>>>>,
+    // 与 components 一一对应的生命周期状态
+    component_states: Vec<ComponentState>,
+    scheduler: Scheduler,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct ComponentState {
+    enabled: bool,
+    awoken: bool,
+    // 已调用 on_enable 且尚未调用 on_disable
+    enable_called: bool,
+    started: bool,
+}
+
+impl Default for ComponentState {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            awoken: false,
+            enable_called: false,
+            started: false,
+        }
+    }
 }
 
 impl GameObject {
@@ -70,6 +102,62 @@ impl GameObject {
         arc_game_object
     }
 
+    /// 在指定的世界坐标实例化预制体
+    pub fn instantiate_at(
+        metadata_prefab: &MetadataPrefab,
+        position: Vector3<f32>,
+        rotation: Quaternion<f32>,
+    ) -> RevelArc<GameObject> {
+        let mut arc_game_object = Self::instantiate(metadata_prefab);
+        arc_game_object
+            .transform
+            .set_position_and_rotation(position, rotation);
+        arc_game_object
+    }
+
+    /// 实例化预制体作为 parent 的子对象，预制体的 Transform 视为相对 parent 的局部值
+    pub fn instantiate_under(
+        metadata_prefab: &MetadataPrefab,
+        parent: RevelWeak<GameObject>,
+    ) -> RevelArc<GameObject> {
+        log::debug!("Instantiate: {}", metadata_prefab.name);
+        let mut arc_game_object = Self::instance(metadata_prefab);
+        match parent.get() {
+            None => {
+                if let Some(world) = WorldManager::active_world().get() {
+                    world.add_game_object(arc_game_object.clone());
+                }
+            }
+            Some(parent_game_object) => {
+                arc_game_object.attach_to(parent_game_object, false);
+            }
+        }
+        arc_game_object
+    }
+
+    /// 在指定的场景实例中实例化预制体
+    pub fn instantiate_in_scene(
+        metadata_prefab: &MetadataPrefab,
+        scene_handle: u32,
+    ) -> RevelArc<GameObject> {
+        log::debug!("Instantiate: {} in scene {}", metadata_prefab.name, scene_handle);
+        let arc_game_object = Self::instance(metadata_prefab);
+        match WorldManager::get_scene_by_handle(scene_handle).get() {
+            Some(world) => world.add_game_object(arc_game_object.clone()),
+            None => {
+                log::warn!(
+                    "Scene instance {} is not loaded, instantiate '{}' into the active scene.",
+                    scene_handle,
+                    metadata_prefab.name
+                );
+                if let Some(world) = WorldManager::active_world().get() {
+                    world.add_game_object(arc_game_object.clone());
+                }
+            }
+        }
+        arc_game_object
+    }
+
     pub fn default() -> GameObject {
         Self {
             id: rand::rng().next_u64(),
@@ -87,6 +175,8 @@ impl GameObject {
             layer: metadata_prefab.layer,
             is_static: metadata_prefab.is_static,
             is_active: metadata_prefab.is_active,
+            destroyed: false,
+            scene_handle: 0,
             asset_id: metadata_prefab.asset_id,
             asset_path: metadata_prefab.asset_path.clone(),
             parent: parent.clone(),
@@ -94,15 +184,18 @@ impl GameObject {
             transform: Default::default(),
             children: HashMap::default(),
             component_mapping: Default::default(),
+            component_states: Default::default(),
+            scheduler: Default::default(),
         };
 
-        let mut transform = Transform::new_with_metadata(&metadata_prefab.transform);
+        game_object.transform = Transform::new_with_metadata(&metadata_prefab.transform).into_arc();
 
         if let Some(parent_game_object) = parent.get() {
-            transform.parent = parent_game_object.transform.downgrade()
+            game_object
+                .transform
+                .set_parent(parent_game_object.transform.downgrade(), false);
         }
 
-        game_object.transform = RevelArc::new(transform);
         let mut arc_game_object = RevelArc::new(game_object);
 
         append_component_loading(
@@ -127,15 +220,6 @@ impl GameObject {
             if metadata_prefab.children.len() > 0 {
                 Self::recursive_children(weak_game_object.clone(), &metadata_prefab.children);
             }
-
-            if let Some(game_object) = weak_game_object.get() {
-                for (_, children_game_object) in &game_object.children {
-                    game_object
-                        .transform
-                        .children
-                        .push(children_game_object.transform.downgrade());
-                }
-            }
         }
     }
 
@@ -181,11 +265,45 @@ impl GameObject {
             }
         }
         self.components.push(arc_mono_behaviours);
+        self.component_states.push(ComponentState::default());
 
-        if let Some(component) = self.components.last_mut() {
-            if let Some(component) = component.last_mut() {
-                component.awake();
-            }
+        // 未激活的对象在激活时才调用 awake
+        if self.is_active_in_hierarchy() {
+            self.awake_and_enable(index);
+        }
+    }
+
+    fn component_index(&self, component: &RevelWeak<Box<dyn MonoBehaviour>>) -> Option<usize> {
+        self.components.iter().position(|chain| {
+            chain
+                .iter()
+                .any(|mono_behaviour| component.ptr_eq(&mono_behaviour.downgrade()))
+        })
+    }
+
+    pub fn is_component_enabled(&self, component: &RevelWeak<Box<dyn MonoBehaviour>>) -> bool {
+        self.component_index(component)
+            .map(|index| self.component_states[index].enabled)
+            .unwrap_or(false)
+    }
+
+    /// 对应 Unity 的 Behaviour.enabled，激活的对象上会触发 on_enable / on_disable
+    pub fn set_component_enabled(&mut self, component: &RevelWeak<Box<dyn MonoBehaviour>>, enabled: bool) {
+        let Some(index) = self.component_index(component) else {
+            log::warn!("Component is not attached to GameObject '{}'.", self.name);
+            return;
+        };
+        if self.component_states[index].enabled == enabled {
+            return;
+        }
+        self.component_states[index].enabled = enabled;
+        if !self.is_active_in_hierarchy() {
+            return;
+        }
+        if enabled {
+            self.awake_and_enable(index);
+        } else {
+            self.disable_component(index);
         }
     }
 
@@ -260,6 +378,40 @@ impl GameObject {
     //     None
     // }
 
+    /// 在自身及子对象中查找组件，跳过未激活的子对象
+    pub fn get_component_in_children<T: MonoBehaviour + 'static>(&self) -> Option<RevelWeak<Box<dyn MonoBehaviour>>> {
+        if let Some(component) = self.try_get_component::<T>() {
+            return Some(component);
+        }
+        self.children
+            .values()
+            .filter(|child| child.is_active)
+            .find_map(|child| child.get_component_in_children::<T>())
+    }
+
+    pub fn get_components_in_children<T: MonoBehaviour + 'static>(&self) -> Vec<RevelWeak<Box<dyn MonoBehaviour>>> {
+        let mut result = self.get_components::<T>();
+        for child in self.children.values().filter(|child| child.is_active) {
+            result.extend(child.get_components_in_children::<T>());
+        }
+        result
+    }
+
+    /// 在自身及父对象中查找组件
+    pub fn get_component_in_parent<T: MonoBehaviour + 'static>(&self) -> Option<RevelWeak<Box<dyn MonoBehaviour>>> {
+        if let Some(component) = self.try_get_component::<T>() {
+            return Some(component);
+        }
+        self.parent.get()?.get_component_in_parent::<T>()
+    }
+
+    pub fn children_weak(&self) -> Vec<RevelWeak<GameObject>> {
+        self.children
+            .values()
+            .map(|child| child.downgrade())
+            .collect()
+    }
+
     pub fn find_transform(&self, instance_id: &i32) -> Option<RevelWeak<Transform>> {
         if self.transform.instance_id == *instance_id {
             return Some(self.transform.downgrade());
@@ -272,17 +424,172 @@ impl GameObject {
         None
     }
 
+    /// 自身的激活状态，对应 Unity 的 activeSelf
     pub fn is_active(&self) -> bool {
         self.is_active
     }
+
+    /// 自身及所有父对象都处于激活状态，对应 Unity 的 activeInHierarchy
+    pub fn is_active_in_hierarchy(&self) -> bool {
+        if !self.is_active {
+            return false;
+        }
+        match self.parent.get() {
+            None => true,
+            Some(parent) => parent.is_active_in_hierarchy(),
+        }
+    }
+
+    /// 延迟 delay 秒后调用一次
+    pub fn invoke(&mut self, delay: f32, callback: impl FnMut() + 'static) -> InvokeHandle {
+        self.scheduler.invoke(delay, callback)
+    }
+
+    /// 延迟 delay 秒后每隔 interval 秒调用一次。对象未激活时暂停
+    pub fn invoke_repeating(
+        &mut self,
+        delay: f32,
+        interval: f32,
+        callback: impl FnMut() + 'static,
+    ) -> InvokeHandle {
+        self.scheduler.invoke_repeating(delay, interval, callback)
+    }
+
+    pub fn is_invoking(&self, handle: InvokeHandle) -> bool {
+        self.scheduler.is_invoking(handle)
+    }
+
+    pub fn cancel_invoke(&mut self, handle: InvokeHandle) {
+        self.scheduler.cancel_invoke(handle)
+    }
+
+    pub fn cancel_all_invokes(&mut self) {
+        self.scheduler.cancel_all_invokes()
+    }
+
+    /// 启动协程，对象被销毁或取消激活时自动停止
+    pub fn start_coroutine(&mut self, coroutine: impl Future<Output = ()> + 'static) -> CoroutineHandle {
+        if !self.is_active_in_hierarchy() {
+            log::error!(
+                "Coroutine couldn't be started because the game object '{}' is inactive!",
+                self.name
+            );
+            return self.scheduler.inactive_coroutine();
+        }
+        self.scheduler.start_coroutine(coroutine)
+    }
+
+    pub fn is_coroutine_running(&self, handle: CoroutineHandle) -> bool {
+        self.scheduler.is_running(handle)
+    }
+
+    pub fn stop_coroutine(&mut self, handle: CoroutineHandle) {
+        self.scheduler.stop_coroutine(handle)
+    }
+
+    pub fn stop_all_coroutines(&mut self) {
+        self.scheduler.stop_all_coroutines()
+    }
+
+    /// 修改父对象，parent 为空时成为所在场景的根对象。
+    /// world_position_stays 为 true 时保持世界空间的位置、旋转和缩放不变
+    pub fn set_parent(&mut self, parent: RevelWeak<GameObject>, world_position_stays: bool) -> bool {
+        if parent.ptr_eq(&self.parent) {
+            return true;
+        }
+        if let Some(new_parent) = parent.get() {
+            if new_parent.transform.is_child_of(&self.transform.downgrade()) {
+                log::error!(
+                    "GameObject '{}': can not set parent to itself or one of its children.",
+                    self.name
+                );
+                return false;
+            }
+        }
+
+        // 先从原来的位置取出
+        let taken = match self.parent.get() {
+            Some(old_parent) => old_parent.children.remove(&self.id),
+            None => WorldManager::take_root_game_object(&self.id),
+        };
+        let Some(mut arc_game_object) = taken else {
+            log::error!(
+                "GameObject '{}' is not in any scene and can not be reparented.",
+                self.name
+            );
+            return false;
+        };
+
+        match parent.get() {
+            Some(new_parent) => arc_game_object.attach_to(new_parent, world_position_stays),
+            None => {
+                let scene_handle = self.scene_handle;
+                arc_game_object.parent = RevelWeak::default();
+                arc_game_object
+                    .transform
+                    .set_parent(RevelWeak::default(), world_position_stays);
+                WorldManager::add_root_game_object(arc_game_object, scene_handle);
+            }
+        }
+        true
+    }
+
+    fn attach_to(&mut self, parent: &mut GameObject, world_position_stays: bool) {
+        let Some(arc_game_object) = self.transform.game_object.upgrade() else {
+            return;
+        };
+        self.parent = parent.transform.game_object.clone();
+        self.transform
+            .set_parent(parent.transform.downgrade(), world_position_stays);
+        self.set_scene_handle(parent.scene_handle);
+        parent.children.insert(self.id, arc_game_object);
+    }
+
+    pub(crate) fn remove_child_recursive(&mut self, id: &u64) -> Option<RevelArc<GameObject>> {
+        if let Some(mut removed) = self.children.remove(id) {
+            removed.parent = RevelWeak::default();
+            removed.transform.set_parent(RevelWeak::default(), true);
+            return Some(removed);
+        }
+        self.children
+            .values_mut()
+            .find_map(|child| child.remove_child_recursive(id))
+    }
+
+    pub fn scene_handle(&self) -> u32 {
+        self.scene_handle
+    }
+
+    pub(crate) fn set_scene_handle(&mut self, scene_handle: u32) {
+        self.scene_handle = scene_handle;
+        for child in self.children.values_mut() {
+            child.set_scene_handle(scene_handle);
+        }
+    }
+
+    /// 修改激活状态，并对受影响的整棵子树调用 awake / on_enable 或 on_disable
     pub fn set_active(&mut self, active: bool) {
-        self.is_active = active
+        if self.is_active == active {
+            return;
+        }
+        let parent_active = match self.parent.get() {
+            None => true,
+            Some(parent) => parent.is_active_in_hierarchy(),
+        };
+        if !parent_active {
+            self.is_active = active;
+            return;
+        }
+        if active {
+            self.is_active = true;
+            self.activate_hierarchy();
+        } else {
+            self.deactivate_hierarchy();
+            self.is_active = false;
+        }
     }
 }
 
-//调用了start的组件map
-static mut STARTED_COMPONENT: Vec<RevelWeak<Box<dyn MonoBehaviour>>> = Vec::new();
-
 macro_rules! recursive_event_fn {
     ($($fn_name:ident),*) => {
         $(
@@ -300,51 +607,300 @@ macro_rules! recursive_event_fn {
     };
 }
 
+// 生命周期: awake -> on_enable -> start (第一次更新前) -> update ... -> on_disable -> on_destroy
 #[allow(unused)]
 impl GameObject {
-    recursive_event_fn!(
-        awake,
-        on_enable,
-        on_validate,
-        start,
-        fixed_update,
-        late_update,
-        on_disable
-    );
+    recursive_event_fn!(on_validate);
+
+    fn component(&self, index: usize) -> Option<RevelArc<Box<dyn MonoBehaviour>>> {
+        self.components.get(index)?.last().cloned()
+    }
+
+    fn awake_and_enable(&mut self, index: usize) {
+        let Some(mut component) = self.component(index) else {
+            return;
+        };
+        if !self.component_states[index].awoken {
+            self.component_states[index].awoken = true;
+            component.awake();
+        }
+        let state = self.component_states[index];
+        if state.enabled && !state.enable_called && self.is_active_in_hierarchy() {
+            self.component_states[index].enable_called = true;
+            component.on_enable();
+        }
+    }
+
+    fn disable_component(&mut self, index: usize) {
+        if !self.component_states[index].enable_called {
+            return;
+        }
+        self.component_states[index].enable_called = false;
+        if let Some(mut component) = self.component(index) {
+            component.on_disable();
+        }
+    }
+
+    fn activate_hierarchy(&mut self) {
+        for index in 0..self.components.len() {
+            self.awake_and_enable(index);
+        }
+        for child in self.children.clone().values_mut() {
+            if child.is_active {
+                child.activate_hierarchy();
+            }
+        }
+    }
+
+    fn deactivate_hierarchy(&mut self) {
+        for index in 0..self.components.len() {
+            self.disable_component(index);
+        }
+        self.scheduler.stop_all_coroutines();
+        for child in self.children.clone().values_mut() {
+            if child.is_active {
+                child.deactivate_hierarchy();
+            }
+        }
+    }
+
+    /// 带 NetworkIdentity 的对象在服务器生成 (on_start_server) 之前不运行 start / update
+    fn is_gameplay_ready(&self) -> bool {
+        match self.try_get_component2::<NetworkIdentity>() {
+            None => true,
+            Some(identity) => identity.is_server && identity.net_id() != 0,
+        }
+    }
+
+    fn tick(&mut self, event: fn(&mut Box<dyn MonoBehaviour>), phase: Option<SchedulerPhase>) {
+        if !self.is_active || self.destroyed || !self.is_gameplay_ready() {
+            return;
+        }
+        for children_game_object in self.children.clone().values_mut() {
+            children_game_object.tick(event, phase)
+        }
+        for index in 0..self.components.len() {
+            let state = self.component_states[index];
+            if !state.enable_called {
+                continue;
+            }
+            let Some(mut component) = self.component(index) else {
+                continue;
+            };
+            if !state.started {
+                self.component_states[index].started = true;
+                component.start();
+            }
+            // start 中可能禁用了自身
+            if self.component_states[index].enable_called {
+                event(&mut component);
+            }
+        }
+        // Invoke 和协程在组件的 FixedUpdate / Update 之后执行
+        if let Some(phase) = phase {
+            if !self.scheduler.is_empty() {
+                self.scheduler.run(phase);
+            }
+        }
+    }
+
+    pub(crate) fn fixed_update(&mut self) {
+        self.tick(|component| component.fixed_update(), Some(SchedulerPhase::FixedUpdate))
+    }
 
     pub(crate) fn update(&mut self) {
+        self.tick(|component| component.update(), Some(SchedulerPhase::Update))
+    }
+
+    pub(crate) fn late_update(&mut self) {
+        self.tick(|component| component.late_update(), None)
+    }
+
+    pub(crate) fn on_disable(&mut self) {
+        for index in 0..self.components.len() {
+            self.disable_component(index);
+        }
         for children_game_object in self.children.values_mut() {
-            children_game_object.update()
-        }
-        for component_chain in self.components.clone().iter_mut() {
-            if let Some(component) = component_chain.last_mut() {
-                #[allow(static_mut_refs)]
-                unsafe {
-                    let started = STARTED_COMPONENT.iter().any(|weak_component| {
-                        weak_component.ptr_eq(&component.downgrade())
-                    });
-                    if !started {
-                        component.start();
-                        STARTED_COMPONENT.push(component.downgrade())
-                    }
-                }
-                component.update()
-            }
+            children_game_object.on_disable();
+        }
+    }
+
+    // 取消自身和子对象的所有 Invoke 和协程
+    pub(crate) fn clear_scheduler(&mut self) {
+        self.scheduler.clear();
+        for children_game_object in self.children.values_mut() {
+            children_game_object.clear_scheduler();
         }
     }
 
+    /// 只有调用过 awake 的组件才会收到 on_destroy
     pub(crate) fn on_destroy(&mut self) {
+        self.destroyed = true;
+        self.scheduler.clear();
         for children_game_object in self.children.values_mut() {
             children_game_object.on_destroy();
         }
-        for component_chain in self.components.iter_mut() {
-            if let Some(component) = component_chain.last_mut() {
-                #[allow(static_mut_refs)]
-                unsafe {
-                    STARTED_COMPONENT.retain(|weak_component| !weak_component.ptr_eq(&component.downgrade()));
-                }
+        for index in 0..self.components.len() {
+            if !self.component_states[index].awoken {
+                continue;
+            }
+            self.component_states[index].awoken = false;
+            if let Some(mut component) = self.component(index) {
                 component.on_destroy();
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commons::Object;
+
+    struct SpawnPoint;
+
+    impl Object for SpawnPoint {
+        fn get_full_name() -> &'static str {
+            "SpawnPoint"
+        }
+    }
+
+    impl MonoBehaviour for SpawnPoint {}
+
+    struct Recorder(std::rc::Rc<std::cell::RefCell<Vec<&'static str>>>);
+
+    impl Object for Recorder {
+        fn get_full_name() -> &'static str {
+            "Recorder"
+        }
+    }
+
+    impl MonoBehaviour for Recorder {
+        fn awake(&mut self) {
+            self.0.borrow_mut().push("awake");
+        }
+        fn on_enable(&mut self) {
+            self.0.borrow_mut().push("on_enable");
+        }
+        fn start(&mut self) {
+            self.0.borrow_mut().push("start");
+        }
+        fn update(&mut self) {
+            self.0.borrow_mut().push("update");
+        }
+        fn on_disable(&mut self) {
+            self.0.borrow_mut().push("on_disable");
+        }
+        fn on_destroy(&mut self) {
+            self.0.borrow_mut().push("on_destroy");
+        }
+    }
+
+    fn prefab(name: &str, local_position: [f32; 3]) -> MetadataPrefab {
+        serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "name": name,
+            "tag": "Untagged",
+            "layer": 0,
+            "isStatic": false,
+            "isActive": true,
+            "transform": {
+                "instanceId": 0,
+                "position": local_position,
+                "localPosition": local_position,
+                "rotation": [0.0, 0.0, 0.0, 1.0],
+                "localRotation": [0.0, 0.0, 0.0, 1.0],
+                "localScale": [1.0, 1.0, 1.0],
+                "type": "UnityEngine.Transform",
+                "assetId": 0,
+                "assetPath": ""
+            },
+            "components": {},
+            "children": [],
+            "assetId": 0,
+            "assetPath": ""
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_instantiate_under_and_reparent() {
+        let mut hull = GameObject::instance(&prefab("Hull", [10.0, 0.0, 0.0]));
+        let mut base = GameObject::instance(&prefab("Base", [0.0, 0.0, -5.0]));
+
+        let mut turret = GameObject::instantiate_under(&prefab("Turret", [0.0, 1.0, 0.0]), hull.downgrade());
+        assert!(hull.children.contains_key(&turret.id));
+        assert!(turret.parent.ptr_eq(&hull.downgrade()));
+        assert_eq!(turret.transform.position(), Vector3::new(10.0, 1.0, 0.0));
+
+        hull.transform.set_position(Vector3::new(20.0, 0.0, 0.0));
+        assert_eq!(turret.transform.position(), Vector3::new(20.0, 1.0, 0.0));
+
+        assert!(turret.set_parent(base.downgrade(), true));
+        assert!(hull.children.is_empty());
+        assert!(hull.transform.children.is_empty());
+        assert!(base.children.contains_key(&turret.id));
+        assert_eq!(turret.transform.position(), Vector3::new(20.0, 1.0, 0.0));
+        assert_eq!(turret.transform.local_position(), Vector3::new(20.0, 1.0, 5.0));
+
+        // 不能挂到自己的子对象下
+        assert!(!base.set_parent(turret.downgrade(), false));
+    }
+
+    #[test]
+    fn test_get_component_in_children_and_parent() {
+        let root = GameObject::instance(&prefab("Zone", [0.0, 0.0, 0.0]));
+        let mut child = GameObject::instantiate_under(&prefab("Point", [0.0, 0.0, 0.0]), root.downgrade());
+        let grandchild = GameObject::instantiate_under(&prefab("Marker", [0.0, 0.0, 0.0]), child.downgrade());
+
+        assert!(root.get_component_in_children::<SpawnPoint>().is_none());
+        child.add_component(vec![(
+            RevelArc::new(Box::new(SpawnPoint) as Box<dyn MonoBehaviour>),
+            TypeId::of::<SpawnPoint>(),
+        )]);
+
+        assert!(root.get_component_in_children::<SpawnPoint>().is_some());
+        assert_eq!(root.get_components_in_children::<SpawnPoint>().len(), 1);
+        assert!(grandchild.get_component_in_parent::<SpawnPoint>().is_some());
+        assert!(root.get_component_in_parent::<SpawnPoint>().is_none());
+
+        // 未激活的子对象不参与查找
+        child.set_active(false);
+        assert!(root.get_component_in_children::<SpawnPoint>().is_none());
+    }
+
+    #[test]
+    fn test_lifecycle() {
+        let events = std::rc::Rc::new(std::cell::RefCell::new(vec![]));
+        let mut root = GameObject::instance(&prefab("Root", [0.0, 0.0, 0.0]));
+        let mut child = GameObject::instantiate_under(&prefab("Child", [0.0, 0.0, 0.0]), root.downgrade());
+
+        // 父对象未激活时不调用 awake
+        root.set_active(false);
+        let component = RevelArc::new(Box::new(Recorder(events.clone())) as Box<dyn MonoBehaviour>);
+        let weak_component = component.downgrade();
+        child.add_component(vec![(component, TypeId::of::<Recorder>())]);
+        root.update();
+        assert!(events.borrow().is_empty());
+
+        root.set_active(true);
+        assert_eq!(*events.borrow(), vec!["awake", "on_enable"]);
+
+        root.update();
+        root.update();
+        assert_eq!(*events.borrow(), vec!["awake", "on_enable", "start", "update", "update"]);
+
+        events.borrow_mut().clear();
+        child.set_component_enabled(&weak_component, false);
+        root.update();
+        child.set_component_enabled(&weak_component, true);
+        assert_eq!(*events.borrow(), vec!["on_disable", "on_enable"]);
+
+        events.borrow_mut().clear();
+        root.set_active(false);
+        root.update();
+        root.on_disable();
+        root.on_destroy();
+        assert_eq!(*events.borrow(), vec!["on_disable", "on_destroy"]);
+    }
+}
@@ -0,0 +1,74 @@
+use crate::macro_namespace::*;
+use crate::macro_network_serialize::*;
+use nalgebra::{Vector2, Vector3};
+
+/// UnityEngine.Rect
+#[namespace(prefix = "UnityEngine")]
+#[derive(Debug, Clone, Copy, PartialEq, Default, NetworkSerialize, NetworkDeserialize)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Rect {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self { x, y, width, height }
+    }
+
+    pub fn position(&self) -> Vector2<f32> {
+        Vector2::new(self.x, self.y)
+    }
+
+    pub fn size(&self) -> Vector2<f32> {
+        Vector2::new(self.width, self.height)
+    }
+
+    pub fn contains(&self, point: Vector2<f32>) -> bool {
+        point.x >= self.x && point.x < self.x + self.width && point.y >= self.y && point.y < self.y + self.height
+    }
+}
+
+/// UnityEngine.Plane，normal · p + distance = 0
+#[namespace(prefix = "UnityEngine")]
+#[derive(Debug, Clone, Copy, PartialEq, Default, NetworkSerialize, NetworkDeserialize)]
+pub struct Plane {
+    pub normal: Vector3<f32>,
+    pub distance: f32,
+}
+
+impl Plane {
+    pub fn new(normal: Vector3<f32>, point: Vector3<f32>) -> Self {
+        let normal = normal.try_normalize(f32::EPSILON).unwrap_or_else(Vector3::zeros);
+        Self {
+            normal,
+            distance: -normal.dot(&point),
+        }
+    }
+
+    pub fn get_distance_to_point(&self, point: Vector3<f32>) -> f32 {
+        self.normal.dot(&point) + self.distance
+    }
+}
+
+/// UnityEngine.Ray
+#[namespace(prefix = "UnityEngine")]
+#[derive(Debug, Clone, Copy, PartialEq, Default, NetworkSerialize, NetworkDeserialize)]
+pub struct Ray {
+    pub origin: Vector3<f32>,
+    pub direction: Vector3<f32>,
+}
+
+impl Ray {
+    pub fn new(origin: Vector3<f32>, direction: Vector3<f32>) -> Self {
+        Self {
+            origin,
+            direction: direction.try_normalize(f32::EPSILON).unwrap_or_else(Vector3::zeros),
+        }
+    }
+
+    pub fn get_point(&self, distance: f32) -> Vector3<f32> {
+        self.origin + self.direction * distance
+    }
+}
@@ -0,0 +1,334 @@
+use crate::commons::thread_static;
+use std::any::{type_name, Any, TypeId};
+use std::collections::HashMap;
+use std::future::Future;
+use std::ops::{Deref, DerefMut};
+use std::pin::Pin;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Condvar, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+type Job = Box<dyn FnOnce(&mut MainThreadDispatcherStatic) + Send>;
+type MessageHandler = Box<dyn FnMut(Box<dyn Any>) -> Box<dyn Any>>;
+
+thread_static! {
+    static mut MAIN_THREAD_DISPATCHER: MainThreadDispatcherStatic = MainThreadDispatcherStatic::new();
+}
+
+/// 可以从其他线程投递到主线程执行的消息
+pub trait DispatchMessage: Send + 'static {
+    /// 处理结果，不需要回复时为 ()
+    type Reply: Send + 'static;
+}
+
+/// 把其他线程 (tokio 等异步运行时) 的工作投递到主循环，在 PlayerLooper::frame_update 中执行
+pub struct MainThreadDispatcherStatic {
+    sender: Sender<Job>,
+    // 关闭后为空，已投递的工作随之丢弃，所有句柄投递失败
+    receiver: Option<Receiver<Job>>,
+    handlers: HashMap<TypeId, MessageHandler>,
+    // 当前线程是否运行 PlayerLooper，其他线程上的 dispatcher 不会被执行
+    loop_thread: bool,
+}
+
+impl Default for MainThreadDispatcherStatic {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MainThreadDispatcherStatic {
+    pub fn new() -> Self {
+        let (sender, receiver) = channel();
+        Self {
+            sender,
+            receiver: Some(receiver),
+            handlers: Default::default(),
+            loop_thread: false,
+        }
+    }
+
+    /// 在主线程获取后再交给其他线程
+    /// 只能在运行 PlayerLooper 的线程上获取 (PlayerLooper::spawn 的 setup 中，或 PlayerLooper::stop_handle / run 之后)
+    /// 其他线程上返回的句柄投递总是失败
+    pub fn handle(&self) -> DispatcherHandle {
+        if !self.loop_thread {
+            log::error!(
+                "MainThreadDispatcher.handle() called outside the PlayerLooper thread, jobs posted to it would never run"
+            );
+            let (sender, _) = channel();
+            return DispatcherHandle { sender };
+        }
+        DispatcherHandle {
+            sender: self.sender.clone(),
+        }
+    }
+
+    /// 标记当前线程为主循环线程，已关闭时重新打开
+    pub(crate) fn bind_loop_thread(&mut self) {
+        self.loop_thread = true;
+        if self.receiver.is_none() {
+            let (sender, receiver) = channel();
+            self.sender = sender;
+            self.receiver = Some(receiver);
+        }
+    }
+
+    /// 关闭 dispatcher，丢弃未执行的工作 (等待中的 Reply 返回 None)，之后的投递失败
+    pub fn close(&mut self) {
+        self.receiver = None;
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.receiver.is_none()
+    }
+
+    /// 注册消息处理函数，只能在主线程调用，同一类型重复注册时覆盖
+    pub fn register_handler<M: DispatchMessage>(&mut self, mut handler: impl FnMut(M) -> M::Reply + 'static) {
+        self.handlers.insert(
+            TypeId::of::<M>(),
+            Box::new(move |message| {
+                let message = *message.downcast::<M>().unwrap();
+                Box::new(handler(message))
+            }),
+        );
+    }
+
+    pub fn unregister_handler<M: DispatchMessage>(&mut self) -> bool {
+        self.handlers.remove(&TypeId::of::<M>()).is_some()
+    }
+
+    /// 执行已投递的工作，返回执行数量。执行期间新投递的工作留到下一次
+    pub fn drain(&mut self) -> usize {
+        let Some(receiver) = &self.receiver else {
+            return 0;
+        };
+        let jobs = receiver.try_iter().collect::<Vec<_>>();
+        let count = jobs.len();
+        for job in jobs {
+            job(self);
+        }
+        count
+    }
+
+    fn dispatch<M: DispatchMessage>(&mut self, message: M, reply_sender: ReplySender<M::Reply>) {
+        let type_id = TypeId::of::<M>();
+        // 先取出，处理函数中可能再注册
+        let Some(mut handler) = self.handlers.remove(&type_id) else {
+            log::warn!("MainThreadDispatcher: no handler registered for {}", type_name::<M>());
+            return;
+        };
+        let reply = handler(Box::new(message));
+        self.handlers.entry(type_id).or_insert(handler);
+        if let Ok(reply) = reply.downcast::<M::Reply>() {
+            reply_sender.send(*reply);
+        }
+    }
+}
+
+pub struct MainThreadDispatcher;
+
+impl Deref for MainThreadDispatcher {
+    type Target = MainThreadDispatcherStatic;
+
+    fn deref(&self) -> &Self::Target {
+        #[allow(static_mut_refs)]
+        unsafe {
+            &MAIN_THREAD_DISPATCHER
+        }
+    }
+}
+
+impl DerefMut for MainThreadDispatcher {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        #[allow(static_mut_refs)]
+        unsafe {
+            &mut MAIN_THREAD_DISPATCHER
+        }
+    }
+}
+
+/// 可克隆、可跨线程的投递句柄
+#[derive(Clone)]
+pub struct DispatcherHandle {
+    sender: Sender<Job>,
+}
+
+impl DispatcherHandle {
+    /// 投递闭包，dispatcher 已关闭或销毁时返回 false
+    pub fn post(&self, f: impl FnOnce() + Send + 'static) -> bool {
+        self.sender.send(Box::new(move |_| f())).is_ok()
+    }
+
+    /// 投递闭包并取回返回值
+    pub fn post_with_reply<R: Send + 'static>(&self, f: impl FnOnce() -> R + Send + 'static) -> Reply<R> {
+        let (reply_sender, reply) = reply_channel();
+        let _ = self.sender.send(Box::new(move |_| reply_sender.send(f())));
+        reply
+    }
+
+    /// 投递消息，由主线程注册的处理函数处理。不关心结果时可直接丢弃 Reply
+    pub fn send<M: DispatchMessage>(&self, message: M) -> Reply<M::Reply> {
+        let (reply_sender, reply) = reply_channel();
+        let _ = self
+            .sender
+            .send(Box::new(move |dispatcher| dispatcher.dispatch(message, reply_sender)));
+        reply
+    }
+}
+
+struct ReplyState<R> {
+    value: Option<R>,
+    closed: bool,
+    waker: Option<Waker>,
+}
+
+struct ReplyShared<R> {
+    state: Mutex<ReplyState<R>>,
+    ready: Condvar,
+}
+
+fn reply_channel<R>() -> (ReplySender<R>, Reply<R>) {
+    let shared = Arc::new(ReplyShared {
+        state: Mutex::new(ReplyState {
+            value: None,
+            closed: false,
+            waker: None,
+        }),
+        ready: Condvar::new(),
+    });
+    (ReplySender(shared.clone()), Reply(shared))
+}
+
+struct ReplySender<R>(Arc<ReplyShared<R>>);
+
+impl<R> ReplySender<R> {
+    fn send(self, value: R) {
+        self.0.state.lock().unwrap().value = Some(value);
+    }
+}
+
+impl<R> Drop for ReplySender<R> {
+    fn drop(&mut self) {
+        let mut state = self.0.state.lock().unwrap();
+        state.closed = true;
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+        self.0.ready.notify_all();
+    }
+}
+
+/// 主线程处理结果。没有处理函数或工作被丢弃时结果为 None
+/// 可以阻塞等待，也可以在异步运行时中 await，不能在主线程上等待
+pub struct Reply<R>(Arc<ReplyShared<R>>);
+
+impl<R> Reply<R> {
+    pub fn wait(self) -> Option<R> {
+        let mut state = self.0.state.lock().unwrap();
+        while !state.closed {
+            state = self.0.ready.wait(state).unwrap();
+        }
+        state.value.take()
+    }
+
+    pub fn wait_timeout(self, timeout: Duration) -> Option<R> {
+        let state = self.0.state.lock().unwrap();
+        let (mut state, _) = self
+            .0
+            .ready
+            .wait_timeout_while(state, timeout, |state| !state.closed)
+            .unwrap();
+        state.value.take()
+    }
+
+    /// 非阻塞获取
+    pub fn try_take(&self) -> Option<R> {
+        self.0.state.lock().unwrap().value.take()
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.0.state.lock().unwrap().closed
+    }
+}
+
+impl<R> Future for Reply<R> {
+    type Output = Option<R>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.0.state.lock().unwrap();
+        if state.closed {
+            return Poll::Ready(state.value.take());
+        }
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    struct Kick {
+        connection_id: u64,
+    }
+
+    impl DispatchMessage for Kick {
+        type Reply = bool;
+    }
+
+    #[test]
+    fn test_dispatch_from_other_thread() {
+        let mut dispatcher = MainThreadDispatcherStatic::new();
+        dispatcher.bind_loop_thread();
+        let kicked = Rc::new(Cell::new(0));
+        let main_kicked = kicked.clone();
+        dispatcher.register_handler(move |kick: Kick| {
+            main_kicked.set(kick.connection_id);
+            kick.connection_id != 0
+        });
+
+        let handle = dispatcher.handle();
+        let worker = std::thread::spawn(move || {
+            let sum = handle.post_with_reply(|| 1 + 2);
+            let kick = handle.send(Kick { connection_id: 7 });
+            (sum.wait(), kick.wait())
+        });
+
+        // 模拟主循环逐帧处理
+        while !worker.is_finished() {
+            dispatcher.drain();
+            std::thread::yield_now();
+        }
+        assert_eq!(worker.join().unwrap(), (Some(3), Some(true)));
+        assert_eq!(kicked.get(), 7);
+
+        // 未注册处理函数时返回 None
+        assert!(dispatcher.unregister_handler::<Kick>());
+        let reply = dispatcher.handle().send(Kick { connection_id: 1 });
+        assert_eq!(dispatcher.drain(), 1);
+        assert_eq!(reply.wait(), None);
+    }
+
+    #[test]
+    fn test_close() {
+        let mut dispatcher = MainThreadDispatcherStatic::new();
+        dispatcher.bind_loop_thread();
+        let handle = dispatcher.handle();
+        let pending = handle.post_with_reply(|| 1);
+
+        // 关闭时丢弃未执行的工作，之后的投递失败
+        dispatcher.close();
+        assert_eq!(pending.wait(), None);
+        assert!(!handle.post(|| ()));
+        assert_eq!(handle.send(Kick { connection_id: 1 }).wait(), None);
+        assert_eq!(dispatcher.drain(), 0);
+
+        // 不在主循环线程上获取的句柄不会被执行
+        let handle = std::thread::spawn(|| MainThreadDispatcher.handle()).join().unwrap();
+        assert!(!handle.post(|| ()));
+    }
+}
@@ -16,7 +16,23 @@ pub use mono_behaviour_factory::*;
 mod transform;
 pub use transform::*;
 
+mod color;
+pub use color::*;
+
+mod geometry;
+pub use geometry::*;
+
 mod components;
+
+mod ai;
+pub use ai::*;
+
+mod coroutine;
+pub use coroutine::*;
+
+mod main_thread_dispatcher;
+pub use main_thread_dispatcher::*;
+
 mod player_looper;
 pub use player_looper::PlayerLooper;
 
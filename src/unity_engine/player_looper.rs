@@ -1,56 +1,232 @@
-use crate::mirror::NetworkLoop;
+use crate::commons::thread_static;
+use crate::mirror::{NetworkLoop, NetworkManager, NetworkServer, NetworkTime, TransportManager};
+use crate::unity_engine::coroutine::set_scheduler_time;
+use crate::unity_engine::main_thread_dispatcher::MainThreadDispatcher;
 use crate::unity_engine::time::Time;
 use crate::unity_engine::world::WorldManager;
-use std::time::Instant;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering::SeqCst;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+thread_static! {
+    // 每个主循环线程 (服务器实例) 各自的停止标志
+    static mut STOP_REQUESTED: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+}
+
+/// 停止 PlayerLooper::run 的句柄，可以克隆到其他线程
+#[derive(Clone)]
+pub struct StopHandle(Arc<AtomicBool>);
+
+impl StopHandle {
+    /// 请求停止，当前帧结束后关闭服务器并让 run 返回
+    /// 只写一个原子变量，可以在信号处理函数中调用
+    pub fn stop(&self) {
+        self.0.store(true, SeqCst)
+    }
+
+    pub fn is_stop_requested(&self) -> bool {
+        self.0.load(SeqCst)
+    }
+
+    /// 底层标志位，可直接交给 signal_hook::flag::register 等注册 SIGINT / SIGTERM
+    pub fn flag(&self) -> Arc<AtomicBool> {
+        self.0.clone()
+    }
+}
 
 pub struct PlayerLooper {
-    last_frame_time: Instant,
-    last_fixed_time: Instant,
+    // 上一次 Update / FixedUpdate 的计划时间 (unscaled)
+    last_frame_time: Duration,
+    last_fixed_time: Duration,
+    // 上一次 Update 的实际时间，用于计算 delta_time
+    last_frame_at: Duration,
 }
 
 impl PlayerLooper {
+    /// 运行主循环，直到通过 StopHandle 请求停止，随后执行 shutdown 并返回
+    /// 已通过 new_virtual 启动虚拟时钟时按虚拟时钟运行，不会 sleep
     pub fn run() {
+        if !Time::is_virtual() {
+            Time::start_instant();
+        }
+        Self::new()._run();
+        Self::shutdown();
+        Self::stop_requested().store(false, SeqCst);
+    }
+
+    /// 在新线程上运行一个独立的服务器实例，拥有自己的连接、对象、场景和元数据
+    /// setup 在该线程上、主循环开始前执行，用于加载元数据、设置端口和初始化 NetworkManager
+    pub fn spawn(
+        name: &str,
+        setup: impl FnOnce() + Send + 'static,
+    ) -> std::io::Result<(std::thread::JoinHandle<()>, StopHandle)> {
+        let stop_requested = Arc::new(AtomicBool::new(false));
+        let thread_stop_requested = stop_requested.clone();
+        let join_handle = std::thread::Builder::new().name(name.to_string()).spawn(move || {
+            #[allow(static_mut_refs)]
+            unsafe {
+                *STOP_REQUESTED = thread_stop_requested;
+            }
+            MainThreadDispatcher.bind_loop_thread();
+            setup();
+            Self::run();
+        })?;
+        Ok((join_handle, StopHandle(stop_requested)))
+    }
+
+    /// 在运行 run 的线程上、调用 run 之前获取停止句柄
+    pub fn stop_handle() -> StopHandle {
+        MainThreadDispatcher.bind_loop_thread();
+        StopHandle(Self::stop_requested())
+    }
+
+    fn stop_requested() -> Arc<AtomicBool> {
+        #[allow(static_mut_refs)]
+        unsafe {
+            STOP_REQUESTED.clone()
+        }
+    }
+
+    /// 断开所有客户端，取消生成并销毁所有对象 (on_destroy)，最后停止传输层
+    /// 未执行的投递工作被丢弃，之后的投递失败
+    pub fn shutdown() {
+        log::info!("PlayerLooper shutting down");
+        MainThreadDispatcher.close();
+        // 通过 NetworkManager 停止，触发 on_stop_server
+        NetworkManager::singleton::<NetworkManager, _>(|network_manager| network_manager.stop_server());
+        if NetworkServer.active {
+            NetworkServer.shutdown();
+        }
+        WorldManager::destroy_all();
+        if TransportManager.is_initialized() {
+            TransportManager.active.shutdown();
+        }
+    }
+
+    /// 使用虚拟时钟创建，时间只随 step / advance 推进，不会 sleep
+    /// 会先重置 Time 和 NetworkTime
+    pub fn new_virtual() -> Self {
+        Self::reset();
+        Time::start_virtual();
+        Self::new()
+    }
+
+    fn new() -> Self {
+        MainThreadDispatcher.bind_loop_thread();
+        let now = Time::unscaled_time_duration();
         PlayerLooper {
-            last_frame_time: Instant::now(),
-            last_fixed_time: Instant::now(),
+            last_frame_time: now,
+            last_fixed_time: now,
+            last_frame_at: now,
+        }
+    }
+
+    /// 重置时钟，同一进程中的多个测试可以从头开始
+    pub fn reset() {
+        Time::reset();
+        NetworkTime.reset_statics();
+    }
+
+    pub fn fixed_update(&mut self) -> bool {
+        if !Self::due(&mut self.last_fixed_time, Time::get_fixed_data_time_duration()) {
+            return false;
+        }
+        // 按 time_scale 决定本次执行几次 FixedUpdate，为 0 时暂停
+        Time::accumulate_fixed_time();
+        while Time::begin_fixed_step() {
+            set_scheduler_time(Time::time());
+            WorldManager::fixed_update();
+            Time::end_fixed_step();
+        }
+        true
+    }
+
+    pub fn frame_update(&mut self) -> bool {
+        if !Self::due(&mut self.last_frame_time, Time::get_frame_rate_duration()) {
+            return false;
         }
-            ._run()
+        let now = Time::unscaled_time_duration();
+        Time::begin_frame(now - self.last_frame_at);
+        self.last_frame_at = now;
+        NetworkLoop.network_early_update(); // TODO 补充注册逻辑
+        // 其他线程投递的工作在网络消息之后、Update 之前执行
+        MainThreadDispatcher.drain();
+        set_scheduler_time(Time::time());
+        WorldManager::update();
+        WorldManager::late_update();
+        NetworkLoop.network_late_update(); // TODO 补充注册逻辑
+        true
     }
 
-    pub fn fixed_update(&mut self) {
-        if let Some(elapsed) = self
-            .last_fixed_time
-            .checked_sub(Time::unscaled_time().elapsed())
-        {
-            if elapsed.elapsed() >= Time::get_fixed_data_time_duration() {
-                self.last_fixed_time = Instant::now();
-                WorldManager::fixed_update();
+    // 按固定间隔排期，不因循环间隔不齐而丢帧；落后超过一个间隔时不补帧
+    fn due(last_time: &mut Duration, interval: Duration) -> bool {
+        let now = Time::unscaled_time_duration();
+        if now < *last_time + interval {
+            return false;
+        }
+        *last_time += interval;
+        if now >= *last_time + interval {
+            *last_time = now;
+        }
+        true
+    }
+
+    /// 推进虚拟时钟并执行 frames 帧 Update
+    pub fn step(&mut self, frames: u32) {
+        if !Time::is_virtual() {
+            log::error!("PlayerLooper::step requires a virtual clock, use PlayerLooper::new_virtual");
+            return;
+        }
+        for _ in 0..frames {
+            loop {
+                // 推进到下一次 Update 或 FixedUpdate
+                let now = Time::unscaled_time_duration();
+                let until_frame = (self.last_frame_time + Time::get_frame_rate_duration()).saturating_sub(now);
+                let until_fixed = (self.last_fixed_time + Time::get_fixed_data_time_duration()).saturating_sub(now);
+                Time::advance(until_frame.min(until_fixed));
+                if self.tick() {
+                    break;
+                }
             }
         }
     }
 
-    pub fn frame_update(&mut self) {
-        if let Some(elapsed) = self
-            .last_frame_time
-            .checked_sub(Time::unscaled_time().elapsed())
-        {
-            if elapsed.elapsed() >= Time::get_frame_rate_duration() {
-                self.last_frame_time = Instant::now();
-                NetworkLoop.network_early_update(); // TODO 补充注册逻辑
-                WorldManager::update();
-                WorldManager::late_update();
-                NetworkLoop.network_late_update(); // TODO 补充注册逻辑
+    /// 推进虚拟时钟 duration，期间按真实循环的节奏执行 Update 和 FixedUpdate
+    pub fn advance(&mut self, duration: Duration) {
+        if !Time::is_virtual() {
+            log::error!("PlayerLooper::advance requires a virtual clock, use PlayerLooper::new_virtual");
+            return;
+        }
+        let end = Time::unscaled_time_duration() + duration;
+        loop {
+            let now = Time::unscaled_time_duration();
+            if now >= end {
+                break;
             }
+            Time::advance(Time::get_min_interval().min(end - now));
+            self.tick();
         }
     }
 
+    // 执行一次循环，返回是否执行了 Update
+    fn tick(&mut self) -> bool {
+        self.fixed_update();
+        let updated = self.frame_update();
+        Time::frame_add();
+        updated
+    }
+
     fn _run(&mut self) {
-        Time::start_instant();
-        loop {
+        let stop_requested = Self::stop_requested();
+        while !stop_requested.load(SeqCst) {
+            if Time::is_virtual() {
+                Time::advance(Time::get_min_interval());
+                self.tick();
+                continue;
+            }
             let tmp_instant = Instant::now();
-            self.fixed_update();
-            self.frame_update();
-            Time::frame_add();
+            self.tick();
 
             let elapsed = tmp_instant.elapsed();
             if elapsed < Time::get_min_interval() {
@@ -60,3 +236,216 @@ impl PlayerLooper {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commons::action::SelfMutAction;
+    use crate::commons::RevelArc;
+    use crate::mirror::{CallbackProcessor, Transport, TransportChannel, TransportManager};
+
+    struct NullTransport;
+
+    impl Transport for NullTransport {
+        fn init(&mut self, _: CallbackProcessor) {}
+        fn available(&self) -> bool {
+            true
+        }
+        fn server_uri(&self) -> http::Uri {
+            http::Uri::from_static("null://localhost")
+        }
+        fn server_active(&self) -> bool {
+            false
+        }
+        fn server_start(&mut self, _: (&str, u16)) {}
+        fn server_send(&self, _: u64, _: &[u8], _: TransportChannel) {}
+        fn server_disconnect(&self, _: u64) {}
+        fn server_get_client_address(&self, _: u64) -> Option<String> {
+            None
+        }
+        fn server_stop(&self) {}
+        fn get_max_packet_size(&self, _: TransportChannel) -> usize {
+            1200
+        }
+        fn server_early_update(&self) {}
+        fn server_late_update(&self) {}
+        fn shutdown(&self) {}
+    }
+
+    #[derive(Default)]
+    struct FrameCounter {
+        early: u32,
+        late: u32,
+    }
+
+    #[test]
+    fn test_step_and_advance() {
+        TransportManager.active = RevelArc::new(Box::new(NullTransport) as Box<dyn Transport>).into();
+        let counter = RevelArc::new(Box::new(FrameCounter::default()));
+        NetworkLoop.append_early_update_handler(SelfMutAction::new(
+            counter.downgrade(),
+            |counter: &mut FrameCounter| counter.early += 1,
+        ));
+        NetworkLoop.append_late_update_handler(SelfMutAction::new(
+            counter.downgrade(),
+            |counter: &mut FrameCounter| counter.late += 1,
+        ));
+
+        let mut looper = PlayerLooper::new_virtual();
+        looper.step(3);
+        assert_eq!((counter.early, counter.late), (3, 3));
+        assert_eq!(Time::unscaled_time_duration(), Time::get_frame_rate_duration() * 3);
+        assert!((Time::time() - 3.0 / 60.0).abs() < 1e-6);
+        assert!((Time::delta_time() - 1.0 / 60.0).abs() < 1e-6);
+
+        // 慢放只影响游戏时钟
+        Time::set_time_scale(0.5);
+        looper.step(2);
+        assert!((Time::time() - 4.0 / 60.0).abs() < 1e-6);
+        assert!((NetworkTime.local_time() - 5.0 / 60.0).abs() < 1e-6);
+
+        Time::set_time_scale(1.0);
+        let fixed_time = Time::fixed_time();
+        looper.advance(Duration::from_secs(1));
+        assert!((Time::fixed_time() - fixed_time - 1.0).abs() < 1e-4);
+        assert!((59..=60).contains(&(counter.early - 5)));
+
+        // 慢放时 FixedUpdate 的步长不变，频率减半
+        Time::set_time_scale(0.5);
+        let fixed_time = Time::fixed_time();
+        looper.advance(Duration::from_secs(1));
+        assert!((Time::fixed_time() - fixed_time - 0.5).abs() < 1e-4);
+        assert_eq!(Time::fixed_delta_time(), Time::get_fixed_data_time_duration().as_secs_f32());
+        Time::set_time_scale(1.0);
+
+        // 重置后从头开始
+        PlayerLooper::new_virtual();
+        assert_eq!(Time::get_frame_count(), 0);
+        assert_eq!(Time::time(), 0.0);
+    }
+
+    #[test]
+    fn test_run_and_shutdown() {
+        use crate::metadata_settings::metadata_prefab::MetadataPrefab;
+        use crate::mirror::{listen_in_scenes, network_prefab, TestTransport};
+        use crate::unity_engine::GameObject;
+
+        PlayerLooper::new_virtual();
+        let transport = listen_in_scenes(vec![("Assets/Scenes/Test.unity", vec![])]);
+        let connections = [transport.connect(1), transport.connect(2)];
+        for connection in connections.iter() {
+            NetworkServer::set_client_ready(connection.clone());
+        }
+
+        let prefab = network_prefab("Pickup", &[]).build().unwrap();
+        let spawned = GameObject::instantiate(&prefab).downgrade();
+        NetworkServer::spawn(spawned.clone());
+        assert_eq!(NetworkServer.spawned.len(), 1);
+
+        // 0.5 秒后在主循环中请求停止
+        let stop_handle = PlayerLooper::stop_handle();
+        let mut timer = GameObject::instantiate(&MetadataPrefab::builder("Timer").build().unwrap());
+        timer.invoke(0.5, move || stop_handle.stop());
+        drop(timer);
+
+        let dispatcher = MainThreadDispatcher.handle();
+        PlayerLooper::run();
+        assert!(Time::time() >= 0.5);
+        assert!(!dispatcher.post(|| ()));
+        assert!(!PlayerLooper::stop_handle().is_stop_requested());
+
+        // 断开所有连接，销毁生成的对象和所有场景
+        assert!(!NetworkServer.active);
+        assert!(NetworkServer.connections.is_empty());
+        assert!(NetworkServer.spawned.is_empty());
+        let mut disconnected = transport.disconnected();
+        disconnected.sort();
+        assert_eq!(disconnected, vec![1, 2]);
+        for connection in connections.iter() {
+            assert_eq!(connection.disconnect_reason.as_deref(), Some("Server shutdown"));
+        }
+        assert!(!spawned.upgradable());
+        assert!(!WorldManager::active_world().upgradable());
+        assert!(WorldManager::root_game_objects().is_empty());
+        assert!(WorldManager::all_root_game_objects().is_empty());
+
+        // NetworkManager::stop_server 同样断开连接并关闭服务器
+        let transport = TestTransport::listen();
+        let connection = transport.connect(3);
+        NetworkManager::default().stop_server();
+        assert!(!NetworkServer.active);
+        assert_eq!(transport.disconnected(), vec![3]);
+        assert_eq!(connection.disconnect_reason.as_deref(), Some("Server shutdown"));
+    }
+
+    #[test]
+    fn test_isolated_instances() {
+        use crate::metadata_settings::metadata_prefab::MetadataPrefab;
+        use crate::metadata_settings::Metadata;
+        use crate::mirror::{listen_in_scenes, network_prefab};
+        use crate::unity_engine::main_thread_dispatcher::DispatcherHandle;
+        use crate::unity_engine::GameObject;
+        use std::sync::mpsc::channel;
+
+        // 每个实例加载自己的场景并监听，A 再接入一个连接并生成对象
+        let spawn = |port: u16, scene_path: &'static str, populate: bool| {
+            let (handle_sender, handle_receiver) = channel::<DispatcherHandle>();
+            let (server, stop) = PlayerLooper::spawn(&format!("server-{}", port), move || {
+                // 每个线程都是全新的服务器状态
+                assert_eq!(NetworkServer.port(), 7777);
+                assert!(!TransportManager.is_initialized());
+                NetworkServer.set_address("0.0.0.0", port);
+                let transport = listen_in_scenes(vec![(scene_path, vec![MetadataPrefab::builder("Ground").build().unwrap()])]);
+                if populate {
+                    let connection = transport.connect(1);
+                    NetworkServer::set_client_ready(connection);
+                    let prefab = network_prefab("Pickup", &[]).build().unwrap();
+                    NetworkServer::spawn(GameObject::instantiate(&prefab).downgrade());
+                }
+                handle_sender.send(MainThreadDispatcher.handle()).unwrap();
+            })
+            .unwrap();
+            (server, stop, handle_receiver.recv().unwrap())
+        };
+        let (server_a, stop_a, handle_a) = spawn(7001, "Assets/Scenes/A.unity", true);
+        let (server_b, stop_b, handle_b) = spawn(7002, "Assets/Scenes/B.unity", false);
+
+        // 在各自的主循环中读取状态
+        let snapshot = |handle: &DispatcherHandle| {
+            handle
+                .post_with_reply(|| {
+                    let metadata = Metadata::current();
+                    let mut scenes = metadata.scenes.keys().cloned().collect::<Vec<_>>();
+                    scenes.sort();
+                    (
+                        NetworkServer.port(),
+                        NetworkServer.spawned.len(),
+                        NetworkServer.connections.keys().copied().collect::<Vec<_>>(),
+                        WorldManager::active_world().get().map(|world| world.get_scene_path()),
+                        WorldManager::root_game_objects().len(),
+                        scenes,
+                    )
+                })
+                .wait()
+                .unwrap()
+        };
+        let a = snapshot(&handle_a);
+        let b = snapshot(&handle_b);
+        assert_eq!(a.0, 7001);
+        assert_eq!((a.1, a.2), (1, vec![1]));
+        assert_eq!(a.3.as_deref(), Some("Assets/Scenes/A.unity"));
+        assert_eq!(a.4, 2);
+        assert_eq!(a.5, vec!["Assets/Scenes/A.unity".to_string()]);
+        assert_eq!(b.0, 7002);
+        assert_eq!((b.1, b.2), (0, vec![]));
+        assert_eq!(b.3.as_deref(), Some("Assets/Scenes/B.unity"));
+        assert_eq!(b.4, 1);
+        assert_eq!(b.5, vec!["Assets/Scenes/B.unity".to_string()]);
+
+        stop_a.stop();
+        stop_b.stop();
+        server_a.join().unwrap();
+        server_b.join().unwrap();
+        assert_eq!(NetworkServer.port(), 7777);
+    }
+}
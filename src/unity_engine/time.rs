@@ -1,43 +1,122 @@
-use once_cell::sync::Lazy;
+use crate::commons::thread_static;
 use std::sync::atomic::Ordering::SeqCst;
 use std::sync::atomic::{AtomicU16, AtomicU64};
 use std::time::{Duration, Instant};
 
-static mut START_INSTANT: Lazy<Option<Instant>> = Lazy::new(|| None); // 游戏开始时的时间
-static mut FRAME_RATE: AtomicU16 = AtomicU16::new(60); // 60 FPS
-static mut FRAME_COUNT: AtomicU64 = AtomicU64::new(0); // 帧计数器
-static mut FIXED_DATA_TIME: AtomicU64 = AtomicU64::new(20); // 20 ms
+thread_static! {
+    static mut CLOCK: Option<Clock> = None; // 游戏开始时的时间
+    static mut FRAME_RATE: AtomicU16 = AtomicU16::new(60); // 60 FPS
+    static mut FRAME_COUNT: AtomicU64 = AtomicU64::new(0); // 帧计数器
+    static mut FIXED_DATA_TIME: AtomicU64 = AtomicU64::new(20); // 20 ms
+    static mut FRAME_TIMING: FrameTiming = FrameTiming::default(); // 游戏时钟
+}
 static mut DEFAULT_PING_INTERVAL: f32 = 0.1; // 默认的ping间隔时间（单位：秒）
 static mut PING_WINDOW_SIZE: u32 = 50; // Ping窗口大小
 
+// 时间源
+enum Clock {
+    // 真实时间，记录开始时刻
+    Real(Instant),
+    // 虚拟时间，只能手动推进，用于测试
+    Virtual(Duration),
+}
+
+// 可缩放的游戏时钟，只影响游戏逻辑，NetworkTime 和传输层仍使用 unscaled 时间
+struct FrameTiming {
+    time_scale: f32,
+    maximum_delta_time: f32,
+    // 当前帧
+    unscaled_delta_time: f32,
+    delta_time: f32,
+    smooth_delta_time: ExponentialMovingAverage,
+    time: f64,
+    // 当前 FixedUpdate
+    fixed_time: f64,
+    // 按 time_scale 累计、还未执行 FixedUpdate 的游戏时间
+    fixed_accumulator: f32,
+    in_fixed_time_step: bool,
+}
+
+impl Default for FrameTiming {
+    fn default() -> Self {
+        Self {
+            time_scale: 1.0,
+            maximum_delta_time: 1.0 / 3.0,
+            unscaled_delta_time: 0.0,
+            delta_time: 0.0,
+            smooth_delta_time: ExponentialMovingAverage::new(10),
+            time: 0.0,
+            fixed_time: 0.0,
+            fixed_accumulator: 0.0,
+            in_fixed_time_step: false,
+        }
+    }
+}
+
 pub struct Time;
 
 #[allow(static_mut_refs)]
 #[allow(unused)]
 impl Time {
     pub fn start_instant() {
+        Self::start(Clock::Real(Instant::now()))
+    }
+
+    /// 使用虚拟时钟启动，时间只随 advance 推进
+    pub fn start_virtual() {
+        Self::start(Clock::Virtual(Duration::ZERO))
+    }
+
+    fn start(clock: Clock) {
         unsafe {
-            if START_INSTANT.is_none() {
-                *START_INSTANT = Some(Instant::now())
-            } else {
-                panic!("Time has already started");
+            if CLOCK.is_some() {
+                log::warn!("Time has already started, call Time::reset first");
+                return;
             }
+            *CLOCK = Some(clock)
         }
     }
 
-    pub fn unscaled_time() -> Instant {
+    pub fn is_started() -> bool {
+        unsafe { CLOCK.is_some() }
+    }
+
+    pub fn is_virtual() -> bool {
+        unsafe { matches!(*CLOCK, Some(Clock::Virtual(_))) }
+    }
+
+    /// 推进虚拟时钟
+    pub fn advance(duration: Duration) {
         unsafe {
-            match *START_INSTANT {
+            match CLOCK.as_mut() {
+                Some(Clock::Virtual(elapsed)) => *elapsed += duration,
+                _ => log::error!("Time::advance requires a virtual clock, call Time::start_virtual first"),
+            }
+        }
+    }
+
+    /// 清空时钟、帧计数和游戏时钟，恢复默认设置
+    pub fn reset() {
+        unsafe {
+            *CLOCK = None;
+            FRAME_RATE.store(60, SeqCst);
+            FRAME_COUNT.store(0, SeqCst);
+            FIXED_DATA_TIME.store(20, SeqCst);
+            *FRAME_TIMING = FrameTiming::default();
+        }
+    }
+
+    pub fn unscaled_time_duration() -> Duration {
+        unsafe {
+            match *CLOCK {
                 None => {
                     panic!("Time has not started yet");
                 }
-                Some(start_instant) => start_instant,
+                Some(Clock::Real(start_instant)) => start_instant.elapsed(),
+                Some(Clock::Virtual(elapsed)) => elapsed,
             }
         }
     }
-    pub fn unscaled_time_duration() -> Duration {
-        Self::unscaled_time().elapsed()
-    }
 
     pub fn unscaled_time_f64() -> f64 {
         Self::unscaled_time_duration().as_secs_f64()
@@ -87,6 +166,123 @@ impl Time {
         }
     }
 
+    /// 上一帧到当前帧的游戏时间 (秒)，FixedUpdate 中返回 fixed_delta_time
+    pub fn delta_time() -> f32 {
+        unsafe {
+            match FRAME_TIMING.in_fixed_time_step {
+                true => Self::fixed_delta_time(),
+                false => FRAME_TIMING.delta_time,
+            }
+        }
+    }
+
+    /// 上一帧到当前帧的真实时间 (秒)，不受 time_scale 影响
+    pub fn unscaled_delta_time() -> f32 {
+        unsafe {
+            match FRAME_TIMING.in_fixed_time_step {
+                true => Self::fixed_unscaled_delta_time(),
+                false => FRAME_TIMING.unscaled_delta_time,
+            }
+        }
+    }
+
+    /// 平滑后的 delta_time
+    pub fn smooth_delta_time() -> f32 {
+        unsafe { FRAME_TIMING.smooth_delta_time.value as f32 }
+    }
+
+    /// 每次 FixedUpdate 推进的游戏时间 (秒)，不随 time_scale 变化，time_scale 只影响 FixedUpdate 的频率
+    pub fn fixed_delta_time() -> f32 {
+        Self::fixed_unscaled_delta_time()
+    }
+
+    pub fn fixed_unscaled_delta_time() -> f32 {
+        Self::get_fixed_data_time_duration().as_secs_f32()
+    }
+
+    /// 游戏开始以来的游戏时间 (秒)，FixedUpdate 中返回 fixed_time
+    pub fn time() -> f64 {
+        unsafe {
+            match FRAME_TIMING.in_fixed_time_step {
+                true => FRAME_TIMING.fixed_time,
+                false => FRAME_TIMING.time,
+            }
+        }
+    }
+
+    pub fn fixed_time() -> f64 {
+        unsafe { FRAME_TIMING.fixed_time }
+    }
+
+    pub fn in_fixed_time_step() -> bool {
+        unsafe { FRAME_TIMING.in_fixed_time_step }
+    }
+
+    pub fn time_scale() -> f32 {
+        unsafe { FRAME_TIMING.time_scale }
+    }
+
+    /// 0 暂停游戏世界，小于 1 慢放
+    pub fn set_time_scale(time_scale: f32) {
+        if !time_scale.is_finite() || time_scale < 0.0 {
+            log::warn!("Time scale must be a non-negative number, got {}", time_scale);
+            return;
+        }
+        unsafe {
+            FRAME_TIMING.time_scale = time_scale;
+        }
+    }
+
+    pub fn maximum_delta_time() -> f32 {
+        unsafe { FRAME_TIMING.maximum_delta_time }
+    }
+
+    /// 单帧 delta_time 的上限，避免卡顿后游戏逻辑跳跃
+    pub fn set_maximum_delta_time(maximum_delta_time: f32) {
+        unsafe {
+            FRAME_TIMING.maximum_delta_time = maximum_delta_time.max(Self::fixed_unscaled_delta_time());
+        }
+    }
+
+    pub(crate) fn begin_frame(unscaled_delta_time: Duration) {
+        unsafe {
+            let unscaled_delta_time = unscaled_delta_time.as_secs_f32();
+            let delta_time = unscaled_delta_time.min(FRAME_TIMING.maximum_delta_time) * FRAME_TIMING.time_scale;
+            FRAME_TIMING.unscaled_delta_time = unscaled_delta_time;
+            FRAME_TIMING.delta_time = delta_time;
+            FRAME_TIMING.smooth_delta_time.add(delta_time as f64);
+            FRAME_TIMING.time += delta_time as f64;
+        }
+    }
+
+    // 每个真实的固定间隔调用一次，按 time_scale 累计游戏时间，上限为 maximum_delta_time
+    pub(crate) fn accumulate_fixed_time() {
+        unsafe {
+            let accumulated = FRAME_TIMING.fixed_accumulator + Self::fixed_delta_time() * FRAME_TIMING.time_scale;
+            FRAME_TIMING.fixed_accumulator = accumulated.min(FRAME_TIMING.maximum_delta_time.max(Self::fixed_delta_time()));
+        }
+    }
+
+    // 累计的游戏时间够一个固定步长时开始 FixedUpdate，返回是否开始
+    pub(crate) fn begin_fixed_step() -> bool {
+        unsafe {
+            let fixed_delta_time = Self::fixed_delta_time();
+            if FRAME_TIMING.fixed_accumulator < fixed_delta_time {
+                return false;
+            }
+            FRAME_TIMING.fixed_accumulator -= fixed_delta_time;
+            FRAME_TIMING.fixed_time += fixed_delta_time as f64;
+            FRAME_TIMING.in_fixed_time_step = true;
+            true
+        }
+    }
+
+    pub(crate) fn end_fixed_step() {
+        unsafe {
+            FRAME_TIMING.in_fixed_time_step = false;
+        }
+    }
+
     pub fn ping_interval() -> f64 {
         unsafe { DEFAULT_PING_INTERVAL as f64 }
     }
@@ -1,10 +1,21 @@
 #![allow(unused)]
+use crate::commons::RevelArc;
 use crate::commons::RevelWeak;
 use crate::metadata_settings::metadata_transform::MetadataTransform;
 use crate::unity_engine::GameObject;
-use nalgebra::{Matrix3, Matrix4, Quaternion, Translation3, UnitQuaternion, Vector3};
+use nalgebra::{Matrix3, Matrix4, Point3, Quaternion, Translation3, UnitQuaternion, Vector3};
+use std::cell::Cell;
 
-#[derive(Default, Clone, Debug)]
+/// 旋转、平移所参考的坐标系
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Space {
+    World,
+    /// 对应 Unity 的 Space.Self
+    #[default]
+    Local,
+}
+
+#[derive(Clone, Debug)]
 pub struct Transform {
     pub instance_id: i32,
 
@@ -12,31 +23,59 @@ pub struct Transform {
     pub children: Vec<RevelWeak<Transform>>,
 
     pub game_object: RevelWeak<GameObject>,
-    pub position: Vector3<f32>,
-    pub local_position: Vector3<f32>,
-    pub rotation: Quaternion<f32>,
-    pub local_rotation: Quaternion<f32>,
-    pub local_scale: Vector3<f32>,
+    self_weak: RevelWeak<Transform>,
+
+    local_position: Vector3<f32>,
+    local_rotation: Quaternion<f32>,
+    local_scale: Vector3<f32>,
+
+    // 世界空间缓存，局部值或父节点变化时整棵子树标记为脏，读取时再重新计算
+    dirty: Cell<bool>,
+    world_position: Cell<Vector3<f32>>,
+    world_rotation: Cell<Quaternion<f32>>,
+    local_to_world: Cell<Matrix4<f32>>,
 }
 
-impl Transform {
-    pub fn new_with_metadata(metadata: &MetadataTransform) -> Self {
+impl Default for Transform {
+    fn default() -> Self {
         Transform {
-            instance_id: metadata.instance_id,
+            instance_id: 0,
             parent: RevelWeak::default(),
             children: vec![],
             game_object: RevelWeak::default(),
-            position: Vector3::new(
-                metadata.position[0],
-                metadata.position[1],
-                metadata.position[2],
-            ),
-            rotation: Quaternion::new(
-                metadata.rotation[3],
-                metadata.rotation[0],
-                metadata.rotation[1],
-                metadata.rotation[2],
-            ),
+            self_weak: RevelWeak::default(),
+            local_position: Vector3::zeros(),
+            local_rotation: Quaternion::identity(),
+            local_scale: Vector3::new(1.0, 1.0, 1.0),
+            dirty: Cell::new(false),
+            world_position: Cell::new(Vector3::zeros()),
+            world_rotation: Cell::new(Quaternion::identity()),
+            local_to_world: Cell::new(Matrix4::identity()),
+        }
+    }
+}
+
+impl Transform {
+    pub fn new_with_metadata(metadata: &MetadataTransform) -> Self {
+        let position = Vector3::new(
+            metadata.position[0],
+            metadata.position[1],
+            metadata.position[2],
+        );
+        let rotation = Quaternion::new(
+            metadata.rotation[3],
+            metadata.rotation[0],
+            metadata.rotation[1],
+            metadata.rotation[2],
+        );
+        let local_scale = Vector3::new(
+            metadata.local_scale[0],
+            metadata.local_scale[1],
+            metadata.local_scale[2],
+        );
+        // 未挂到层级中之前，直接使用导出的世界坐标
+        Transform {
+            instance_id: metadata.instance_id,
             local_position: Vector3::new(
                 metadata.local_position[0],
                 metadata.local_position[1],
@@ -48,13 +87,317 @@ impl Transform {
                 metadata.local_rotation[1],
                 metadata.local_rotation[2],
             ),
-            local_scale: Vector3::new(
-                metadata.local_scale[0],
-                metadata.local_scale[1],
-                metadata.local_scale[2],
+            local_scale,
+            world_position: Cell::new(position),
+            world_rotation: Cell::new(rotation),
+            local_to_world: Cell::new(Self::trs(&position, &rotation, &local_scale)),
+            ..Default::default()
+        }
+    }
+
+    /// 包装为 RevelArc，之后才能参与层级 (set_parent)
+    pub fn into_arc(self) -> RevelArc<Transform> {
+        let mut arc_transform = RevelArc::new(self);
+        arc_transform.self_weak = arc_transform.downgrade();
+        arc_transform
+    }
+
+    fn trs(
+        position: &Vector3<f32>,
+        rotation: &Quaternion<f32>,
+        scale: &Vector3<f32>,
+    ) -> Matrix4<f32> {
+        let translation = Translation3::from(*position).to_homogeneous();
+        let rotation = UnitQuaternion::from_quaternion(*rotation).to_homogeneous();
+        let scale = Matrix4::new_nonuniform_scaling(scale);
+        translation * rotation * scale
+    }
+}
+
+// 层级
+impl Transform {
+    pub fn self_weak(&self) -> RevelWeak<Transform> {
+        self.self_weak.clone()
+    }
+
+    /// 修改父节点。world_position_stays 为 true 时保持世界空间的位置、旋转和缩放不变
+    pub fn set_parent(&mut self, parent: RevelWeak<Transform>, world_position_stays: bool) {
+        if parent.ptr_eq(&self.parent) {
+            return;
+        }
+        if let Some(new_parent) = parent.get() {
+            if new_parent.is_child_of(&self.self_weak) {
+                log::error!(
+                    "Transform {}: can not set parent to itself or one of its children.",
+                    self.instance_id
+                );
+                return;
+            }
+        }
+
+        let position = self.position();
+        let rotation = self.rotation();
+        let lossy_scale = self.lossy_scale();
+
+        if let Some(old_parent) = self.parent.get() {
+            old_parent
+                .children
+                .retain(|child| !child.ptr_eq(&self.self_weak));
+        }
+        if let Some(new_parent) = parent.get() {
+            new_parent.children.push(self.self_weak.clone());
+        }
+        self.parent = parent;
+
+        if world_position_stays {
+            let parent_scale = self
+                .parent
+                .get()
+                .map(|parent| parent.lossy_scale())
+                .unwrap_or(Vector3::new(1.0, 1.0, 1.0));
+            self.local_scale = lossy_scale.component_div(&parent_scale.map(|v| {
+                if v.abs() < f32::EPSILON {
+                    1.0
+                } else {
+                    v
+                }
+            }));
+            self.mark_dirty();
+            self.set_position_and_rotation(position, rotation);
+        } else {
+            self.mark_dirty();
+        }
+    }
+
+    /// parent 是否为自身或祖先
+    pub fn is_child_of(&self, parent: &RevelWeak<Transform>) -> bool {
+        if self.self_weak.ptr_eq(parent) {
+            return true;
+        }
+        match self.parent.get() {
+            None => false,
+            Some(self_parent) => self_parent.is_child_of(parent),
+        }
+    }
+
+    pub fn root(&self) -> RevelWeak<Transform> {
+        match self.parent.get() {
+            None => self.self_weak.clone(),
+            Some(parent) => parent.root(),
+        }
+    }
+
+    fn mark_dirty(&self) {
+        // 脏节点的子树一定也是脏的
+        if self.dirty.get() {
+            return;
+        }
+        self.dirty.set(true);
+        for child in self.children.iter() {
+            if let Some(child) = child.get() {
+                child.mark_dirty();
+            }
+        }
+    }
+
+    fn recompute(&self) {
+        if !self.dirty.get() {
+            return;
+        }
+        let local_matrix = Self::trs(&self.local_position, &self.local_rotation, &self.local_scale);
+        let (matrix, rotation) = match self.parent.get() {
+            None => (local_matrix, self.local_rotation),
+            Some(parent) => (
+                parent.local_to_world_matrix() * local_matrix,
+                parent.rotation() * self.local_rotation,
             ),
+        };
+        self.local_to_world.set(matrix);
+        self.world_rotation.set(rotation.normalize());
+        self.world_position
+            .set(Vector3::new(matrix[(0, 3)], matrix[(1, 3)], matrix[(2, 3)]));
+        self.dirty.set(false);
+    }
+}
+
+// 局部空间
+impl Transform {
+    pub fn local_position(&self) -> Vector3<f32> {
+        self.local_position
+    }
+
+    pub fn set_local_position(&mut self, local_position: Vector3<f32>) {
+        self.local_position = local_position;
+        self.mark_dirty();
+    }
+
+    pub fn local_rotation(&self) -> Quaternion<f32> {
+        self.local_rotation
+    }
+
+    pub fn set_local_rotation(&mut self, local_rotation: Quaternion<f32>) {
+        self.local_rotation = local_rotation;
+        self.mark_dirty();
+    }
+
+    pub fn local_scale(&self) -> Vector3<f32> {
+        self.local_scale
+    }
+
+    pub fn set_local_scale(&mut self, local_scale: Vector3<f32>) {
+        self.local_scale = local_scale;
+        self.mark_dirty();
+    }
+
+    pub fn local_euler_angles(&self) -> Vector3<f32> {
+        Self::quaternion_to_euler_angles(self.local_rotation)
+    }
+}
+
+// 世界空间
+impl Transform {
+    pub fn position(&self) -> Vector3<f32> {
+        self.recompute();
+        self.world_position.get()
+    }
+
+    pub fn set_position(&mut self, position: Vector3<f32>) {
+        self.local_position = match self.parent.get() {
+            None => position,
+            Some(parent) => parent.inverse_transform_point(position),
+        };
+        self.mark_dirty();
+    }
+
+    pub fn rotation(&self) -> Quaternion<f32> {
+        self.recompute();
+        self.world_rotation.get()
+    }
+
+    pub fn set_rotation(&mut self, rotation: Quaternion<f32>) {
+        self.local_rotation = match self.parent.get() {
+            None => rotation,
+            Some(parent) => parent.rotation().try_inverse().unwrap_or_default() * rotation,
+        };
+        self.mark_dirty();
+    }
+
+    pub fn set_position_and_rotation(&mut self, position: Vector3<f32>, rotation: Quaternion<f32>) {
+        self.set_position(position);
+        self.set_rotation(rotation);
+    }
+
+    /// 世界空间缩放，父节点带旋转且非等比缩放时只是近似值
+    pub fn lossy_scale(&self) -> Vector3<f32> {
+        let matrix = self.local_to_world_matrix();
+        Vector3::new(
+            matrix.fixed_view::<3, 1>(0, 0).norm(),
+            matrix.fixed_view::<3, 1>(0, 1).norm(),
+            matrix.fixed_view::<3, 1>(0, 2).norm(),
+        )
+    }
+
+    pub fn local_to_world_matrix(&self) -> Matrix4<f32> {
+        self.recompute();
+        self.local_to_world.get()
+    }
+
+    pub fn world_to_local_matrix(&self) -> Matrix4<f32> {
+        self.local_to_world_matrix()
+            .try_inverse()
+            .unwrap_or_else(Matrix4::identity)
+    }
+
+    pub fn forward(&self) -> Vector3<f32> {
+        self.transform_direction(Vector3::z())
+    }
+
+    pub fn right(&self) -> Vector3<f32> {
+        self.transform_direction(Vector3::x())
+    }
+
+    pub fn up(&self) -> Vector3<f32> {
+        self.transform_direction(Vector3::y())
+    }
+
+    /// 局部坐标点 -> 世界坐标点，受缩放影响
+    pub fn transform_point(&self, point: Vector3<f32>) -> Vector3<f32> {
+        self.local_to_world_matrix()
+            .transform_point(&Point3::from(point))
+            .coords
+    }
+
+    pub fn inverse_transform_point(&self, point: Vector3<f32>) -> Vector3<f32> {
+        self.world_to_local_matrix()
+            .transform_point(&Point3::from(point))
+            .coords
+    }
+
+    /// 局部方向 -> 世界方向，只受旋转影响
+    pub fn transform_direction(&self, direction: Vector3<f32>) -> Vector3<f32> {
+        UnitQuaternion::from_quaternion(self.rotation()) * direction
+    }
+
+    pub fn inverse_transform_direction(&self, direction: Vector3<f32>) -> Vector3<f32> {
+        UnitQuaternion::from_quaternion(self.rotation()).inverse() * direction
+    }
+
+    /// 使 forward 指向目标点
+    pub fn look_at(&mut self, target: Vector3<f32>, world_up: Vector3<f32>) {
+        let direction = target - self.position();
+        if direction.norm_squared() < f32::EPSILON {
+            return;
+        }
+        let rotation = UnitQuaternion::face_towards(&direction, &world_up);
+        self.set_rotation(*rotation.quaternion());
+    }
+
+    /// 按欧拉角 (度) 旋转，顺序与 Unity 的 Quaternion.Euler 一致 (Z、X、Y)
+    pub fn rotate(&mut self, euler_angles: Vector3<f32>, relative_to: Space) {
+        let euler_rotation = Self::euler(euler_angles);
+        match relative_to {
+            Space::Local => {
+                let local_rotation = self.local_rotation * euler_rotation;
+                self.set_local_rotation(local_rotation);
+            }
+            Space::World => {
+                let rotation = euler_rotation * self.rotation();
+                self.set_rotation(rotation);
+            }
         }
     }
+
+    /// 绕世界空间中经过 point 的 axis 旋转 angle 度
+    pub fn rotate_around(&mut self, point: Vector3<f32>, axis: Vector3<f32>, angle: f32) {
+        let Some(axis) = nalgebra::Unit::try_new(axis, f32::EPSILON) else {
+            return;
+        };
+        let rotation = UnitQuaternion::from_axis_angle(&axis, angle.to_radians());
+        let position = point + rotation * (self.position() - point);
+        let new_rotation = rotation.quaternion() * self.rotation();
+        self.set_position_and_rotation(position, new_rotation);
+    }
+
+    pub fn translate(&mut self, translation: Vector3<f32>, relative_to: Space) {
+        let translation = match relative_to {
+            Space::Local => self.transform_direction(translation),
+            Space::World => translation,
+        };
+        let position = self.position() + translation;
+        self.set_position(position);
+    }
+
+    pub fn euler_angles(&self) -> Vector3<f32> {
+        Self::quaternion_to_euler_angles(self.rotation())
+    }
+
+    /// Unity 的 Quaternion.Euler
+    pub fn euler(euler_angles: Vector3<f32>) -> Quaternion<f32> {
+        let x = UnitQuaternion::from_axis_angle(&Vector3::x_axis(), euler_angles.x.to_radians());
+        let y = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), euler_angles.y.to_radians());
+        let z = UnitQuaternion::from_axis_angle(&Vector3::z_axis(), euler_angles.z.to_radians());
+        *(y * x * z).quaternion()
+    }
 }
 
 impl Transform {
@@ -119,32 +462,32 @@ impl Transform {
 impl Transform {
     /// 计算全局变换矩阵
     fn to_global_matrix(&self) -> Matrix4<f32> {
-        let translation = Translation3::from(self.position).to_homogeneous();
-        let rotation = UnitQuaternion::from_quaternion(self.rotation).to_homogeneous();
-        let scale = Matrix4::new_nonuniform_scaling(&self.local_scale);
-        translation * rotation * scale
+        self.local_to_world_matrix()
     }
 
     /// 从变换矩阵中提取位置、旋转和缩放
     fn from_matrix(matrix: &Matrix4<f32>) -> Self {
         let translation = Vector3::from(matrix.fixed_view::<3, 1>(0, 3));
-        let rotation =
-            *UnitQuaternion::from_matrix(&Matrix3::from(matrix.fixed_view::<3, 3>(0, 0)));
         let scale = Vector3::new(
             matrix.fixed_view::<3, 1>(0, 0).norm(),
             matrix.fixed_view::<3, 1>(0, 1).norm(),
             matrix.fixed_view::<3, 1>(0, 2).norm(),
         );
+        let mut rotation_matrix = Matrix3::from(matrix.fixed_view::<3, 3>(0, 0));
+        for (mut column, scale) in rotation_matrix.column_iter_mut().zip(scale.iter()) {
+            if *scale > f32::EPSILON {
+                column /= *scale;
+            }
+        }
+        let rotation = *UnitQuaternion::from_matrix(&rotation_matrix);
         Transform {
-            instance_id: 0,
-            parent: Default::default(),
-            children: vec![],
-            game_object: Default::default(),
-            position: translation,
-            rotation,
-            local_position: Vector3::zeros(),
-            local_rotation: Quaternion::identity(),
+            local_position: translation,
+            local_rotation: rotation,
             local_scale: scale,
+            world_position: Cell::new(translation),
+            world_rotation: Cell::new(rotation),
+            local_to_world: Cell::new(*matrix),
+            ..Default::default()
         }
     }
 
@@ -164,3 +507,42 @@ impl Transform {
         Transform::from_matrix(&relative_matrix)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(a: Vector3<f32>, b: Vector3<f32>) {
+        assert!((a - b).norm() < 1e-4, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn test_hierarchy_propagation() {
+        let mut hull = Transform::default().into_arc();
+        let mut turret = Transform::default().into_arc();
+        turret.set_parent(hull.downgrade(), false);
+        turret.set_local_position(Vector3::new(0.0, 1.0, 2.0));
+        assert_near(turret.position(), Vector3::new(0.0, 1.0, 2.0));
+
+        // 车身绕 Y 轴旋转 90 度后，炮塔的世界坐标随之变化
+        hull.set_position(Vector3::new(10.0, 0.0, 0.0));
+        hull.rotate(Vector3::new(0.0, 90.0, 0.0), Space::World);
+        assert_near(turret.position(), Vector3::new(12.0, 1.0, 0.0));
+        assert_near(turret.forward(), Vector3::new(1.0, 0.0, 0.0));
+        assert_near(
+            hull.inverse_transform_point(turret.position()),
+            Vector3::new(0.0, 1.0, 2.0),
+        );
+
+        // 保持世界坐标脱离父节点
+        turret.set_parent(RevelWeak::default(), true);
+        assert!(hull.children.is_empty());
+        assert_near(turret.position(), Vector3::new(12.0, 1.0, 0.0));
+        assert_near(turret.local_position(), Vector3::new(12.0, 1.0, 0.0));
+
+        turret.look_at(Vector3::new(12.0, 1.0, 5.0), Vector3::y());
+        assert_near(turret.forward(), Vector3::new(0.0, 0.0, 1.0));
+        turret.translate(Vector3::new(0.0, 0.0, 1.0), Space::Local);
+        assert_near(turret.position(), Vector3::new(12.0, 1.0, 1.0));
+    }
+}
@@ -1,33 +1,47 @@
 use crate::commons::action::SelfMutAction;
 use crate::commons::RevelArc;
 use crate::commons::RevelWeak;
+use crate::commons::thread_static;
 use crate::metadata_settings::Metadata;
+use crate::mirror::NetworkIdentity;
 use crate::unity_engine::game_object::GameObject;
-use once_cell::sync::Lazy;
+use crate::unity_engine::MonoBehaviour;
 use std::collections::HashMap;
 use std::ops::{Deref, DerefMut};
-use std::sync::atomic::AtomicIsize;
+use std::sync::atomic::{AtomicIsize, AtomicU32};
 use std::sync::atomic::Ordering::SeqCst;
 
-static mut WORLDS: Lazy<Vec<RevelArc<World>>> = Lazy::new(|| Vec::new());
-static mut ACTIVE_WORLD_INDEX: AtomicIsize = AtomicIsize::new(-1);
-static mut DONT_DESTROY_OBJECT: Lazy<HashMap<u64, RevelArc<GameObject>>> =
-    Lazy::new(|| HashMap::default());
+thread_static! {
+    static mut WORLDS: Vec<RevelArc<World>> = Vec::new();
+    static mut ACTIVE_WORLD_INDEX: AtomicIsize = AtomicIsize::new(-1);
+    static mut DONT_DESTROY_OBJECT: HashMap<u64, RevelArc<GameObject>> = HashMap::default();
+    // 正在执行 FixedUpdate / Update / LateUpdate，期间的销毁推迟到本轮更新结束
+    static mut UPDATING: bool = false;
+    static mut PENDING_DESTROYS: Vec<u64> = Vec::new();
+}
+// 场景实例句柄，0 保留给 DontDestroyOnLoad，各服务器实例间也不重复
+static NEXT_SCENE_HANDLE: AtomicU32 = AtomicU32::new(1);
 
 #[derive(Default)]
 pub struct World {
     #[allow(unused)]
     scene_name: String,
     scene_path: String,
+    /// 场景实例句柄，同一场景加载多次时用于区分各个实例
+    handle: u32,
+    /// 通过 load_scene_instance 加载的隔离实例，只对分配到该实例的连接可见
+    is_instance: bool,
     game_objects: HashMap<u64, RevelArc<GameObject>>,
 }
 
-static mut SCENE_LOADED_ACTION: Lazy<SelfMutAction<(String, LoadSceneMode), ()>> =
-    Lazy::new(|| SelfMutAction::default());
+thread_static! {
+    static mut SCENE_LOADED_ACTION: SelfMutAction<(String, LoadSceneMode), ()> = SelfMutAction::default();
+    static mut SCENE_UNLOADED_ACTION: SelfMutAction<(String,), ()> = SelfMutAction::default();
+}
 
 impl World {
     fn new(scene_path: &str) -> Self {
-        match Metadata::get_scene(scene_path) {
+        match Metadata::current().get_scene(scene_path) {
             None => {
                 panic!(
                     "Failed to load scene '{}'. Please check if the scene exists in the metadata.",
@@ -38,10 +52,13 @@ impl World {
                 let mut world = World {
                     scene_name: scene_path.to_string(),
                     scene_path: scene_path.to_string(),
+                    handle: NEXT_SCENE_HANDLE.fetch_add(1, SeqCst),
+                    is_instance: false,
                     game_objects: Default::default(),
                 };
                 for (_, metadata_prefab) in scene_metadata.iter() {
-                    let arc_game_object = GameObject::instance(metadata_prefab);
+                    let mut arc_game_object = GameObject::instance(metadata_prefab);
+                    arc_game_object.set_scene_handle(world.handle);
                     world
                         .game_objects
                         .insert(arc_game_object.id, arc_game_object);
@@ -67,7 +84,8 @@ impl World {
         }
     }
 
-    pub fn add_game_object(&mut self, arc_game_object: RevelArc<GameObject>) {
+    pub fn add_game_object(&mut self, mut arc_game_object: RevelArc<GameObject>) {
+        arc_game_object.set_scene_handle(self.handle);
         self.game_objects
             .insert(arc_game_object.id, arc_game_object);
     }
@@ -75,6 +93,60 @@ impl World {
     pub fn get_scene_path(&self) -> String {
         return self.scene_path.clone();
     }
+
+    pub fn handle(&self) -> u32 {
+        self.handle
+    }
+
+    pub fn is_instance(&self) -> bool {
+        self.is_instance
+    }
+
+    pub fn root_game_objects(&self) -> Vec<RevelWeak<GameObject>> {
+        self.game_objects
+            .values()
+            .map(|arc_game_object| arc_game_object.downgrade())
+            .collect()
+    }
+}
+
+// 只在本场景 (实例) 中查询，同一场景的多个实例互不可见
+impl World {
+    pub fn find(&self, name: &str) -> Option<RevelWeak<GameObject>> {
+        WorldManager::find_first(&self.root_game_objects(), &|game_object| {
+            game_object.name == name
+        })
+    }
+
+    pub fn find_with_tag(&self, tag: &str) -> Option<RevelWeak<GameObject>> {
+        WorldManager::find_first(&self.root_game_objects(), &|game_object| {
+            game_object.tag == tag
+        })
+    }
+
+    pub fn find_objects_with_tag(&self, tag: &str) -> Vec<RevelWeak<GameObject>> {
+        let mut result = vec![];
+        WorldManager::find_all(
+            &self.root_game_objects(),
+            &|game_object| game_object.tag == tag,
+            &mut result,
+        );
+        result
+    }
+
+    pub fn find_objects_of_type<T: MonoBehaviour + 'static>(&self) -> Vec<RevelWeak<Box<dyn MonoBehaviour>>> {
+        let mut game_objects = vec![];
+        WorldManager::find_all(
+            &self.root_game_objects(),
+            &|game_object| game_object.try_get_component::<T>().is_some(),
+            &mut game_objects,
+        );
+        game_objects
+            .iter()
+            .filter_map(|weak_game_object| weak_game_object.get())
+            .flat_map(|game_object| game_object.get_components::<T>())
+            .collect()
+    }
 }
 
 pub enum LoadSceneMode {
@@ -96,8 +168,9 @@ impl WorldManagerStatic {
     }
 }
 
-static mut WORLD_MANAGER_STATIC: Lazy<WorldManagerStatic> =
-    Lazy::new(|| WorldManagerStatic { loading: false });
+thread_static! {
+    static mut WORLD_MANAGER_STATIC: WorldManagerStatic = WorldManagerStatic { loading: false };
+}
 
 pub struct WorldManager;
 
@@ -123,10 +196,30 @@ impl DerefMut for WorldManager {
 
 impl WorldManager {
     pub fn load_scene(scene_path: &str, mode: LoadSceneMode) -> usize {
+        Self::load_world(scene_path, mode, false)
+    }
+
+    fn load_world(scene_path: &str, mode: LoadSceneMode, is_instance: bool) -> usize {
         #[allow(static_mut_refs)]
         unsafe {
             Self.loading = true;
-            let world = World::new(scene_path);
+            let mut world = World::new(scene_path);
+            world.is_instance = is_instance;
+            if let LoadSceneMode::Single = mode {
+                NetworkIdentity::clear_scene_objects();
+            }
+            if let Err(conflicts) = NetworkIdentity::register_scene_objects(
+                world.handle,
+                &world.scene_path,
+                &world.root_game_objects(),
+            ) {
+                log::error!(
+                    "Scene '{}' (handle {}) loaded with conflicting sceneIds {:X?}, the duplicated objects will not be spawned.",
+                    scene_path,
+                    world.handle,
+                    conflicts
+                );
+            }
             let i = match mode {
                 LoadSceneMode::Single => {
                     WORLDS
@@ -154,6 +247,156 @@ impl WorldManager {
         }
     }
 
+    pub fn set_scene_unloaded(f: SelfMutAction<(String,), ()>) {
+        #[allow(static_mut_refs)]
+        unsafe {
+            *SCENE_UNLOADED_ACTION = f;
+        }
+    }
+
+    /// 卸载场景，销毁其中所有对象。不能卸载唯一的场景
+    /// 同一场景存在多个实例时卸载最先加载的那个
+    pub fn unload_scene(scene_path: &str) -> bool {
+        match Self::get_scene_by_path(scene_path).get() {
+            None => {
+                log::error!("Failed to unload scene '{}': scene is not loaded.", scene_path);
+                false
+            }
+            Some(world) => Self::unload_scene_by_handle(world.handle),
+        }
+    }
+
+    /// 按句柄卸载某个场景实例
+    pub fn unload_scene_by_handle(handle: u32) -> bool {
+        #[allow(static_mut_refs)]
+        unsafe {
+            let Some(index) = WORLDS.iter().position(|world| world.handle == handle) else {
+                log::error!("Failed to unload scene instance {}: scene is not loaded.", handle);
+                return false;
+            };
+            if WORLDS.len() == 1 {
+                log::error!(
+                    "Failed to unload scene '{}': unloading the last loaded scene is not supported.",
+                    WORLDS[index].scene_path
+                );
+                return false;
+            }
+
+            let mut world = WORLDS.remove(index);
+            world.destroy_all_game_object();
+            NetworkIdentity::unregister_scene_objects(world.handle);
+
+            let active_index = ACTIVE_WORLD_INDEX.load(SeqCst);
+            if active_index == index as isize {
+                ACTIVE_WORLD_INDEX.store(0, SeqCst);
+            } else if active_index > index as isize {
+                ACTIVE_WORLD_INDEX.store(active_index - 1, SeqCst);
+            }
+
+            if SCENE_UNLOADED_ACTION.is_registered() {
+                SCENE_UNLOADED_ACTION.call((world.scene_path.clone(),));
+            }
+            true
+        }
+    }
+
+    /// 额外加载一个场景实例，同一路径可以加载多份，各实例拥有独立的对象集合。
+    /// 用于同一张地图上同时进行多场对局
+    pub fn load_scene_instance(scene_path: &str) -> RevelWeak<World> {
+        let index = Self::load_world(scene_path, LoadSceneMode::Additive, true);
+        #[allow(static_mut_refs)]
+        unsafe {
+            WORLDS
+                .get(index)
+                .map(|world| world.downgrade())
+                .unwrap_or_default()
+        }
+    }
+
+    pub fn get_scene_by_handle(handle: u32) -> RevelWeak<World> {
+        #[allow(static_mut_refs)]
+        unsafe {
+            WORLDS
+                .iter()
+                .find(|world| world.handle == handle)
+                .map(|world| world.downgrade())
+                .unwrap_or_default()
+        }
+    }
+
+    pub fn is_scene_instance(handle: u32) -> bool {
+        #[allow(static_mut_refs)]
+        unsafe {
+            WORLDS
+                .iter()
+                .any(|world| world.handle == handle && world.is_instance)
+        }
+    }
+
+    /// 所有路径相同的场景实例
+    pub fn get_scene_instances(scene_path: &str) -> Vec<RevelWeak<World>> {
+        #[allow(static_mut_refs)]
+        unsafe {
+            WORLDS
+                .iter()
+                .filter(|world| world.scene_path == scene_path)
+                .map(|world| world.downgrade())
+                .collect()
+        }
+    }
+
+    /// 将根对象移动到另一个场景实例
+    pub fn move_game_object_to_scene(weak_game_object: RevelWeak<GameObject>, handle: u32) -> bool {
+        let Some(game_object) = weak_game_object.get() else {
+            return false;
+        };
+        if game_object.parent.upgradable() {
+            log::error!(
+                "MoveGameObjectToScene: '{}' is not a root GameObject.",
+                game_object.name
+            );
+            return false;
+        }
+        let id = game_object.id;
+        let Some(target) = Self::get_scene_by_handle(handle).upgrade() else {
+            log::error!("MoveGameObjectToScene: scene instance {} is not loaded.", handle);
+            return false;
+        };
+        #[allow(static_mut_refs)]
+        unsafe {
+            let removed = WORLDS
+                .iter_mut()
+                .find_map(|world| world.game_objects.remove(&id))
+                .or_else(|| DONT_DESTROY_OBJECT.remove(&id));
+            match removed {
+                None => false,
+                Some(arc_game_object) => {
+                    let mut target = target;
+                    target.add_game_object(arc_game_object);
+                    true
+                }
+            }
+        }
+    }
+
+    pub fn get_scene_by_path(scene_path: &str) -> RevelWeak<World> {
+        #[allow(static_mut_refs)]
+        unsafe {
+            WORLDS
+                .iter()
+                .find(|world| world.scene_path == scene_path)
+                .map(|world| world.downgrade())
+                .unwrap_or_default()
+        }
+    }
+
+    pub fn worlds() -> Vec<RevelWeak<World>> {
+        #[allow(static_mut_refs)]
+        unsafe {
+            WORLDS.iter().map(|world| world.downgrade()).collect()
+        }
+    }
+
     pub fn set_active_scene(index: usize) -> usize {
         #[allow(static_mut_refs)]
         unsafe {
@@ -183,12 +426,8 @@ impl WorldManager {
         unsafe {
             let id = arc_game_object.id;
 
-            let index = ACTIVE_WORLD_INDEX.load(std::sync::atomic::Ordering::SeqCst);
-
-            if let Some(world) = WORLDS.get_mut(index as usize) {
-                if world.game_objects.contains_key(&id) {
-                    world.game_objects.remove(&id);
-                }
+            for world in WORLDS.iter_mut() {
+                world.game_objects.remove(&id);
             }
 
             if DONT_DESTROY_OBJECT.contains_key(&id) {
@@ -197,27 +436,97 @@ impl WorldManager {
                     id
                 );
             }
+            let mut arc_game_object = arc_game_object;
+            arc_game_object.set_scene_handle(0);
             let weak_game_object = arc_game_object.downgrade();
             DONT_DESTROY_OBJECT.insert(id, arc_game_object);
             weak_game_object
         }
     }
 
+    /// 销毁所有场景和 DontDestroyOnLoad 中的对象，用于关闭
+    pub(crate) fn destroy_all() {
+        #[allow(static_mut_refs)]
+        unsafe {
+            for world in std::mem::take(&mut *WORLDS).iter_mut() {
+                world.destroy_all_game_object();
+                NetworkIdentity::unregister_scene_objects(world.handle);
+            }
+            ACTIVE_WORLD_INDEX.store(-1, SeqCst);
+            let mut dont_destroy_objects = std::mem::take(&mut *DONT_DESTROY_OBJECT);
+            for (_, game_object) in dont_destroy_objects.iter_mut() {
+                game_object.on_disable();
+            }
+            for (_, game_object) in dont_destroy_objects.iter_mut() {
+                game_object.on_destroy();
+            }
+        }
+    }
+
+    /// 更新期间 (组件回调、Invoke 和协程中) 调用时推迟到本轮更新结束后销毁
     pub fn destroy(id: &u64) {
         #[allow(static_mut_refs)]
         unsafe {
+            if *UPDATING {
+                if !PENDING_DESTROYS.contains(id) {
+                    PENDING_DESTROYS.push(*id);
+                }
+                return;
+            }
             if let Some(mut arc_game_object) = DONT_DESTROY_OBJECT.remove(id) {
                 arc_game_object.on_disable();
                 arc_game_object.on_destroy();
+            } else if let Some(world) = WORLDS
+                .iter_mut()
+                .find(|world| world.game_objects.contains_key(id))
+            {
+                world.destroy_game_object_with_id(id);
             } else {
-                let index = ACTIVE_WORLD_INDEX.load(std::sync::atomic::Ordering::SeqCst);
-                if let Some(world) = WORLDS.get_mut(index as usize) {
-                    world.destroy_game_object_with_id(id);
+                // 非根对象，从父对象的 children 中移除
+                let root_game_objects = Self::all_root_game_objects();
+                for weak_game_object in root_game_objects.iter() {
+                    if let Some(game_object) = weak_game_object.get() {
+                        if let Some(mut removed) = game_object.remove_child_recursive(id) {
+                            removed.on_disable();
+                            removed.on_destroy();
+                            break;
+                        }
+                    }
                 }
             }
         }
     }
 
+    /// 从所在场景 (或 DontDestroyOnLoad) 的根对象中取出
+    pub(crate) fn take_root_game_object(id: &u64) -> Option<RevelArc<GameObject>> {
+        #[allow(static_mut_refs)]
+        unsafe {
+            WORLDS
+                .iter_mut()
+                .find_map(|world| world.game_objects.remove(id))
+                .or_else(|| DONT_DESTROY_OBJECT.remove(id))
+        }
+    }
+
+    /// 作为根对象放入场景实例，句柄为 0 或场景不存在时放入 DontDestroyOnLoad 或活动场景
+    pub(crate) fn add_root_game_object(mut arc_game_object: RevelArc<GameObject>, scene_handle: u32) {
+        #[allow(static_mut_refs)]
+        unsafe {
+            if scene_handle == 0 {
+                arc_game_object.set_scene_handle(0);
+                DONT_DESTROY_OBJECT.insert(arc_game_object.id, arc_game_object);
+                return;
+            }
+        }
+        let world = match Self::get_scene_by_handle(scene_handle).upgradable() {
+            true => Self::get_scene_by_handle(scene_handle),
+            false => Self::active_world(),
+        };
+        if let Some(world) = world.get() {
+            world.add_game_object(arc_game_object);
+        }
+    }
+
     pub fn root_game_objects() -> Vec<RevelWeak<GameObject>> {
         #[allow(static_mut_refs)]
         unsafe {
@@ -251,50 +560,160 @@ impl WorldManager {
             root_game_objects
         }
     }
-}
 
-impl WorldManager {
-    pub(super) fn fixed_update() {
+    /// 所有已加载场景以及 DontDestroyOnLoad 中的根对象
+    pub fn all_root_game_objects() -> Vec<RevelWeak<GameObject>> {
         #[allow(static_mut_refs)]
         unsafe {
-            for (_, game_object) in DONT_DESTROY_OBJECT.iter_mut() {
-                game_object.fixed_update();
+            let mut root_game_objects = vec![];
+            for world in WORLDS.iter() {
+                root_game_objects.extend(world.root_game_objects());
             }
-            for world in WORLDS.iter_mut() {
-                for (_, game_object) in world.game_objects.iter_mut() {
-                    game_object.fixed_update();
-                }
+            root_game_objects.extend(
+                DONT_DESTROY_OBJECT
+                    .values()
+                    .map(|arc_game_object| arc_game_object.downgrade()),
+            );
+            root_game_objects
+        }
+    }
+}
+
+// 查询，只包含激活的对象，与 Unity 的 GameObject.Find 系列一致
+impl WorldManager {
+    fn find_first(
+        game_objects: &[RevelWeak<GameObject>],
+        predicate: &dyn Fn(&GameObject) -> bool,
+    ) -> Option<RevelWeak<GameObject>> {
+        for weak_game_object in game_objects.iter() {
+            let Some(game_object) = weak_game_object.get() else {
+                continue;
+            };
+            if !game_object.is_active() {
+                continue;
+            }
+            if predicate(game_object) {
+                return Some(weak_game_object.clone());
+            }
+            let children = game_object.children_weak();
+            if let Some(found) = Self::find_first(&children, predicate) {
+                return Some(found);
             }
         }
+        None
     }
 
-    pub(super) fn update() {
-        #[allow(static_mut_refs)]
-        unsafe {
-            for (_, game_object) in DONT_DESTROY_OBJECT.iter_mut() {
-                game_object.update();
+    fn find_all(
+        game_objects: &[RevelWeak<GameObject>],
+        predicate: &dyn Fn(&GameObject) -> bool,
+        result: &mut Vec<RevelWeak<GameObject>>,
+    ) {
+        for weak_game_object in game_objects.iter() {
+            let Some(game_object) = weak_game_object.get() else {
+                continue;
+            };
+            if !game_object.is_active() {
+                continue;
             }
-            for world in WORLDS.iter_mut() {
-                for (_, game_object) in world.game_objects.iter_mut() {
-                    game_object.update();
-                }
+            if predicate(game_object) {
+                result.push(weak_game_object.clone());
             }
+            Self::find_all(&game_object.children_weak(), predicate, result);
         }
     }
 
-    pub(super) fn late_update() {
+    /// 按名称查找第一个对象
+    pub fn find(name: &str) -> Option<RevelWeak<GameObject>> {
+        Self::find_first(&Self::all_root_game_objects(), &|game_object| {
+            game_object.name == name
+        })
+    }
+
+    /// 按标签查找第一个对象
+    pub fn find_with_tag(tag: &str) -> Option<RevelWeak<GameObject>> {
+        Self::find_first(&Self::all_root_game_objects(), &|game_object| {
+            game_object.tag == tag
+        })
+    }
+
+    pub fn find_objects_with_tag(tag: &str) -> Vec<RevelWeak<GameObject>> {
+        let mut result = vec![];
+        Self::find_all(
+            &Self::all_root_game_objects(),
+            &|game_object| game_object.tag == tag,
+            &mut result,
+        );
+        result
+    }
+
+    pub fn find_objects_by_layer(layer: i32) -> Vec<RevelWeak<GameObject>> {
+        let mut result = vec![];
+        Self::find_all(
+            &Self::all_root_game_objects(),
+            &|game_object| game_object.layer == layer,
+            &mut result,
+        );
+        result
+    }
+
+    /// 所有激活对象上类型为 T 的组件
+    pub fn find_objects_of_type<T: MonoBehaviour + 'static>() -> Vec<RevelWeak<Box<dyn MonoBehaviour>>> {
+        let mut game_objects = vec![];
+        Self::find_all(
+            &Self::all_root_game_objects(),
+            &|game_object| game_object.try_get_component::<T>().is_some(),
+            &mut game_objects,
+        );
+        game_objects
+            .iter()
+            .filter_map(|weak_game_object| weak_game_object.get())
+            .flat_map(|game_object| game_object.get_components::<T>())
+            .collect()
+    }
+
+    pub fn find_object_of_type<T: MonoBehaviour + 'static>() -> Option<RevelWeak<Box<dyn MonoBehaviour>>> {
+        Self::find_first(&Self::all_root_game_objects(), &|game_object| {
+            game_object.try_get_component::<T>().is_some()
+        })?
+        .get()?
+        .try_get_component::<T>()
+    }
+}
+
+impl WorldManager {
+    // 更新前取出所有根对象的快照，回调中增删对象或卸载场景不会影响本轮遍历，
+    // 快照同时保证本轮更新中被移除的对象在遍历结束前不会被释放
+    fn update_root_game_objects(update: fn(&mut GameObject)) {
         #[allow(static_mut_refs)]
         unsafe {
-            for (_, game_object) in DONT_DESTROY_OBJECT.iter_mut() {
-                game_object.late_update();
+            let mut game_objects = DONT_DESTROY_OBJECT.values().cloned().collect::<Vec<_>>();
+            for world in WORLDS.iter() {
+                game_objects.extend(world.game_objects.values().cloned());
             }
-            for world in WORLDS.iter_mut() {
-                for (_, game_object) in world.game_objects.iter_mut() {
-                    game_object.late_update();
-                }
+
+            *UPDATING = true;
+            for game_object in game_objects.iter_mut() {
+                update(game_object);
+            }
+            *UPDATING = false;
+
+            for id in std::mem::take(&mut *PENDING_DESTROYS) {
+                Self::destroy(&id);
             }
         }
     }
+
+    pub(super) fn fixed_update() {
+        Self::update_root_game_objects(GameObject::fixed_update);
+    }
+
+    pub(super) fn update() {
+        Self::update_root_game_objects(GameObject::update);
+    }
+
+    pub(super) fn late_update() {
+        Self::update_root_game_objects(GameObject::late_update);
+    }
 }
 
 #[cfg(test)]
@@ -307,7 +726,7 @@ mod tests {
         WorldManager::load_scene("Assets/Scenes/RoomScene.unity", LoadSceneMode::Single);
         // WorldManager::set_active_scene(1);
         // let network_room_player_prefab =
-        //     Metadata::get_prefab("Assets/Prefabs/NetworkRoomPlayer.prefab").unwrap();
+        //     Metadata::current().get_prefab("Assets/Prefabs/NetworkRoomPlayer.prefab").unwrap();
         //
         // let arc_game_object = GameObject::instance(&network_room_player_prefab);
         // let id = arc_game_object.read().unwrap().id;
@@ -335,4 +754,123 @@ mod tests {
         // WorldManager::change_scene("Assets/Scenes/RoomScene.unity");
         // WorldManager::change_scene("Assets/Scenes/RoomScene.unity");
     }
+
+    #[test]
+    fn test_scene_instance_queries() {
+        use crate::metadata_settings::metadata_prefab::MetadataPrefab;
+
+        let spawn_point = MetadataPrefab::builder("SpawnPoint").tag("Respawn").build().unwrap();
+        Metadata::load_scenes(vec![
+            ("Assets/Scenes/Lobby.unity", vec![]),
+            ("Assets/Scenes/Arena.unity", vec![spawn_point]),
+        ]);
+        WorldManager::load_scene("Assets/Scenes/Lobby.unity", LoadSceneMode::Single);
+        let first = WorldManager::load_scene_instance("Assets/Scenes/Arena.unity");
+        let second = WorldManager::load_scene_instance("Assets/Scenes/Arena.unity");
+        let (first, second) = (first.get().unwrap(), second.get().unwrap());
+        assert_ne!(first.handle(), second.handle());
+        assert!(WorldManager::is_scene_instance(first.handle()));
+        assert_eq!(WorldManager::get_scene_instances("Assets/Scenes/Arena.unity").len(), 2);
+
+        // 每个实例只能查到自己的对象
+        let found = first.find("SpawnPoint").unwrap();
+        assert_eq!(found.get().unwrap().scene_handle(), first.handle());
+        let found = second.find_with_tag("Respawn").unwrap();
+        assert_eq!(found.get().unwrap().scene_handle(), second.handle());
+        assert_eq!(second.find_objects_with_tag("Respawn").len(), 1);
+        assert_eq!(WorldManager::find_objects_with_tag("Respawn").len(), 2);
+
+        let second_handle = second.handle();
+        assert!(WorldManager::unload_scene_by_handle(first.handle()));
+        assert_eq!(found.get().unwrap().scene_handle(), second_handle);
+        assert_eq!(WorldManager::get_scene_instances("Assets/Scenes/Arena.unity").len(), 1);
+        assert_eq!(WorldManager::find_objects_with_tag("Respawn").len(), 1);
+    }
+
+    #[test]
+    fn test_scene_ids_per_instance() {
+        use crate::metadata_settings::metadata_prefab::MetadataPrefab;
+
+        let scene_object = |name: &str, scene_id: u64| MetadataPrefab::builder(name).scene_id(scene_id).build().unwrap();
+        Metadata::load_scenes(vec![
+            ("Assets/Scenes/Lobby.unity", vec![]),
+            ("Assets/Scenes/Arena.unity", vec![scene_object("Door", 0xA1), scene_object("Chest", 0xB2)]),
+            ("Assets/Scenes/Broken.unity", vec![scene_object("Door", 0xC3), scene_object("Copy", 0xC3)]),
+        ]);
+        WorldManager::load_scene("Assets/Scenes/Lobby.unity", LoadSceneMode::Single);
+        let first = WorldManager::load_scene_instance("Assets/Scenes/Arena.unity").get().unwrap().handle();
+        let second = WorldManager::load_scene_instance("Assets/Scenes/Arena.unity").get().unwrap().handle();
+
+        // 同一场景的两个实例使用相同的 sceneId，按场景句柄区分，互不冲突
+        for scene_handle in [first, second] {
+            for scene_id in [0xA1, 0xB2] {
+                let identity = NetworkIdentity::get_scene_object(scene_handle, scene_id).unwrap();
+                let identity = identity.get().unwrap();
+                assert!(!identity.scene_id_conflict());
+                assert_eq!(identity.game_object.get().unwrap().scene_handle(), scene_handle);
+            }
+        }
+        let door = |scene_handle| NetworkIdentity::get_scene_object(scene_handle, 0xA1).unwrap();
+        assert!(!door(first).ptr_eq(&door(second)));
+
+        // 卸载一个实例不影响另一个
+        WorldManager::unload_scene_by_handle(first);
+        assert!(NetworkIdentity::get_scene_object(first, 0xA1).is_none());
+        assert!(NetworkIdentity::get_scene_object(second, 0xA1).is_some());
+
+        // 同一场景内重复的 sceneId 会被标记并返回
+        let broken = WorldManager::load_scene_instance("Assets/Scenes/Broken.unity");
+        let broken = broken.get().unwrap();
+        let conflicts = NetworkIdentity::register_scene_objects(
+            broken.handle(),
+            &broken.get_scene_path(),
+            &broken.root_game_objects(),
+        );
+        assert_eq!(conflicts, Err(vec![0xC3]));
+        let conflicted = broken
+            .root_game_objects()
+            .iter()
+            .filter_map(|game_object| game_object.get()?.try_get_component2::<NetworkIdentity>())
+            .filter(|identity| identity.scene_id_conflict())
+            .count();
+        assert_eq!(conflicted, 1);
+    }
+
+    #[test]
+    fn test_structural_changes_during_update() {
+        use crate::metadata_settings::metadata_prefab::MetadataPrefab;
+        use crate::unity_engine::coroutine::set_scheduler_time;
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let prefab = |name: &str| MetadataPrefab::builder(name).build().unwrap();
+        Metadata::load_scenes(vec![("Assets/Scenes/Test.unity", vec![prefab("Root")])]);
+        WorldManager::load_scene("Assets/Scenes/Test.unity", LoadSceneMode::Single);
+        set_scheduler_time(0.0);
+
+        // Invoke 中销毁自身，另一个 Invoke 中实例化新的根对象
+        let destroyed = {
+            let mut destroyed = GameObject::instantiate(&prefab("Destroyed"));
+            let destroyed_id = destroyed.id;
+            destroyed.invoke(0.0, move || WorldManager::destroy(&destroyed_id));
+            destroyed.downgrade()
+        };
+        let mut spawner = GameObject::instantiate(&prefab("Spawner"));
+        let spawned = Rc::new(RefCell::new(None));
+        let spawned_clone = spawned.clone();
+        spawner.invoke(0.0, move || {
+            *spawned_clone.borrow_mut() = Some(GameObject::instantiate(&prefab("Spawned")).downgrade());
+        });
+
+        WorldManager::update();
+        assert!(!destroyed.upgradable());
+        let spawned = spawned.borrow().clone().unwrap();
+        assert!(spawned.upgradable());
+        assert!(WorldManager::root_game_objects()
+            .iter()
+            .any(|game_object| game_object.ptr_eq(&spawned)));
+
+        WorldManager::update();
+        assert_eq!(WorldManager::root_game_objects().len(), 3);
+    }
 }
@@ -19,62 +19,50 @@ pub(crate) fn callback_processor_handler(input: TokenStream) -> TokenStream {
         struct_name.to_string().to_snake_case()
     );
 
+    // 回调处理器按线程保存，同一进程中的多个服务器实例各自使用自己的回调
+    // 调用前先取出函数指针，回调中可以重新 init (例如服务器关闭时)
     let expanded = quote! {
-        static mut #static_callback_processor_ident: once_cell::sync::Lazy<Option<CallbackProcessor>> = once_cell::sync::Lazy::new(|| None);
+        thread_local! {
+            static #static_callback_processor_ident: std::cell::RefCell<Option<CallbackProcessor>> = const { std::cell::RefCell::new(None) };
+        }
 
         fn #init_callback_processor_fn_ident(callback_processor: CallbackProcessor){
-            unsafe {
-                *#static_callback_processor_ident = Some(callback_processor);
-            }
+            #static_callback_processor_ident.with(|processor| *processor.borrow_mut() = Some(callback_processor));
         }
 
         fn on_server_connected_with_address(connection_id: u64, address: &str) {
-            unsafe{
-                if let Some(ref callback_processor) = *#static_callback_processor_ident {
-                    (callback_processor.on_server_connected_with_address)(connection_id, address);
-                }
+            if let Some(callback) = #static_callback_processor_ident.with(|processor| processor.borrow().as_ref().map(|processor| processor.on_server_connected_with_address)) {
+                callback(connection_id, address);
             }
         }
         fn on_server_connected(connection_id: u64) {
-           unsafe{
-                if let Some(ref callback_processor) = *#static_callback_processor_ident {
-                    (callback_processor.on_server_connected)(connection_id);
-                }
+            if let Some(callback) = #static_callback_processor_ident.with(|processor| processor.borrow().as_ref().map(|processor| processor.on_server_connected)) {
+                callback(connection_id);
             }
         }
         fn on_server_data_received(connection_id: u64, data: &[u8], channel: TransportChannel) {
-            unsafe{
-                if let Some(ref callback_processor) = *#static_callback_processor_ident {
-                    (callback_processor.on_server_data_received)(connection_id, data, channel);
-                }
+            if let Some(callback) = #static_callback_processor_ident.with(|processor| processor.borrow().as_ref().map(|processor| processor.on_server_data_received)) {
+                callback(connection_id, data, channel);
             }
         }
         fn on_server_error(connection_id: u64, error: TransportError, reason: &str) {
-            unsafe{
-                if let Some(ref callback_processor) = *#static_callback_processor_ident {
-                    (callback_processor.on_server_error)(connection_id, error, reason);
-                }
+            if let Some(callback) = #static_callback_processor_ident.with(|processor| processor.borrow().as_ref().map(|processor| processor.on_server_error)) {
+                callback(connection_id, error, reason);
             }
         }
         fn on_server_data_sent(connection_id: u64, data: &[u8], channel: TransportChannel) {
-            unsafe{
-                if let Some(ref callback_processor) = *#static_callback_processor_ident {
-                    (callback_processor.on_server_data_sent)(connection_id, data, channel);
-                }
+            if let Some(callback) = #static_callback_processor_ident.with(|processor| processor.borrow().as_ref().map(|processor| processor.on_server_data_sent)) {
+                callback(connection_id, data, channel);
             }
         }
         fn on_server_transport_exception(connection_id: u64, error: Box<dyn std::error::Error>) {
-            unsafe{
-                if let Some(ref callback_processor) = *#static_callback_processor_ident {
-                    (callback_processor.on_server_transport_exception)(connection_id, error);
-                }
+            if let Some(callback) = #static_callback_processor_ident.with(|processor| processor.borrow().as_ref().map(|processor| processor.on_server_transport_exception)) {
+                callback(connection_id, error);
             }
         }
         fn on_server_disconnected(connection_id: u64) {
-            unsafe{
-                if let Some(ref callback_processor) = *#static_callback_processor_ident {
-                    (callback_processor.on_server_disconnected)(connection_id);
-                }
+            if let Some(callback) = #static_callback_processor_ident.with(|processor| processor.borrow().as_ref().map(|processor| processor.on_server_disconnected)) {
+                callback(connection_id);
             }
         }
     };
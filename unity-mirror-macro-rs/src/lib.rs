@@ -6,6 +6,7 @@ use proc_macro::TokenStream;
 mod callback_processor;
 mod metadata_settings;
 mod network_message;
+mod network_serialize;
 
 mod network_manager;
 
@@ -108,11 +109,21 @@ pub fn parent_on_deserialize(attr: TokenStream, item: TokenStream) -> TokenStrea
     network_behaviour::parent_on_deserialize(attr, item)
 }
 
-#[proc_macro_derive(NetworkMessage)]
+#[proc_macro_derive(NetworkMessage, attributes(message))]
 pub fn message(input: TokenStream) -> TokenStream {
     network_message::handler(input)
 }
 
+#[proc_macro_derive(NetworkSerialize, attributes(network))]
+pub fn network_serialize(input: TokenStream) -> TokenStream {
+    network_serialize::serialize_handler(input)
+}
+
+#[proc_macro_derive(NetworkDeserialize, attributes(network))]
+pub fn network_deserialize(input: TokenStream) -> TokenStream {
+    network_serialize::deserialize_handler(input)
+}
+
 #[proc_macro_derive(CallbackProcessor)]
 pub fn callback_processor(input: TokenStream) -> TokenStream {
     callback_processor::callback_processor_handler(input)
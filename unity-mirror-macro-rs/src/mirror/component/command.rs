@@ -3,7 +3,7 @@ use crate::utils::string_case::StringCase;
 use proc_macro::TokenStream;
 use quote::{format_ident, quote};
 use syn::parse::{Parse, ParseStream};
-use syn::{Expr, FnArg, LitStr, Path, Token, parse_macro_input, parse_quote};
+use syn::{Expr, FnArg, LitStr, Path, Token, Type, parse_macro_input, parse_quote};
 
 mod kw {
     syn::custom_keyword!(struct_path);
@@ -83,20 +83,38 @@ pub(crate) fn handler(attr: TokenStream, item: TokenStream) -> TokenStream {
 
     let invoke_user_code = format_ident!("__invoke_user_code_command_{}", fn_ident);
 
-    let fn_inputs = item_fn
-        .sig
-        .inputs
-        .iter()
-        .filter(|input| match input {
-            FnArg::Receiver(_) => false,
-            _ => true,
-        })
-        .map(|_| {
-            quote! {
-                DataTypeDeserializer::deserialize(reader)
+    // 参数先读到局部变量，读取失败时不调用用户代码
+    let mut arg_reads = Vec::new();
+    let mut fn_inputs = Vec::new();
+    for (index, input) in item_fn.sig.inputs.iter().enumerate() {
+        let FnArg::Typed(pat_type) = input else {
+            continue;
+        };
+        let arg_ident = format_ident!("__arg_{}", index);
+        match pat_type.ty.as_ref() {
+            // 引用参数读为拥有所有权的值再借用
+            Type::Reference(reference) => {
+                let owned_ty: Type = match reference.elem.as_ref() {
+                    Type::Slice(slice) => {
+                        let elem = &slice.elem;
+                        parse_quote! { Vec<#elem> }
+                    }
+                    Type::Path(path) if path.path.is_ident("str") => parse_quote! { String },
+                    elem => elem.clone(),
+                };
+                arg_reads.push(quote! {
+                    let #arg_ident: #owned_ty = DataTypeDeserializer::deserialize(reader);
+                });
+                fn_inputs.push(quote! { &#arg_ident });
             }
-        })
-        .collect::<Vec<_>>();
+            ty => {
+                arg_reads.push(quote! {
+                    let #arg_ident: #ty = DataTypeDeserializer::deserialize(reader);
+                });
+                fn_inputs.push(quote! { #arg_ident });
+            }
+        }
+    }
 
     let csharp_func_inputs = to_csharp_function_inputs(item_fn.sig.inputs.clone());
 
@@ -108,6 +126,13 @@ pub(crate) fn handler(attr: TokenStream, item: TokenStream) -> TokenStream {
             reader: &mut NetworkReader,
             connection: RevelArc<Box<NetworkConnectionToClient>>,
         ) {
+            #(#arg_reads)*
+            // 由 NetworkServer 断开发送方
+            if let Some(err) = reader.error() {
+                log::warn!("Command {} received malformed arguments: {}", #fn_name, err);
+                return;
+            }
+
             obj_chain.reverse();
 
             for obj in obj_chain.iter() {
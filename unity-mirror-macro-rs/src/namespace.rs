@@ -1,7 +1,7 @@
 use crate::NamespaceArgs;
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{ItemStruct, parse_macro_input};
+use syn::{DeriveInput, parse_macro_input};
 impl NamespaceArgs {
     pub(crate) fn get_full_name(&self, struct_ident: &syn::Ident) -> String {
         let prefix = match &self.prefix {
@@ -29,17 +29,18 @@ impl NamespaceArgs {
 pub(crate) fn handler(attr: TokenStream, input: TokenStream) -> TokenStream {
     // 解析属性参数
     let namespace_args = parse_macro_input!(attr as NamespaceArgs);
-    // 结构体
-    let item_struct = parse_macro_input!(input as ItemStruct);
+    // 结构体或枚举
+    let item_struct = parse_macro_input!(input as DeriveInput);
     // 结构体的标识符
     let struct_ident = &item_struct.ident;
     // 结构体的命名空间
     let full_name = namespace_args.get_full_name(struct_ident);
+    let (impl_generics, ty_generics, where_clause) = item_struct.generics.split_for_impl();
 
     quote! {
         #item_struct
 
-        impl Object for #struct_ident {
+        impl #impl_generics Object for #struct_ident #ty_generics #where_clause {
             fn get_full_name() -> &'static str
             where
                 Self: Sized,
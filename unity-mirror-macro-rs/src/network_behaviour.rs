@@ -11,6 +11,7 @@ struct NetworkBehaviourArgs {
     pub parent: Option<Path>,
     pub metadata: Option<Path>,
     pub not_impl_nos: bool,
+    pub delta_compression: bool,
 }
 
 impl Parse for NetworkBehaviourArgs {
@@ -18,6 +19,7 @@ impl Parse for NetworkBehaviourArgs {
         let mut parent = None;
         let mut metadata = None;
         let mut not_impl_nos = false;
+        let mut delta_compression = false;
 
         while !input.is_empty() {
             {
@@ -39,6 +41,9 @@ impl Parse for NetworkBehaviourArgs {
                     "not_impl_nos" => {
                         not_impl_nos = true;
                     }
+                    "delta_compression" => {
+                        delta_compression = true;
+                    }
                     _ => {}
                 }
             }
@@ -49,6 +54,7 @@ impl Parse for NetworkBehaviourArgs {
             parent,
             metadata,
             not_impl_nos,
+            delta_compression,
         })
     }
 }
@@ -58,6 +64,7 @@ pub(crate) fn handler(attr: TokenStream, item: TokenStream) -> TokenStream {
         parent,
         metadata,
         not_impl_nos,
+        delta_compression,
     } = syn::parse_macro_input!(attr as NetworkBehaviourArgs);
 
     if parent.is_none() {
@@ -147,9 +154,18 @@ pub(crate) fn handler(attr: TokenStream, item: TokenStream) -> TokenStream {
                 break;
             }
             if attr.path().is_ident("sync_var") {
+                let args = match SyncVarArgs::from_attribute(attr) {
+                    Ok(args) => args,
+                    Err(err) => return err.to_compile_error().into(),
+                };
+                if args.quantize.is_some() && !delta_compression {
+                    return syn::Error::new_spanned(attr, "`quantize` requires `delta_compression` on the network_behaviour")
+                        .to_compile_error()
+                        .into();
+                }
                 // 修改字段的可见性
                 field.vis = Visibility::Inherited;
-                sync_var_fields.push((field.ident.clone().unwrap(), field.ty.clone()));
+                sync_var_fields.push((field.ident.clone().unwrap(), field.ty.clone(), is_reference_type(&field.ty), args));
                 break;
             }
         }
@@ -157,6 +173,22 @@ pub(crate) fn handler(attr: TokenStream, item: TokenStream) -> TokenStream {
     let sync_var_count = sync_var_fields.len();
     let sync_obj_count = sync_obj_fields.len();
 
+    // 增量压缩只处理本组件的同步变量
+    if delta_compression {
+        let parent_is_network_behaviour = parent
+            .as_ref()
+            .and_then(|parent| parent.segments.last())
+            .is_some_and(|segment| segment.ident == "NetworkBehaviour");
+        if !parent_is_network_behaviour || sync_obj_count > 0 || not_impl_nos {
+            return syn::Error::new_spanned(
+                &item_struct.ident,
+                "`delta_compression` requires parent(NetworkBehaviour), no #[sync_obj] fields and no `not_impl_nos`",
+            )
+            .to_compile_error()
+            .into();
+        }
+    }
+
     let mut init_sync_objs = Vec::new();
     let mut serialize_sync_objs_all_ts = Vec::new();
     let mut serialize_sync_objs_delta_ts = Vec::new();
@@ -197,61 +229,124 @@ pub(crate) fn handler(attr: TokenStream, item: TokenStream) -> TokenStream {
     let mut on_change_callback_ts = Vec::new();
     let mut parent_slot = None;
 
-    for (field_index, (field, field_type)) in sync_var_fields.iter().enumerate() {
-        let get_sync_field_ident = format_ident!("get_{}", field);
-        let set_sync_field_ident = format_ident!("set_{}", field);
-        let on_change_callback_ident = format_ident!("on_{}_changed", field);
+    // 网络对象引用保存的 netId 字段
+    let mut reference_id_fields = Vec::new();
+    // 增量压缩的快照和读取
+    let mut sync_var_snapshot_ts = Vec::new();
+    let mut deserialize_sync_var_delta_ts = Vec::new();
+
+    for (field_index, (field, field_type, is_reference, args)) in sync_var_fields.iter().enumerate() {
+        let set_internal_ident = format_ident!("set_{}_internal", field);
+        let reference_id_field = format_ident!("__{}_reference_id", field);
+
+        // 未指定 hook 时在 OnChangeCallback 中生成默认的空 hook
+        if args.hook.is_none() {
+            let on_change_callback_ident = format_ident!("on_{}_changed", field);
+            on_change_callback_ts.push(quote! {
+                fn #on_change_callback_ident(&mut self, old_value: &#field_type, new_value: &#field_type){}
+            });
+        }
+
+        // 初始状态是否调用 hook
+        let hook_on_initial = args.hook_on_initial;
+
+        // 写入 writer / 从 reader 读取并设置
+        let (write_value_ts, read_value_ts) = match is_reference {
+            true => (
+                quote! {
+                    <#field_type as SyncVarReference>::write_reference_id(&self.#reference_id_field, writer);
+                },
+                quote! {
+                    let reference_id = <#field_type as SyncVarReference>::read_reference_id(reader);
+                    self.#set_internal_ident(reference_id, false, !initial_state || #hook_on_initial);
+                },
+            ),
+            false => (
+                quote! {
+                    self.#field.serialize(writer);
+                },
+                quote! {
+                    let value = <#field_type as DataTypeDeserializer>::deserialize(reader);
+                    self.#set_internal_ident(value, false, !initial_state || #hook_on_initial);
+                },
+            ),
+        };
+
         serialize_sync_var_ts.push(quote! {
             if initial_state || (dirty_bits & (1u64 << (self.var_start_offset + #field_index as u8))) != 0 {
-                self.#field.serialize(writer);
+                #write_value_ts
             }
         });
 
         deserialize_sync_var_ts.push(quote! {
             if initial_state || (dirty_bits & (1u64 << (self.var_start_offset + #field_index as u8))) != 0 {
-                self.#set_sync_field_ident(<#field_type as DataTypeDeserializer>::deserialize(reader));
+                #read_value_ts
             }
         });
 
-        sync_variable_getter_setter.push(quote! {
-            pub fn #get_sync_field_ident(&self) -> &#field_type {
-                &self.#field
+        match &args.quantize {
+            Some(_) if *is_reference => {
+                return syn::Error::new_spanned(field, "`quantize` cannot be used on network object references")
+                    .to_compile_error()
+                    .into();
             }
+            Some(quantization) => {
+                sync_var_snapshot_ts.push(quote! {
+                    {
+                        let mut bits = BitWriter::new();
+                        <#field_type as Quantize>::write_quantized(&self.#field, &#quantization, &mut bits);
+                        SyncVarDeltaField::Bits(bits)
+                    }
+                });
+                deserialize_sync_var_delta_ts.push(quote! {
+                    if delta.changed(reader) {
+                        let value = delta.read_quantized::<#field_type>(&#quantization, reader);
+                        self.#set_internal_ident(value, false, true);
+                    }
+                });
+            }
+            None => {
+                sync_var_snapshot_ts.push(quote! {
+                    {
+                        let writer = &mut NetworkWriter::new();
+                        #write_value_ts
+                        SyncVarDeltaField::Bytes(writer.to_vec())
+                    }
+                });
+                deserialize_sync_var_delta_ts.push(quote! {
+                    if delta.changed(reader) {
+                        #read_value_ts
+                    }
+                });
+            }
+        }
 
-            pub fn #set_sync_field_ident(&mut self, value: #field_type) {
-
-                 let old_value = unsafe {
-                    let mut value_buffer = [0u8; size_of::<#field_type>()];
-                    std::ptr::copy_nonoverlapping(
-                        &self.#field as *const #field_type as *const u8,
-                        value_buffer.as_mut_ptr(),
-                        size_of::<#field_type>(),
-                    );
-                    std::mem::transmute::<[u8; size_of::<#field_type>()], #field_type>(value_buffer)
-                };
-
-                let new_value = unsafe {
-                    let mut value_buffer = [0u8; size_of::<#field_type>()];
-                    std::ptr::copy_nonoverlapping(
-                        &value as *const #field_type as *const u8,
-                        value_buffer.as_mut_ptr(),
-                        size_of::<#field_type>(),
-                    );
-                    std::mem::transmute::<[u8; size_of::<#field_type>()], #field_type>(value_buffer)
-                };
+        if *is_reference {
+            reference_id_fields.push(reference_id_field);
+        }
 
-                self.#field = value;
+        sync_variable_getter_setter.push(sync_var_accessors(field, field_type, field_index, *is_reference, args));
+    }
 
-                if let Some(mut network_behaviour) = self.ancestor.get() {
-                    network_behaviour.sync_var_dirty_bits |= 1u64 << (self.var_start_offset + #field_index as u8);
-                }
+    // 增量压缩：服务器按观察者基线发送快照的变化，非初始数据按增量格式读取
+    let mut delta_serializer_ts = None;
+    let mut delta_deserializer_ts = None;
+    if delta_compression {
+        delta_serializer_ts = Some(quote! {
+            fn is_delta_compressed(&self) -> bool {
+                true
+            }
 
-                self.#on_change_callback_ident(&old_value, &new_value)
+            fn sync_var_snapshot(&self) -> SyncVarSnapshot {
+                vec![#(#sync_var_snapshot_ts,)*]
             }
         });
-
-        on_change_callback_ts.push(quote! {
-            fn #on_change_callback_ident(&mut self, old_value: &#field_type, new_value: &#field_type){}
+        delta_deserializer_ts = Some(quote! {
+            if !initial_state {
+                let mut delta = SyncVarDeltaReader::new(reader);
+                #(#deserialize_sync_var_delta_ts)*
+                return;
+            }
         });
     }
 
@@ -302,6 +397,17 @@ pub(crate) fn handler(attr: TokenStream, item: TokenStream) -> TokenStream {
         var_start_offset: u8
     ));
 
+    // 正在执行 hook 的同步变量
+    ext_fields.push(parse_quote!(
+        sync_var_hook_guard: u64
+    ));
+
+    for reference_id_field in reference_id_fields.iter() {
+        ext_fields.push(parse_quote!(
+            #reference_id_field: NetworkReferenceId
+        ));
+    }
+
     // 扩展字段
     match &mut item_struct.fields {
         Fields::Named(fields_named) => {
@@ -402,6 +508,14 @@ pub(crate) fn handler(attr: TokenStream, item: TokenStream) -> TokenStream {
                     self.network_identity = weak_identity;
                 }
 
+                fn get_component_index(&self) -> u8 {
+                    self.component_index
+                }
+
+                fn get_network_identity(&self) -> &RevelWeak<Box<NetworkIdentity>> {
+                    &self.network_identity
+                }
+
                 fn is_dirty(&self) -> bool {
                     if let Some(ancestor) = self.ancestor.get() {
                         return ancestor.is_dirty();
@@ -417,6 +531,10 @@ pub(crate) fn handler(attr: TokenStream, item: TokenStream) -> TokenStream {
                     &self.sync_mode
                 }
 
+                fn get_priority(&self) -> f32 {
+                    self.priority
+                }
+
                 fn clear_all_dirty_bits(&mut self) {
                     if let Some(mut parent) = self.parent.get() {
                         parent.clear_all_dirty_bits();
@@ -458,6 +576,8 @@ pub(crate) fn handler(attr: TokenStream, item: TokenStream) -> TokenStream {
                         #(#serialize_sync_var_ts)*
                     }
                 }
+
+                #delta_serializer_ts
             }
 
             // impl NetworkBehaviourDeserializer for #struct_ident {
@@ -483,13 +603,15 @@ pub(crate) fn handler(attr: TokenStream, item: TokenStream) -> TokenStream {
                         return;
                     }
 
+                    #delta_deserializer_ts
+
                     if let Some(mut network_behaviour) = self.ancestor.get() {
                         let mut dirty_bits = 0;
                         if initial_state{
                             #(#deserialize_sync_var_ts)*
                             return;
                         }
-                        network_behaviour.sync_var_dirty_bits = reader.read_blittable::<u64>();
+                        network_behaviour.sync_var_dirty_bits = reader.read_blittable_compress::<u64>();
                         dirty_bits = network_behaviour.sync_var_dirty_bits;
                         #(#deserialize_sync_var_ts)*
                     }
@@ -582,3 +704,185 @@ pub(crate) fn parent_on_deserialize(_: TokenStream, item: TokenStream) -> TokenS
         #item_fn
     })
 }
+
+// RevelWeak<..> 类型的同步变量为网络对象引用 (GameObject / NetworkIdentity / NetworkBehaviour)
+fn is_reference_type(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(type_path) => type_path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "RevelWeak"),
+        _ => false,
+    }
+}
+
+// 同步变量的 get/set
+// set_xxx 标记为脏并调用 hook，set_xxx_without_dirty 只修改值并调用 hook，值没有变化时都不做处理
+// hook 执行期间再次设置同一变量不会重复调用 hook (对应 Mirror 的 SyncVarHookGuard)
+fn sync_var_accessors(
+    field: &Ident,
+    field_type: &syn::Type,
+    field_index: usize,
+    is_reference: bool,
+    args: &SyncVarArgs,
+) -> proc_macro2::TokenStream {
+    let get_ident = format_ident!("get_{}", field);
+    let set_ident = format_ident!("set_{}", field);
+    let set_without_dirty_ident = format_ident!("set_{}_without_dirty", field);
+    let set_internal_ident = format_ident!("set_{}_internal", field);
+    let hook_ident = args
+        .hook
+        .clone()
+        .unwrap_or_else(|| format_ident!("on_{}_changed", field));
+
+    let notify = quote! {
+        let dirty_bit = 1u64 << (self.var_start_offset + #field_index as u8);
+        if mark_dirty {
+            if let Some(mut network_behaviour) = self.ancestor.get() {
+                network_behaviour.sync_var_dirty_bits |= dirty_bit;
+            }
+        }
+
+        if invoke_hook && (self.sync_var_hook_guard & dirty_bit) == 0 {
+            self.sync_var_hook_guard |= dirty_bit;
+            self.#hook_ident(&old_value, &new_value);
+            self.sync_var_hook_guard &= !dirty_bit;
+        }
+    };
+
+    // 网络对象引用只同步 netId，读取时通过 NetworkServer.spawned 解析，对象销毁后解析为空
+    if is_reference {
+        let reference_id_field = format_ident!("__{}_reference_id", field);
+        let get_reference_id_ident = format_ident!("get_{}_reference_id", field);
+        return quote! {
+            pub fn #get_ident(&self) -> #field_type {
+                <#field_type as SyncVarReference>::resolve(&self.#reference_id_field)
+            }
+
+            pub fn #get_reference_id_ident(&self) -> NetworkReferenceId {
+                self.#reference_id_field
+            }
+
+            pub fn #set_ident(&mut self, value: #field_type) {
+                self.#set_internal_ident(<#field_type as SyncVarReference>::reference_id(&value), true, true);
+            }
+
+            pub fn #set_without_dirty_ident(&mut self, value: #field_type) {
+                self.#set_internal_ident(<#field_type as SyncVarReference>::reference_id(&value), false, true);
+            }
+
+            fn #set_internal_ident(&mut self, reference_id: NetworkReferenceId, mark_dirty: bool, invoke_hook: bool) {
+                if self.#reference_id_field == reference_id {
+                    return;
+                }
+
+                let old_value = <#field_type as SyncVarReference>::resolve(&self.#reference_id_field);
+                self.#reference_id_field = reference_id;
+                self.#field = <#field_type as SyncVarReference>::resolve(&reference_id);
+                let new_value = self.#field.clone();
+
+                #notify
+            }
+        };
+    }
+
+    quote! {
+        pub fn #get_ident(&self) -> &#field_type {
+            &self.#field
+        }
+
+        pub fn #set_ident(&mut self, value: #field_type) {
+            self.#set_internal_ident(value, true, true);
+        }
+
+        pub fn #set_without_dirty_ident(&mut self, value: #field_type) {
+            self.#set_internal_ident(value, false, true);
+        }
+
+        fn #set_internal_ident(&mut self, value: #field_type, mark_dirty: bool, invoke_hook: bool) {
+            if self.#field == value {
+                return;
+            }
+
+            let old_value = std::mem::replace(&mut self.#field, value);
+            let new_value = self.#field.clone();
+
+            #notify
+        }
+    }
+}
+
+// #[sync_var(hook = method, hook_on_initial, quantize(min, max, precision))]
+// 同步变量的类型需实现 PartialEq + Clone，set 时比较新旧值，并克隆新值传给 hook
+#[derive(Default)]
+struct SyncVarArgs {
+    // 自定义 hook，签名为 fn(&mut self, old_value: &T, new_value: &T)，默认为 OnChangeCallback::on_xxx_changed
+    hook: Option<Ident>,
+    // 反序列化初始状态时也调用 hook
+    hook_on_initial: bool,
+    quantize: Option<proc_macro2::TokenStream>,
+}
+
+impl SyncVarArgs {
+    fn from_attribute(attr: &syn::Attribute) -> syn::Result<Self> {
+        let mut args = SyncVarArgs::default();
+        if !matches!(attr.meta, syn::Meta::List(_)) {
+            return Ok(args);
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("hook") {
+                args.hook = Some(meta.value()?.parse()?);
+                return Ok(());
+            }
+            if meta.path.is_ident("hook_on_initial") {
+                args.hook_on_initial = true;
+                return Ok(());
+            }
+            if meta.path.is_ident("quantize") {
+                let content;
+                syn::parenthesized!(content in meta.input);
+                let values = Punctuated::<syn::Expr, Comma>::parse_terminated(&content)?;
+                if values.len() != 3 {
+                    return Err(meta.error("expected `quantize(min, max, precision)`"));
+                }
+                let (min, max, precision) = (&values[0], &values[1], &values[2]);
+                args.quantize = Some(quote! {
+                    Quantization::new((#min) as f32, (#max) as f32, (#precision) as f32)
+                });
+                return Ok(());
+            }
+            Err(meta.error("unsupported sync_var argument"))
+        })?;
+        Ok(args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(attr: syn::Attribute) -> syn::Result<SyncVarArgs> {
+        SyncVarArgs::from_attribute(&attr)
+    }
+
+    fn normalize(tokens: proc_macro2::TokenStream) -> String {
+        tokens.to_string().replace(' ', "")
+    }
+
+    #[test]
+    fn test_sync_var_args() {
+        let plain = args(parse_quote!(#[sync_var])).unwrap();
+        assert!(plain.hook.is_none() && !plain.hook_on_initial && plain.quantize.is_none());
+
+        let hooked = args(parse_quote!(#[sync_var(hook = on_health, hook_on_initial)])).unwrap();
+        assert_eq!(hooked.hook.unwrap(), "on_health");
+        assert!(hooked.hook_on_initial);
+
+        let quantized = args(parse_quote!(#[sync_var(quantize(-10, 10.0, 0.5))])).unwrap();
+        assert!(normalize(quantized.quantize.unwrap()).contains("Quantization::new((-10)asf32,(10.0)asf32,(0.5)asf32)"));
+
+        assert!(args(parse_quote!(#[sync_var(quantize(0, 1))])).is_err());
+        assert!(args(parse_quote!(#[sync_var(unknown)])).is_err());
+    }
+}
@@ -1,16 +1,51 @@
+use crate::utils::serialize_fields::{bounded_generics, deserialize_body, serialize_body};
 use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
 use syn::{parse_macro_input, DeriveInput};
 
-pub(crate) fn handler(input: TokenStream) -> TokenStream {
-    let input = parse_macro_input!(input as DeriveInput);
-    let struct_ident = &input.ident;
+// 字段按声明顺序读写，支持 #[message(skip)]、#[message(with = path)] 和带 repr 标签的枚举
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let ident = &input.ident;
+    let serialize = serialize_body(input, "message", "NetworkMessage")?;
+    let deserialize = deserialize_body(input, "message", "NetworkMessage")?;
+    let bounds = match input.data {
+        syn::Data::Enum(_) => quote! { DataTypeSerializer + DataTypeDeserializer + Default },
+        _ => quote! { DataTypeSerializer + DataTypeDeserializer },
+    };
+    let generics = bounded_generics(input, bounds);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
-    let output = quote! {
+    Ok(quote! {
+        impl #impl_generics NetworkMessage for #ident #ty_generics #where_clause {
 
-        impl NetworkMessage for #struct_ident {
+        }
 
+        impl #impl_generics MessageSerializer for #ident #ty_generics #where_clause {
+            fn serialize(&mut self, writer: &mut NetworkWriter)
+            where
+                Self: Sized,
+            {
+                writer.write_blittable(Self::get_full_name().hash16());
+                #serialize
+            }
         }
-    };
-    output.into()
+
+        impl #impl_generics MessageDeserializer for #ident #ty_generics #where_clause {
+            fn deserialize(reader: &mut NetworkReader) -> Self
+            where
+                Self: Sized,
+            {
+                #deserialize
+            }
+        }
+    })
+}
+
+pub(crate) fn handler(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(output) => output.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
 }
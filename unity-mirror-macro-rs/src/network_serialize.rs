@@ -0,0 +1,57 @@
+use crate::utils::serialize_fields::{bounded_generics, deserialize_body, serialize_body};
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, DeriveInput};
+
+// 与 Weaver 生成的读写函数一致：结构体按字段声明顺序，枚举先写 repr 类型的标签
+// 字段属性 #[network(skip)]、#[network(with = path)]
+// 作为 Rpc / Command 参数时需要 #[namespace] 提供 C# 全名
+pub(crate) fn serialize_handler(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let ident = &input.ident;
+    let body = match serialize_body(&input, "network", "NetworkSerialize") {
+        Ok(body) => body,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let generics = bounded_generics(&input, quote! { DataTypeSerializer });
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics DataTypeSerializer for #ident #ty_generics #where_clause {
+            fn serialize(&self, writer: &mut NetworkWriter)
+            where
+                Self: Sized,
+            {
+                #body
+            }
+        }
+    }
+    .into()
+}
+
+pub(crate) fn deserialize_handler(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let ident = &input.ident;
+    let body = match deserialize_body(&input, "network", "NetworkDeserialize") {
+        Ok(body) => body,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    // 枚举读到未知标签时第一个变体的字段取 Default
+    let generics = match input.data {
+        syn::Data::Enum(_) => bounded_generics(&input, quote! { DataTypeDeserializer + Default }),
+        _ => bounded_generics(&input, quote! { DataTypeDeserializer }),
+    };
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics DataTypeDeserializer for #ident #ty_generics #where_clause {
+            fn deserialize(reader: &mut NetworkReader) -> Self
+            where
+                Self: Sized,
+            {
+                #body
+            }
+        }
+    }
+    .into()
+}
@@ -1,8 +1,8 @@
-use quote::{ToTokens, format_ident, quote};
+use quote::quote;
 use syn::punctuated::Punctuated;
 use syn::{
-    FnArg, GenericArgument, Path, PathArguments, PathSegment, Token, Type, TypeArray, TypePath,
-    TypeReference, TypeSlice,
+    FnArg, GenericArgument, Path, PathArguments, PathSegment, Token, Type, TypeArray, TypeParamBound,
+    TypePath, TypeReference, TypeSlice, TypeTraitObject, TypeTuple,
 };
 
 pub(crate) fn to_csharp_function_inputs(
@@ -13,20 +13,7 @@ pub(crate) fn to_csharp_function_inputs(
         FnArg::Typed(pat_type) => Some(pat_type),
         _ => None,
     }) {
-        match type_to_csharp(&arg.ty) {
-            None => {
-                let param_type_name = arg.ty.to_token_stream().to_string();
-                let param_type = format_ident!("{}", param_type_name);
-                params.push(quote! {
-                    {
-                        #param_type::get_full_name()
-                    }
-                });
-            }
-            Some(r#type) => {
-                params.push(quote! { #r#type.to_string() });
-            }
-        }
+        params.push(type_to_csharp_tokens(&arg.ty));
     }
 
     if params.len() > 0 {
@@ -40,6 +27,53 @@ pub(crate) fn to_csharp_function_inputs(
     }
 }
 
+// 参数类型的 C# 全名表达式，内置类型为字面量，
+// 其余类型 (#[namespace] 声明或派生 NetworkSerialize 的类型) 取 Object::get_full_name，
+// 派生类型缺少 #[namespace] 时报错 "has no C# full name"
+fn type_to_csharp_tokens(r#type: &Type) -> proc_macro2::TokenStream {
+    if let Some(name) = type_to_csharp(r#type) {
+        return quote! { #name.to_string() };
+    }
+    match r#type {
+        Type::Reference(TypeReference { elem, .. }) => type_to_csharp_tokens(elem),
+        Type::Array(TypeArray { elem, .. }) | Type::Slice(TypeSlice { elem, .. }) => {
+            let elem = type_to_csharp_tokens(elem);
+            quote! { format!("{}[]", #elem) }
+        }
+        Type::Tuple(TypeTuple { elems, .. }) if !elems.is_empty() => {
+            let elems = elems.iter().map(type_to_csharp_tokens).collect::<Vec<_>>();
+            let prefix = format!("System.ValueTuple`{}", elems.len());
+            quote! { format!("{}<{}>", #prefix, vec![#(#elems,)*].join(",")) }
+        }
+        Type::Path(TypePath { path, .. }) => {
+            let last_path = path.segments.last().unwrap();
+            let generics = match &last_path.arguments {
+                PathArguments::AngleBracketed(args) => args
+                    .args
+                    .iter()
+                    .filter_map(|arg| match arg {
+                        GenericArgument::Type(ty) => Some(ty),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>(),
+                _ => Vec::new(),
+            };
+            let prefix = match (last_path.ident.to_string().as_str(), generics.len()) {
+                ("RevelArc" | "RevelWeak" | "Box", 1) => return type_to_csharp_tokens(generics[0]),
+                ("Vec", 1) => "System.Collections.Generic.List`1",
+                ("Option", 1) => "System.Nullable`1",
+                // HashMap 的 Hasher 参数不参与 C# 类型名
+                ("HashMap", 2 | 3) => "System.Collections.Generic.Dictionary`2",
+                ("BTreeMap", 2) => "System.Collections.Generic.SortedDictionary`2",
+                _ => return quote! { <#r#type as Object>::get_full_name().to_string() },
+            };
+            let elems = generics.iter().take(2).map(|ty| type_to_csharp_tokens(ty)).collect::<Vec<_>>();
+            quote! { format!("{}<{}>", #prefix, vec![#(#elems,)*].join(",")) }
+        }
+        _ => quote! { <#r#type as Object>::get_full_name().to_string() },
+    }
+}
+
 pub fn type_to_csharp(r#type: &Type) -> Option<String> {
     match r#type {
         Type::Reference(TypeReference { elem, .. }) => type_to_csharp(elem),
@@ -69,9 +103,19 @@ pub fn type_to_csharp(r#type: &Type) -> Option<String> {
                 "NetworkConnectionToClient" => {
                     Some("Mirror.NetworkConnectionToClient".to_string())
                 }
+                "Vector2" => Some("UnityEngine.Vector2".to_string()),
                 "Vector3" | "nalgebra::Vector3" => Some("UnityEngine.Vector3".to_string()),
+                "Vector4" => Some("UnityEngine.Vector4".to_string()),
                 "Quaternion" | "nalgebra::Quaternion" => Some("UnityEngine.Quaternion".to_string()),
+                "Matrix4" => Some("UnityEngine.Matrix4x4".to_string()),
+                "SystemTime" => Some("System.DateTime".to_string()),
+                "Uri" => Some("System.Uri".to_string()),
+                "GameObject" => Some("UnityEngine.GameObject".to_string()),
+                "NetworkIdentity" => Some("Mirror.NetworkIdentity".to_string()),
                 "Vec" => process_generic_type(last_path, path, "System.Collections.Generic.List`1"),
+                "Option" => process_generic_type(last_path, path, "System.Nullable`1"),
+                "HashMap" => process_generic_type(last_path, path, "System.Collections.Generic.Dictionary`2"),
+                "BTreeMap" => process_generic_type(last_path, path, "System.Collections.Generic.SortedDictionary`2"),
                 "RevelArc" | "RevelWeak" | "Box" => {
                     if let PathSegment {
                         arguments: PathArguments::AngleBracketed(args),
@@ -91,6 +135,19 @@ pub fn type_to_csharp(r#type: &Type) -> Option<String> {
         Type::Array(TypeArray { elem, .. }) | Type::Slice(TypeSlice { elem, .. }) => {
             Some(format!("{}[]", type_to_csharp(elem)?))
         }
+        Type::Tuple(TypeTuple { elems, .. }) if !elems.is_empty() => {
+            let elems = elems.iter().map(type_to_csharp).collect::<Option<Vec<_>>>()?;
+            Some(format!("System.ValueTuple`{}<{}>", elems.len(), elems.join(",")))
+        }
+        // dyn TNetworkBehaviour 引用
+        Type::TraitObject(TypeTraitObject { bounds, .. }) => bounds.iter().find_map(|bound| match bound {
+            TypeParamBound::Trait(bound)
+                if bound.path.segments.last()?.ident == "TNetworkBehaviour" =>
+            {
+                Some("Mirror.NetworkBehaviour".to_string())
+            }
+            _ => None,
+        }),
         _ => None,
     }
 }
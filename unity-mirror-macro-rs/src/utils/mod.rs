@@ -2,6 +2,7 @@ pub(crate) mod random_string;
 pub(crate) mod string_case;
 
 pub(crate) mod csharp;
+pub(crate) mod serialize_fields;
 
 #[allow(unused)]
 pub fn write_to_file(prefix: &str, value: String) {
@@ -0,0 +1,283 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::parse::{Parse, ParseStream};
+use syn::spanned::Spanned;
+use syn::{parse_quote, Data, DeriveInput, Expr, Fields, Generics, Ident, Path, Token};
+
+// 按 Weaver 的顺序读写字段，NetworkMessage 与 NetworkSerialize / NetworkDeserialize 共用
+// 字段属性 (attr 为 message 或 network)：
+// #[attr(skip)]
+// #[attr(with = path)]  path::serialize(&value, writer) / path::deserialize(reader)
+// #[attr(serialize_with = path, deserialize_with = path)]
+
+mod kw {
+    syn::custom_keyword!(skip);
+    syn::custom_keyword!(with);
+    syn::custom_keyword!(serialize_with);
+    syn::custom_keyword!(deserialize_with);
+}
+
+#[derive(Default)]
+struct FieldArgs {
+    skip: bool,
+    serialize_with: Option<Path>,
+    deserialize_with: Option<Path>,
+}
+
+impl Parse for FieldArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = FieldArgs::default();
+        while !input.is_empty() {
+            if input.peek(kw::skip) {
+                input.parse::<kw::skip>()?;
+                args.skip = true;
+            } else if input.peek(kw::with) {
+                input.parse::<kw::with>()?;
+                input.parse::<Token![=]>()?;
+                let path: Path = input.parse()?;
+                args.serialize_with = Some(syn::parse_quote! { #path::serialize });
+                args.deserialize_with = Some(syn::parse_quote! { #path::deserialize });
+            } else if input.peek(kw::serialize_with) {
+                input.parse::<kw::serialize_with>()?;
+                input.parse::<Token![=]>()?;
+                args.serialize_with = Some(input.parse()?);
+            } else if input.peek(kw::deserialize_with) {
+                input.parse::<kw::deserialize_with>()?;
+                input.parse::<Token![=]>()?;
+                args.deserialize_with = Some(input.parse()?);
+            } else {
+                return Err(input.error("Expected `skip`, `with`, `serialize_with` or `deserialize_with`"));
+            }
+            if input.peek(Token![,]) {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        Ok(args)
+    }
+}
+
+fn field_args(field: &syn::Field, attr_name: &str) -> syn::Result<FieldArgs> {
+    for attr in field.attrs.iter() {
+        if attr.path().is_ident(attr_name) {
+            return attr.parse_args::<FieldArgs>();
+        }
+    }
+    Ok(FieldArgs::default())
+}
+
+// 按字段顺序写入，values 为各字段的引用
+fn serialize_fields(fields: &Fields, values: &[TokenStream], attr_name: &str) -> syn::Result<TokenStream> {
+    let mut output = Vec::new();
+    for (field, value) in fields.iter().zip(values) {
+        let args = field_args(field, attr_name)?;
+        if args.skip {
+            continue;
+        }
+        let ty = &field.ty;
+        output.push(match args.serialize_with {
+            Some(path) => quote! { #path(#value, writer); },
+            None => quote! { <#ty as DataTypeSerializer>::serialize(#value, writer); },
+        });
+    }
+    Ok(quote! { #(#output)* })
+}
+
+// 按字段顺序读取，跳过的字段使用 Default
+fn deserialize_fields(path: TokenStream, fields: &Fields, attr_name: &str) -> syn::Result<TokenStream> {
+    let mut reads = Vec::new();
+    let mut values = Vec::new();
+    for (index, field) in fields.iter().enumerate() {
+        let args = field_args(field, attr_name)?;
+        let ty = &field.ty;
+        let value = format_ident!("__field_{}", index);
+        reads.push(if args.skip {
+            quote! { let #value: #ty = Default::default(); }
+        } else {
+            match args.deserialize_with {
+                Some(path) => quote! { let #value: #ty = #path(reader); },
+                None => quote! { let #value: #ty = <#ty as DataTypeDeserializer>::deserialize(reader); },
+            }
+        });
+        values.push(value);
+    }
+    let construct = construct_fields(fields, &values);
+    Ok(quote! {
+        {
+            #(#reads)*
+            #path #construct
+        }
+    })
+}
+
+fn construct_fields(fields: &Fields, values: &[Ident]) -> TokenStream {
+    match fields {
+        Fields::Named(named) => {
+            let names = named.named.iter().map(|field| &field.ident);
+            quote! { { #(#names: #values,)* } }
+        }
+        Fields::Unnamed(_) => quote! { (#(#values,)*) },
+        Fields::Unit => quote! {},
+    }
+}
+
+fn repr_type(input: &DeriveInput) -> Option<Ident> {
+    let mut repr = None;
+    for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("repr")) {
+        let _ = attr.parse_nested_meta(|meta| {
+            if let Some(ident) = meta.path.get_ident() {
+                if ["u8", "u16", "u32", "u64", "i8", "i16", "i32", "i64"].contains(&ident.to_string().as_str()) {
+                    repr = Some(ident.clone());
+                }
+            }
+            Ok(())
+        });
+    }
+    repr
+}
+
+// 各变体的标签表达式，未指定的在上一个基础上加 1
+fn variant_tags(input: &DeriveInput, repr: &Ident) -> Vec<Expr> {
+    let Data::Enum(data) = &input.data else {
+        return Vec::new();
+    };
+    let mut tags: Vec<Expr> = Vec::new();
+    for variant in data.variants.iter() {
+        let tag = match (&variant.discriminant, tags.last()) {
+            (Some((_, expr)), _) => syn::parse_quote! { (#expr) as #repr },
+            (None, Some(previous)) => syn::parse_quote! { #previous + 1 },
+            (None, None) => syn::parse_quote! { 0 as #repr },
+        };
+        tags.push(tag);
+    }
+    tags
+}
+
+fn enum_repr(input: &DeriveInput, derive_name: &str) -> syn::Result<Ident> {
+    repr_type(input).ok_or_else(|| {
+        syn::Error::new(
+            input.span(),
+            format!("{} enums require an integer #[repr(..)] for the tag", derive_name),
+        )
+    })
+}
+
+/// 写入 self 的函数体，枚举先用 repr 类型的序列化写标签，再写该变体的字段
+pub(crate) fn serialize_body(input: &DeriveInput, attr_name: &str, derive_name: &str) -> syn::Result<TokenStream> {
+    match &input.data {
+        Data::Struct(data) => {
+            let values = data
+                .fields
+                .iter()
+                .enumerate()
+                .map(|(index, field)| match &field.ident {
+                    Some(name) => quote! { &self.#name },
+                    None => {
+                        let index = syn::Index::from(index);
+                        quote! { &self.#index }
+                    }
+                })
+                .collect::<Vec<_>>();
+            serialize_fields(&data.fields, &values, attr_name)
+        }
+        Data::Enum(data) => {
+            let repr = enum_repr(input, derive_name)?;
+            let mut arms = Vec::new();
+            for (variant, tag) in data.variants.iter().zip(variant_tags(input, &repr)) {
+                let variant_ident = &variant.ident;
+                let values = (0..variant.fields.len())
+                    .map(|index| format_ident!("__field_{}", index))
+                    .collect::<Vec<_>>();
+                let pattern = construct_fields(&variant.fields, &values);
+                let refs = values.iter().map(|value| quote! { #value }).collect::<Vec<_>>();
+                let write_fields = serialize_fields(&variant.fields, &refs, attr_name)?;
+                arms.push(quote! {
+                    Self::#variant_ident #pattern => {
+                        <#repr as DataTypeSerializer>::serialize(&(#tag), writer);
+                        #write_fields
+                    }
+                });
+            }
+            Ok(quote! {
+                #[allow(unused_variables)]
+                match &*self {
+                    #(#arms)*
+                }
+            })
+        }
+        Data::Union(_) => Err(syn::Error::new(
+            input.span(),
+            format!("{} cannot be derived for unions", derive_name),
+        )),
+    }
+}
+
+/// 读取并返回 Self 的函数体，未知标签时记录 InvalidValue 并返回第一个变体 (字段为 Default)
+pub(crate) fn deserialize_body(input: &DeriveInput, attr_name: &str, derive_name: &str) -> syn::Result<TokenStream> {
+    match &input.data {
+        Data::Struct(data) => deserialize_fields(quote! { Self }, &data.fields, attr_name),
+        Data::Enum(data) => {
+            let repr = enum_repr(input, derive_name)?;
+            let mut arms = Vec::new();
+            for (variant, tag) in data.variants.iter().zip(variant_tags(input, &repr)) {
+                let variant_ident = &variant.ident;
+                let read_fields = deserialize_fields(quote! { Self::#variant_ident }, &variant.fields, attr_name)?;
+                arms.push(quote! {
+                    tag if tag == #tag => #read_fields,
+                });
+            }
+            let fallback = match data.variants.first() {
+                Some(variant) => {
+                    let variant_ident = &variant.ident;
+                    let values = variant
+                        .fields
+                        .iter()
+                        .map(|_| quote! { Default::default() })
+                        .collect::<Vec<_>>();
+                    match &variant.fields {
+                        Fields::Named(named) => {
+                            let names = named.named.iter().map(|field| &field.ident);
+                            quote! { Self::#variant_ident { #(#names: #values,)* } }
+                        }
+                        Fields::Unnamed(_) => quote! { Self::#variant_ident(#(#values,)*) },
+                        Fields::Unit => quote! { Self::#variant_ident },
+                    }
+                }
+                None => {
+                    return Err(syn::Error::new(
+                        input.span(),
+                        format!("{} cannot be derived for empty enums", derive_name),
+                    ))
+                }
+            };
+            Ok(quote! {
+                match <#repr as DataTypeDeserializer>::deserialize(reader) {
+                    #(#arms)*
+                    _ => {
+                        reader.fail(ReadError::InvalidValue {
+                            type_name: std::any::type_name::<Self>(),
+                        });
+                        #fallback
+                    }
+                }
+            })
+        }
+        Data::Union(_) => Err(syn::Error::new(
+            input.span(),
+            format!("{} cannot be derived for unions", derive_name),
+        )),
+    }
+}
+
+/// 为每个类型参数加上读写字段需要的约束，例如 Wrapper<T> 要求 T: DataTypeSerializer
+pub(crate) fn bounded_generics(input: &DeriveInput, bounds: TokenStream) -> Generics {
+    let mut generics = input.generics.clone();
+    let type_params = generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect::<Vec<_>>();
+    let where_clause = generics.make_where_clause();
+    for ident in type_params {
+        where_clause.predicates.push(parse_quote! { #ident: #bounds });
+    }
+    generics
+}